@@ -0,0 +1,45 @@
+use std::env;
+use std::path::PathBuf;
+
+/// Find the directories containing the SoapySDR C++ headers, using the same environment
+/// variables and pkg-config package as `soapysdr-sys`.
+fn include_dirs() -> Vec<PathBuf> {
+    if let Some(paths) = env::var_os("SOAPY_SDR_ROOT").or_else(|| env::var_os("SoapySDR_DIR")) {
+        let dirs: Vec<PathBuf> = env::split_paths(&paths)
+            .map(|path| path.join("include"))
+            .filter(|path| path.join("SoapySDR").is_dir())
+            .collect();
+        if !dirs.is_empty() {
+            return dirs;
+        }
+    }
+
+    match pkg_config::Config::new()
+        .atleast_version("0.8.0")
+        .cargo_metadata(false)
+        .probe("SoapySDR")
+    {
+        Ok(lib) => lib.include_paths,
+        Err(e) => {
+            eprintln!("pkg_config: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=shim/module.cpp");
+    println!("cargo:rerun-if-env-changed=SOAPY_SDR_ROOT");
+    println!("cargo:rerun-if-env-changed=SoapySDR_DIR");
+
+    if env::var_os("DOCS_RS").is_some() {
+        return;
+    }
+
+    let mut build = cc::Build::new();
+    build.cpp(true).std("c++14").file("shim/module.cpp");
+    for dir in include_dirs() {
+        build.include(dir);
+    }
+    build.compile("soapysdr_driver_shim");
+}
@@ -0,0 +1,225 @@
+//! A SoapySDR module with a receive-only device that produces a complex tone.
+//!
+//! Build with `cargo build --example tone_driver`, then load the resulting library with
+//! `soapysdr::modules::load` or copy it into a SoapySDR module directory, and open the device
+//! with `driver=rust_tone`.
+
+use std::f64::consts::TAU;
+use std::sync::{Arc, Mutex};
+
+use num_complex::Complex;
+use soapysdr::{
+    Args, Direction, Error, ErrorCode, Format, HardwareTime, Range, RxMetadata, StreamFlags,
+};
+use soapysdr_driver::{DeviceImpl, Driver, StreamImpl, register_driver};
+
+/// Frequency of the tone relative to the tuned center frequency.
+const TONE_OFFSET: f64 = 100e3;
+
+struct ToneDriver;
+
+impl Driver for ToneDriver {
+    fn find(&self, args: &Args) -> Vec<Args> {
+        match args.get("driver") {
+            Some(driver) if driver != "rust_tone" => Vec::new(),
+            _ => vec!["driver=rust_tone, label=Rust tone generator".into()],
+        }
+    }
+
+    fn make(&self, _args: &Args) -> Result<Box<dyn DeviceImpl>, Error> {
+        Ok(Box::new(ToneDevice {
+            state: Arc::new(Mutex::new(State {
+                frequency: 100e6,
+                sample_rate: 1e6,
+                gain: 0.0,
+            })),
+        }))
+    }
+}
+
+struct State {
+    frequency: f64,
+    sample_rate: f64,
+    gain: f64,
+}
+
+struct ToneDevice {
+    state: Arc<Mutex<State>>,
+}
+
+fn rx_only(direction: Direction) -> Result<(), Error> {
+    match direction {
+        Direction::Rx => Ok(()),
+        Direction::Tx => Err(Error {
+            code: ErrorCode::NotSupported,
+            message: "Transmit not supported".into(),
+        }),
+    }
+}
+
+impl DeviceImpl for ToneDevice {
+    fn driver_key(&self) -> Result<String, Error> {
+        Ok("rust_tone".into())
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        Ok("tone".into())
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        Ok(if direction == Direction::Rx { 1 } else { 0 })
+    }
+
+    fn native_stream_format(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        Ok((Format::CF32, 1.0))
+    }
+
+    fn setup_stream(
+        &self,
+        direction: Direction,
+        format: Format,
+        _channels: &[usize],
+        _args: &Args,
+    ) -> Result<Box<dyn StreamImpl>, Error> {
+        rx_only(direction)?;
+        if format != Format::CF32 {
+            return Err(Error {
+                code: ErrorCode::NotSupported,
+                message: format!("Unsupported format {}", format),
+            });
+        }
+        Ok(Box::new(ToneStream {
+            state: self.state.clone(),
+            sample: 0,
+        }))
+    }
+
+    fn antennas(&self, _direction: Direction, _channel: usize) -> Result<Vec<String>, Error> {
+        Ok(vec!["RX".into()])
+    }
+
+    fn antenna(&self, _direction: Direction, _channel: usize) -> Result<String, Error> {
+        Ok("RX".into())
+    }
+
+    fn list_gains(&self, _direction: Direction, _channel: usize) -> Result<Vec<String>, Error> {
+        Ok(vec!["DIGITAL".into()])
+    }
+
+    fn set_gain_element(
+        &self,
+        direction: Direction,
+        _channel: usize,
+        _name: &str,
+        gain: f64,
+    ) -> Result<(), Error> {
+        rx_only(direction)?;
+        self.state.lock().unwrap().gain = gain.clamp(-40.0, 0.0);
+        Ok(())
+    }
+
+    fn gain_element(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+    ) -> Result<f64, Error> {
+        Ok(self.state.lock().unwrap().gain)
+    }
+
+    fn gain_element_range(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+    ) -> Result<Range, Error> {
+        Ok(Range::new(-40.0, 0.0, 1.0))
+    }
+
+    fn set_frequency(
+        &self,
+        direction: Direction,
+        _channel: usize,
+        frequency: f64,
+        _args: &Args,
+    ) -> Result<(), Error> {
+        rx_only(direction)?;
+        self.state.lock().unwrap().frequency = frequency;
+        Ok(())
+    }
+
+    fn frequency(&self, _direction: Direction, _channel: usize) -> Result<f64, Error> {
+        Ok(self.state.lock().unwrap().frequency)
+    }
+
+    fn frequency_range(&self, _direction: Direction, _channel: usize) -> Result<Vec<Range>, Error> {
+        Ok(vec![Range::new(1e6, 6e9, 0.0)])
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        _channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        rx_only(direction)?;
+        self.state.lock().unwrap().sample_rate = rate;
+        Ok(())
+    }
+
+    fn sample_rate(&self, _direction: Direction, _channel: usize) -> Result<f64, Error> {
+        Ok(self.state.lock().unwrap().sample_rate)
+    }
+
+    fn sample_rate_range(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        Ok(vec![Range::new(250e3, 10e6, 0.0)])
+    }
+}
+
+struct ToneStream {
+    state: Arc<Mutex<State>>,
+    sample: i64,
+}
+
+impl StreamImpl for ToneStream {
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(4096)
+    }
+
+    fn read(&mut self, buffers: &mut [&mut [u8]], _timeout_us: i64) -> Result<RxMetadata, Error> {
+        let (rate, amplitude) = {
+            let state = self.state.lock().unwrap();
+            (state.sample_rate, 10f64.powf(state.gain / 20.0))
+        };
+        let size = Format::CF32.size();
+        let len = buffers[0].len() / size;
+        for i in 0..len {
+            let phase = TAU * TONE_OFFSET * (self.sample + i as i64) as f64 / rate;
+            let sample = Complex::from_polar(amplitude, phase);
+            let bytes = [
+                (sample.re as f32).to_ne_bytes(),
+                (sample.im as f32).to_ne_bytes(),
+            ]
+            .concat();
+            buffers[0][i * size..(i + 1) * size].copy_from_slice(&bytes);
+        }
+
+        let time = HardwareTime::from_samples(self.sample, rate);
+        self.sample += len as i64;
+        Ok(RxMetadata {
+            len,
+            flags: StreamFlags::HAS_TIME,
+            raw_time: time,
+        })
+    }
+}
+
+register_driver!("rust_tone", ToneDriver);
@@ -0,0 +1,498 @@
+// Implements SoapySDR::Device by forwarding each call to the Rust driver traits.
+//
+// The soapy_rust_* functions are defined in src/ffi.rs. Control calls return nonzero on failure
+// and store the message in `err`, which is thrown here so SoapySDR reports it like any other
+// driver error. Stream calls return SoapySDR stream error codes directly.
+
+#include <SoapySDR/Device.hpp>
+#include <SoapySDR/Logger.hpp>
+#include <SoapySDR/Registry.hpp>
+#include <SoapySDR/Version.hpp>
+
+#include <cstddef>
+#include <memory>
+#include <stdexcept>
+#include <string>
+#include <vector>
+
+using SoapySDR::Kwargs;
+using SoapySDR::KwargsList;
+using SoapySDR::Range;
+using SoapySDR::RangeList;
+
+typedef void (*KwargsVisitor)(void *ctx, const char *key, const char *value);
+
+extern "C" {
+
+// Called from Rust to read arguments and fill in results.
+
+void soapy_shim_kwargs_visit(const void *kwargs, void *ctx, KwargsVisitor visit) noexcept
+{
+    for (const auto &pair : *static_cast<const Kwargs *>(kwargs))
+        visit(ctx, pair.first.c_str(), pair.second.c_str());
+}
+
+void soapy_shim_kwargs_set(void *kwargs, const char *key, size_t key_len, const char *value, size_t value_len) noexcept
+{
+    (*static_cast<Kwargs *>(kwargs))[std::string(key, key_len)] = std::string(value, value_len);
+}
+
+void *soapy_shim_kwargs_list_push(void *list) noexcept
+{
+    auto &l = *static_cast<KwargsList *>(list);
+    l.emplace_back();
+    return &l.back();
+}
+
+void soapy_shim_string_set(void *out, const char *s, size_t len) noexcept
+{
+    static_cast<std::string *>(out)->assign(s, len);
+}
+
+void soapy_shim_strings_push(void *out, const char *s, size_t len) noexcept
+{
+    static_cast<std::vector<std::string> *>(out)->emplace_back(s, len);
+}
+
+void soapy_shim_ranges_push(void *out, double minimum, double maximum, double step) noexcept
+{
+    static_cast<RangeList *>(out)->emplace_back(minimum, maximum, step);
+}
+
+// Implemented in Rust.
+
+int soapy_rust_find(const void *args, void *results, void *err);
+int soapy_rust_make(const void *args, void **device, void *err);
+void soapy_rust_device_drop(void *device);
+
+int soapy_rust_driver_key(const void *device, void *out, void *err);
+int soapy_rust_hardware_key(const void *device, void *out, void *err);
+int soapy_rust_hardware_info(const void *device, void *out, void *err);
+
+int soapy_rust_num_channels(const void *device, int dir, size_t *out, void *err);
+int soapy_rust_full_duplex(const void *device, int dir, size_t ch, bool *out, void *err);
+
+int soapy_rust_stream_formats(const void *device, int dir, size_t ch, void *out, void *err);
+int soapy_rust_native_stream_format(const void *device, int dir, size_t ch, void *out, double *full_scale, void *err);
+int soapy_rust_setup_stream(const void *device, int dir, const char *format, const size_t *channels, size_t num_channels, const void *args, void **stream, void *err);
+void soapy_rust_stream_drop(void *stream);
+int soapy_rust_stream_mtu(const void *stream, size_t *out, void *err);
+int soapy_rust_stream_activate(void *stream, int flags, long long time_ns, size_t num_elems);
+int soapy_rust_stream_deactivate(void *stream, int flags, long long time_ns);
+int soapy_rust_stream_read(void *stream, void *const *buffs, size_t num_elems, int *flags, long long *time_ns, long timeout_us);
+int soapy_rust_stream_write(void *stream, const void *const *buffs, size_t num_elems, int flags, long long time_ns, long timeout_us);
+
+int soapy_rust_list_antennas(const void *device, int dir, size_t ch, void *out, void *err);
+int soapy_rust_set_antenna(const void *device, int dir, size_t ch, const char *name, void *err);
+int soapy_rust_antenna(const void *device, int dir, size_t ch, void *out, void *err);
+
+int soapy_rust_list_gains(const void *device, int dir, size_t ch, void *out, void *err);
+int soapy_rust_has_gain_mode(const void *device, int dir, size_t ch, bool *out, void *err);
+int soapy_rust_set_gain_mode(const void *device, int dir, size_t ch, bool automatic, void *err);
+int soapy_rust_gain_mode(const void *device, int dir, size_t ch, bool *out, void *err);
+int soapy_rust_set_gain(const void *device, int dir, size_t ch, double value, void *err);
+int soapy_rust_gain(const void *device, int dir, size_t ch, double *out, void *err);
+int soapy_rust_gain_range(const void *device, int dir, size_t ch, double *out, void *err);
+int soapy_rust_set_gain_element(const void *device, int dir, size_t ch, const char *name, double value, void *err);
+int soapy_rust_gain_element(const void *device, int dir, size_t ch, const char *name, double *out, void *err);
+int soapy_rust_gain_element_range(const void *device, int dir, size_t ch, const char *name, double *out, void *err);
+
+int soapy_rust_set_frequency(const void *device, int dir, size_t ch, double frequency, const void *args, void *err);
+int soapy_rust_frequency(const void *device, int dir, size_t ch, double *out, void *err);
+int soapy_rust_frequency_range(const void *device, int dir, size_t ch, void *out, void *err);
+
+int soapy_rust_set_sample_rate(const void *device, int dir, size_t ch, double rate, void *err);
+int soapy_rust_sample_rate(const void *device, int dir, size_t ch, double *out, void *err);
+int soapy_rust_sample_rate_range(const void *device, int dir, size_t ch, void *out, void *err);
+
+int soapy_rust_set_bandwidth(const void *device, int dir, size_t ch, double bandwidth, void *err);
+int soapy_rust_bandwidth(const void *device, int dir, size_t ch, double *out, void *err);
+int soapy_rust_bandwidth_range(const void *device, int dir, size_t ch, void *out, void *err);
+
+int soapy_rust_has_hardware_time(const void *device, const char *what, bool *out, void *err);
+int soapy_rust_hardware_time(const void *device, const char *what, long long *out, void *err);
+int soapy_rust_set_hardware_time(const void *device, const char *what, long long time_ns, void *err);
+
+} // extern "C"
+
+namespace {
+
+void check(const int ret, const std::string &err)
+{
+    if (ret != 0) throw std::runtime_error(err);
+}
+
+Range toRange(const double *r)
+{
+    return Range(r[0], r[1], r[2]);
+}
+
+class RustDevice : public SoapySDR::Device
+{
+public:
+    explicit RustDevice(void *device):
+        _device(device)
+    {
+    }
+
+    ~RustDevice(void)
+    {
+        soapy_rust_device_drop(_device);
+    }
+
+    using SoapySDR::Device::setFrequency;
+    using SoapySDR::Device::getFrequency;
+    using SoapySDR::Device::getFrequencyRange;
+
+    /*******************************************************************
+     * Identification API
+     ******************************************************************/
+
+    std::string getDriverKey(void) const override
+    {
+        std::string out, err;
+        check(soapy_rust_driver_key(_device, &out, &err), err);
+        return out;
+    }
+
+    std::string getHardwareKey(void) const override
+    {
+        std::string out, err;
+        check(soapy_rust_hardware_key(_device, &out, &err), err);
+        return out;
+    }
+
+    Kwargs getHardwareInfo(void) const override
+    {
+        Kwargs out;
+        std::string err;
+        check(soapy_rust_hardware_info(_device, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Channels API
+     ******************************************************************/
+
+    size_t getNumChannels(const int dir) const override
+    {
+        size_t out = 0;
+        std::string err;
+        check(soapy_rust_num_channels(_device, dir, &out, &err), err);
+        return out;
+    }
+
+    bool getFullDuplex(const int dir, const size_t ch) const override
+    {
+        bool out = false;
+        std::string err;
+        check(soapy_rust_full_duplex(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Stream API
+     ******************************************************************/
+
+    std::vector<std::string> getStreamFormats(const int dir, const size_t ch) const override
+    {
+        std::vector<std::string> out;
+        std::string err;
+        check(soapy_rust_stream_formats(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    std::string getNativeStreamFormat(const int dir, const size_t ch, double &fullScale) const override
+    {
+        std::string out, err;
+        check(soapy_rust_native_stream_format(_device, dir, ch, &out, &fullScale, &err), err);
+        return out;
+    }
+
+    SoapySDR::Stream *setupStream(const int dir, const std::string &format, const std::vector<size_t> &channels, const Kwargs &args) override
+    {
+        void *stream = nullptr;
+        std::string err;
+        check(soapy_rust_setup_stream(_device, dir, format.c_str(), channels.data(), channels.size(), &args, &stream, &err), err);
+        return reinterpret_cast<SoapySDR::Stream *>(stream);
+    }
+
+    void closeStream(SoapySDR::Stream *stream) override
+    {
+        soapy_rust_stream_drop(stream);
+    }
+
+    size_t getStreamMTU(SoapySDR::Stream *stream) const override
+    {
+        size_t out = 0;
+        std::string err;
+        check(soapy_rust_stream_mtu(stream, &out, &err), err);
+        return out;
+    }
+
+    int activateStream(SoapySDR::Stream *stream, const int flags, const long long timeNs, const size_t numElems) override
+    {
+        return soapy_rust_stream_activate(stream, flags, timeNs, numElems);
+    }
+
+    int deactivateStream(SoapySDR::Stream *stream, const int flags, const long long timeNs) override
+    {
+        return soapy_rust_stream_deactivate(stream, flags, timeNs);
+    }
+
+    int readStream(SoapySDR::Stream *stream, void *const *buffs, const size_t numElems, int &flags, long long &timeNs, const long timeoutUs) override
+    {
+        return soapy_rust_stream_read(stream, buffs, numElems, &flags, &timeNs, timeoutUs);
+    }
+
+    int writeStream(SoapySDR::Stream *stream, const void *const *buffs, const size_t numElems, int &flags, const long long timeNs, const long timeoutUs) override
+    {
+        return soapy_rust_stream_write(stream, buffs, numElems, flags, timeNs, timeoutUs);
+    }
+
+    /*******************************************************************
+     * Antenna API
+     ******************************************************************/
+
+    std::vector<std::string> listAntennas(const int dir, const size_t ch) const override
+    {
+        std::vector<std::string> out;
+        std::string err;
+        check(soapy_rust_list_antennas(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    void setAntenna(const int dir, const size_t ch, const std::string &name) override
+    {
+        std::string err;
+        check(soapy_rust_set_antenna(_device, dir, ch, name.c_str(), &err), err);
+    }
+
+    std::string getAntenna(const int dir, const size_t ch) const override
+    {
+        std::string out, err;
+        check(soapy_rust_antenna(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Gain API
+     ******************************************************************/
+
+    std::vector<std::string> listGains(const int dir, const size_t ch) const override
+    {
+        std::vector<std::string> out;
+        std::string err;
+        check(soapy_rust_list_gains(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    bool hasGainMode(const int dir, const size_t ch) const override
+    {
+        bool out = false;
+        std::string err;
+        check(soapy_rust_has_gain_mode(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    void setGainMode(const int dir, const size_t ch, const bool automatic) override
+    {
+        std::string err;
+        check(soapy_rust_set_gain_mode(_device, dir, ch, automatic, &err), err);
+    }
+
+    bool getGainMode(const int dir, const size_t ch) const override
+    {
+        bool out = false;
+        std::string err;
+        check(soapy_rust_gain_mode(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    void setGain(const int dir, const size_t ch, const double value) override
+    {
+        std::string err;
+        check(soapy_rust_set_gain(_device, dir, ch, value, &err), err);
+    }
+
+    void setGain(const int dir, const size_t ch, const std::string &name, const double value) override
+    {
+        std::string err;
+        check(soapy_rust_set_gain_element(_device, dir, ch, name.c_str(), value, &err), err);
+    }
+
+    double getGain(const int dir, const size_t ch) const override
+    {
+        double out = 0.0;
+        std::string err;
+        check(soapy_rust_gain(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    double getGain(const int dir, const size_t ch, const std::string &name) const override
+    {
+        double out = 0.0;
+        std::string err;
+        check(soapy_rust_gain_element(_device, dir, ch, name.c_str(), &out, &err), err);
+        return out;
+    }
+
+    Range getGainRange(const int dir, const size_t ch) const override
+    {
+        double out[3] = {0.0, 0.0, 0.0};
+        std::string err;
+        check(soapy_rust_gain_range(_device, dir, ch, out, &err), err);
+        return toRange(out);
+    }
+
+    Range getGainRange(const int dir, const size_t ch, const std::string &name) const override
+    {
+        double out[3] = {0.0, 0.0, 0.0};
+        std::string err;
+        check(soapy_rust_gain_element_range(_device, dir, ch, name.c_str(), out, &err), err);
+        return toRange(out);
+    }
+
+    /*******************************************************************
+     * Frequency API
+     ******************************************************************/
+
+    void setFrequency(const int dir, const size_t ch, const double frequency, const Kwargs &args) override
+    {
+        std::string err;
+        check(soapy_rust_set_frequency(_device, dir, ch, frequency, &args, &err), err);
+    }
+
+    double getFrequency(const int dir, const size_t ch) const override
+    {
+        double out = 0.0;
+        std::string err;
+        check(soapy_rust_frequency(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    RangeList getFrequencyRange(const int dir, const size_t ch) const override
+    {
+        RangeList out;
+        std::string err;
+        check(soapy_rust_frequency_range(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Sample Rate API
+     ******************************************************************/
+
+    void setSampleRate(const int dir, const size_t ch, const double rate) override
+    {
+        std::string err;
+        check(soapy_rust_set_sample_rate(_device, dir, ch, rate, &err), err);
+    }
+
+    double getSampleRate(const int dir, const size_t ch) const override
+    {
+        double out = 0.0;
+        std::string err;
+        check(soapy_rust_sample_rate(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    RangeList getSampleRateRange(const int dir, const size_t ch) const override
+    {
+        RangeList out;
+        std::string err;
+        check(soapy_rust_sample_rate_range(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Bandwidth API
+     ******************************************************************/
+
+    void setBandwidth(const int dir, const size_t ch, const double bandwidth) override
+    {
+        std::string err;
+        check(soapy_rust_set_bandwidth(_device, dir, ch, bandwidth, &err), err);
+    }
+
+    double getBandwidth(const int dir, const size_t ch) const override
+    {
+        double out = 0.0;
+        std::string err;
+        check(soapy_rust_bandwidth(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    RangeList getBandwidthRange(const int dir, const size_t ch) const override
+    {
+        RangeList out;
+        std::string err;
+        check(soapy_rust_bandwidth_range(_device, dir, ch, &out, &err), err);
+        return out;
+    }
+
+    /*******************************************************************
+     * Time API
+     ******************************************************************/
+
+    bool hasHardwareTime(const std::string &what) const override
+    {
+        bool out = false;
+        std::string err;
+        check(soapy_rust_has_hardware_time(_device, what.c_str(), &out, &err), err);
+        return out;
+    }
+
+    long long getHardwareTime(const std::string &what) const override
+    {
+        long long out = 0;
+        std::string err;
+        check(soapy_rust_hardware_time(_device, what.c_str(), &out, &err), err);
+        return out;
+    }
+
+    void setHardwareTime(const long long timeNs, const std::string &what) override
+    {
+        std::string err;
+        check(soapy_rust_set_hardware_time(_device, what.c_str(), timeNs, &err), err);
+    }
+
+private:
+    void *_device;
+};
+
+KwargsList findRust(const Kwargs &args)
+{
+    KwargsList results;
+    std::string err;
+    if (soapy_rust_find(&args, &results, &err) != 0)
+    {
+        SoapySDR::logf(SOAPY_SDR_ERROR, "find failed: %s", err.c_str());
+    }
+    return results;
+}
+
+SoapySDR::Device *makeRust(const Kwargs &args)
+{
+    void *device = nullptr;
+    std::string err;
+    check(soapy_rust_make(&args, &device, &err), err);
+    return new RustDevice(device);
+}
+
+// Destroyed when the module is unloaded, removing the driver from the SoapySDR registry.
+std::unique_ptr<SoapySDR::Registry> registration;
+
+} // namespace
+
+extern "C" void soapy_shim_register(const char *name) noexcept
+{
+    try
+    {
+        registration.reset(new SoapySDR::Registry(name, &findRust, &makeRust, SOAPY_SDR_ABI_VERSION));
+    }
+    catch (const std::exception &ex)
+    {
+        SoapySDR::logf(SOAPY_SDR_ERROR, "Failed to register driver %s: %s", name, ex.what());
+    }
+}
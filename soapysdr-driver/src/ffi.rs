@@ -0,0 +1,880 @@
+//! Functions called by the C++ shim in `shim/module.cpp`, and the shim functions they use to
+//! read arguments and return results.
+
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_int, c_long, c_longlong, c_void};
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::{Mutex, OnceLock};
+
+use soapysdr::{Args, Direction, Error, ErrorCode, Format, HardwareTime, Range, StreamFlags};
+use soapysdr_sys::{
+    SOAPY_SDR_ERROR, SOAPY_SDR_STREAM_ERROR, SOAPY_SDR_TX, SoapySDR_log, SoapySDRLogLevel,
+};
+
+use crate::{DeviceImpl, Driver, StreamImpl};
+
+type KwargsVisitor = unsafe extern "C" fn(*mut c_void, *const c_char, *const c_char);
+
+unsafe extern "C" {
+    fn soapy_shim_register(name: *const c_char);
+    fn soapy_shim_kwargs_visit(kwargs: *const c_void, ctx: *mut c_void, visit: KwargsVisitor);
+    fn soapy_shim_kwargs_set(
+        kwargs: *mut c_void,
+        key: *const c_char,
+        key_len: usize,
+        value: *const c_char,
+        value_len: usize,
+    );
+    fn soapy_shim_kwargs_list_push(list: *mut c_void) -> *mut c_void;
+    fn soapy_shim_string_set(out: *mut c_void, s: *const c_char, len: usize);
+    fn soapy_shim_strings_push(out: *mut c_void, s: *const c_char, len: usize);
+    fn soapy_shim_ranges_push(out: *mut c_void, minimum: f64, maximum: f64, step: f64);
+}
+
+/// Log a message through SoapySDR's logger, like the shim's `SoapySDR::logf` calls.
+fn log(level: SoapySDRLogLevel, message: &str) {
+    let message = CString::new(format!("soapysdr-driver: {message}").replace('\0', ""))
+        .expect("null bytes were removed");
+    unsafe { SoapySDR_log(level, message.as_ptr()) }
+}
+
+static DRIVER: OnceLock<Box<dyn Driver>> = OnceLock::new();
+
+/// Called by [`register_driver!`](crate::register_driver) when the module is loaded.
+pub fn register(name: &str, driver: Box<dyn Driver>) {
+    if DRIVER.set(driver).is_err() {
+        log(
+            SOAPY_SDR_ERROR,
+            &format!("only one driver can be registered per module; ignoring {name:?}"),
+        );
+        return;
+    }
+    let name = CString::new(name).expect("driver name must not contain null byte");
+    unsafe { soapy_shim_register(name.as_ptr()) }
+}
+
+struct StreamHandle {
+    stream: Mutex<Box<dyn StreamImpl>>,
+    sample_size: usize,
+    num_channels: usize,
+}
+
+fn direction(dir: c_int) -> Direction {
+    if dir == SOAPY_SDR_TX as c_int {
+        Direction::Tx
+    } else {
+        Direction::Rx
+    }
+}
+
+fn optional_time(flags: c_int, time_ns: c_longlong) -> Option<HardwareTime> {
+    StreamFlags::from_bits(flags)
+        .contains(StreamFlags::HAS_TIME)
+        .then(|| HardwareTime::from_ns(time_ns))
+}
+
+/// Convert a timeout from C, where `long` is 32 bits on Windows.
+#[allow(clippy::useless_conversion)]
+fn timeout(us: c_long) -> i64 {
+    us.into()
+}
+
+fn panic_error(payload: Box<dyn Any + Send>) -> Error {
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".into());
+    Error {
+        code: ErrorCode::Other,
+        message: format!("Driver panicked: {}", message),
+    }
+}
+
+/// Run `f`, catching panics so they don't unwind into C++.
+fn catch<T>(f: impl FnOnce() -> Result<T, Error>) -> Result<T, Error> {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or_else(|payload| Err(panic_error(payload)))
+}
+
+/// The SoapySDR status code for an error. Never 0, so it can't be mistaken for success.
+fn error_code(e: &Error) -> c_int {
+    match e.code as c_int {
+        0 => SOAPY_SDR_STREAM_ERROR,
+        code => code,
+    }
+}
+
+/// Run a control call, returning 0 on success or storing the error message in `err`.
+fn status(err: *mut c_void, f: impl FnOnce() -> Result<(), Error>) -> c_int {
+    match catch(f) {
+        Ok(()) => 0,
+        Err(e) => {
+            set_string(err, &e.message);
+            error_code(&e)
+        }
+    }
+}
+
+/// Run a control call that writes its result to `out`.
+fn value<T>(err: *mut c_void, out: *mut T, f: impl FnOnce() -> Result<T, Error>) -> c_int {
+    status(err, || {
+        let v = f()?;
+        unsafe { out.write(v) };
+        Ok(())
+    })
+}
+
+unsafe fn device<'a>(device: *const c_void) -> &'a dyn DeviceImpl {
+    unsafe { &**(device as *const Box<dyn DeviceImpl>) }
+}
+
+unsafe fn stream<'a>(stream: *const c_void) -> &'a StreamHandle {
+    unsafe { &*(stream as *const StreamHandle) }
+}
+
+unsafe fn c_string(s: *const c_char) -> String {
+    unsafe { CStr::from_ptr(s).to_string_lossy().into_owned() }
+}
+
+unsafe extern "C" fn visit_kwarg(ctx: *mut c_void, key: *const c_char, value: *const c_char) {
+    unsafe {
+        let args = &mut *(ctx as *mut Args);
+        args.set(
+            CStr::from_ptr(key).to_bytes(),
+            CStr::from_ptr(value).to_bytes(),
+        );
+    }
+}
+
+unsafe fn read_args(kwargs: *const c_void) -> Args {
+    let mut args = Args::new();
+    unsafe { soapy_shim_kwargs_visit(kwargs, &mut args as *mut Args as *mut c_void, visit_kwarg) };
+    args
+}
+
+fn set_args(out: *mut c_void, args: &Args) {
+    for (key, value) in args {
+        unsafe {
+            soapy_shim_kwargs_set(
+                out,
+                key.as_ptr() as *const c_char,
+                key.len(),
+                value.as_ptr() as *const c_char,
+                value.len(),
+            )
+        }
+    }
+}
+
+fn set_string(out: *mut c_void, s: &str) {
+    unsafe { soapy_shim_string_set(out, s.as_ptr() as *const c_char, s.len()) }
+}
+
+fn push_strings<S: AsRef<str>>(out: *mut c_void, strings: &[S]) {
+    for s in strings {
+        let s = s.as_ref();
+        unsafe { soapy_shim_strings_push(out, s.as_ptr() as *const c_char, s.len()) }
+    }
+}
+
+fn push_ranges(out: *mut c_void, ranges: &[Range]) {
+    for r in ranges {
+        unsafe { soapy_shim_ranges_push(out, r.minimum, r.maximum, r.step) }
+    }
+}
+
+fn write_range(out: *mut f64, range: Range) {
+    unsafe { slice::from_raw_parts_mut(out, 3) }.copy_from_slice(&[
+        range.minimum,
+        range.maximum,
+        range.step,
+    ]);
+}
+
+fn driver() -> Result<&'static dyn Driver, Error> {
+    DRIVER.get().map(|d| &**d).ok_or_else(|| Error {
+        code: ErrorCode::Other,
+        message: "No driver registered".into(),
+    })
+}
+
+/*
+ * Driver
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_find(
+    args: *const c_void,
+    results: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        let found = driver()?.find(&unsafe { read_args(args) });
+        for args in &found {
+            set_args(unsafe { soapy_shim_kwargs_list_push(results) }, args);
+        }
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_make(
+    args: *const c_void,
+    out: *mut *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        let device = driver()?.make(&unsafe { read_args(args) })?;
+        Ok(Box::into_raw(Box::new(device)) as *mut c_void)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_device_drop(device: *mut c_void) {
+    let device = unsafe { Box::from_raw(device as *mut Box<dyn DeviceImpl>) };
+    if let Err(e) = catch(|| {
+        drop(device);
+        Ok(())
+    }) {
+        log(SOAPY_SDR_ERROR, &e.message);
+    }
+}
+
+/*
+ * Identification and channels
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_driver_key(
+    d: *const c_void,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        set_string(out, &unsafe { device(d) }.driver_key()?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_hardware_key(
+    d: *const c_void,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        set_string(out, &unsafe { device(d) }.hardware_key()?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_hardware_info(
+    d: *const c_void,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        set_args(out, &unsafe { device(d) }.hardware_info()?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_num_channels(
+    d: *const c_void,
+    dir: c_int,
+    out: *mut usize,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.num_channels(direction(dir))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_full_duplex(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut bool,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.full_duplex(direction(dir), ch)
+    })
+}
+
+/*
+ * Streams
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_formats(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        let formats = unsafe { device(d) }.stream_formats(direction(dir), ch)?;
+        push_strings(out, &formats.iter().map(|f| f.as_str()).collect::<Vec<_>>());
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_native_stream_format(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    full_scale: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        let (format, scale) = unsafe { device(d) }.native_stream_format(direction(dir), ch)?;
+        set_string(out, format.as_str());
+        unsafe { full_scale.write(scale) };
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_setup_stream(
+    d: *const c_void,
+    dir: c_int,
+    format: *const c_char,
+    channels: *const usize,
+    num_channels: usize,
+    args: *const c_void,
+    out: *mut *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        let format_name = unsafe { c_string(format) };
+        let format: Format = format_name.parse().map_err(|_| Error {
+            code: ErrorCode::NotSupported,
+            message: format!("Unsupported stream format {:?}", format_name),
+        })?;
+        let channels = match num_channels {
+            0 => &[0][..],
+            n => unsafe { slice::from_raw_parts(channels, n) },
+        };
+        let stream =
+            unsafe { device(d) }.setup_stream(direction(dir), format, channels, &unsafe {
+                read_args(args)
+            })?;
+        let handle = StreamHandle {
+            stream: Mutex::new(stream),
+            sample_size: format.size(),
+            num_channels: channels.len(),
+        };
+        Ok(Box::into_raw(Box::new(handle)) as *mut c_void)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_drop(s: *mut c_void) {
+    let handle = unsafe { Box::from_raw(s as *mut StreamHandle) };
+    if let Err(e) = catch(|| {
+        drop(handle);
+        Ok(())
+    }) {
+        log(SOAPY_SDR_ERROR, &e.message);
+    }
+}
+
+/// Run a stream call, returning its non-negative result or a SoapySDR error code.
+fn stream_status(
+    s: *const c_void,
+    f: impl FnOnce(&StreamHandle, &mut dyn StreamImpl) -> Result<c_int, Error>,
+) -> c_int {
+    let handle = unsafe { stream(s) };
+    let result = catch(|| {
+        let mut stream = handle.stream.lock().unwrap_or_else(|e| e.into_inner());
+        f(handle, &mut **stream)
+    });
+    match result {
+        Ok(n) => n,
+        Err(e) => {
+            // Timeouts, overflows and underflows are routine; the caller sees the code.
+            if !matches!(
+                e.code,
+                ErrorCode::Timeout | ErrorCode::Overflow | ErrorCode::Underflow
+            ) {
+                log(SOAPY_SDR_ERROR, &format!("stream error: {}", e.message));
+            }
+            error_code(&e)
+        }
+    }
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_mtu(
+    s: *const c_void,
+    out: *mut usize,
+    err: *mut c_void,
+) -> c_int {
+    let handle = unsafe { stream(s) };
+    value(err, out, || {
+        handle
+            .stream
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .mtu()
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_activate(
+    s: *mut c_void,
+    flags: c_int,
+    time_ns: c_longlong,
+    num_elems: usize,
+) -> c_int {
+    stream_status(s, |_, stream| {
+        stream.activate(
+            StreamFlags::from_bits(flags),
+            optional_time(flags, time_ns),
+            num_elems,
+        )?;
+        Ok(0)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_deactivate(
+    s: *mut c_void,
+    flags: c_int,
+    time_ns: c_longlong,
+) -> c_int {
+    stream_status(s, |_, stream| {
+        stream.deactivate(StreamFlags::from_bits(flags), optional_time(flags, time_ns))?;
+        Ok(0)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_read(
+    s: *mut c_void,
+    buffs: *const *mut c_void,
+    num_elems: usize,
+    flags: *mut c_int,
+    time_ns: *mut c_longlong,
+    timeout_us: c_long,
+) -> c_int {
+    stream_status(s, |handle, stream| {
+        let len = num_elems.min(c_int::MAX as usize);
+        let mut buffers: Vec<&mut [u8]> =
+            unsafe { slice::from_raw_parts(buffs, handle.num_channels) }
+                .iter()
+                .map(|&buf| unsafe {
+                    slice::from_raw_parts_mut(buf as *mut u8, len * handle.sample_size)
+                })
+                .collect();
+        let meta = stream.read(&mut buffers, timeout(timeout_us))?;
+        unsafe {
+            flags.write(meta.flags.bits());
+            time_ns.write(meta.raw_time.as_ns());
+        }
+        Ok(meta.len.min(len) as c_int)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_stream_write(
+    s: *mut c_void,
+    buffs: *const *const c_void,
+    num_elems: usize,
+    flags: c_int,
+    time_ns: c_longlong,
+    timeout_us: c_long,
+) -> c_int {
+    stream_status(s, |handle, stream| {
+        let len = num_elems.min(c_int::MAX as usize);
+        let buffers: Vec<&[u8]> = unsafe { slice::from_raw_parts(buffs, handle.num_channels) }
+            .iter()
+            .map(|&buf| unsafe {
+                slice::from_raw_parts(buf as *const u8, len * handle.sample_size)
+            })
+            .collect();
+        let written = stream.write(
+            &buffers,
+            StreamFlags::from_bits(flags),
+            optional_time(flags, time_ns),
+            timeout(timeout_us),
+        )?;
+        Ok(written.min(len) as c_int)
+    })
+}
+
+/*
+ * Antennas
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_list_antennas(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        push_strings(out, &unsafe { device(d) }.antennas(direction(dir), ch)?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_antenna(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    name: *const c_char,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || unsafe {
+        device(d).set_antenna(direction(dir), ch, &c_string(name))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_antenna(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        set_string(out, &unsafe { device(d) }.antenna(direction(dir), ch)?);
+        Ok(())
+    })
+}
+
+/*
+ * Gains
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_list_gains(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        push_strings(out, &unsafe { device(d) }.list_gains(direction(dir), ch)?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_has_gain_mode(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut bool,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.has_gain_mode(direction(dir), ch)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_gain_mode(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    automatic: bool,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        unsafe { device(d) }.set_gain_mode(direction(dir), ch, automatic)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_gain_mode(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut bool,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.gain_mode(direction(dir), ch)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_gain(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    gain: f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        unsafe { device(d) }.set_gain(direction(dir), ch, gain)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_gain(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || unsafe { device(d) }.gain(direction(dir), ch))
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_gain_range(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        write_range(out, unsafe { device(d) }.gain_range(direction(dir), ch)?);
+        Ok(())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_gain_element(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    name: *const c_char,
+    gain: f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || unsafe {
+        device(d).set_gain_element(direction(dir), ch, &c_string(name), gain)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_gain_element(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    name: *const c_char,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || unsafe {
+        device(d).gain_element(direction(dir), ch, &c_string(name))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_gain_element_range(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    name: *const c_char,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        write_range(out, unsafe {
+            device(d).gain_element_range(direction(dir), ch, &c_string(name))
+        }?);
+        Ok(())
+    })
+}
+
+/*
+ * Frequency
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_frequency(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    frequency: f64,
+    args: *const c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || unsafe {
+        device(d).set_frequency(direction(dir), ch, frequency, &read_args(args))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_frequency(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.frequency(direction(dir), ch)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_frequency_range(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        push_ranges(
+            out,
+            &unsafe { device(d) }.frequency_range(direction(dir), ch)?,
+        );
+        Ok(())
+    })
+}
+
+/*
+ * Sample rate
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_sample_rate(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    rate: f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        unsafe { device(d) }.set_sample_rate(direction(dir), ch, rate)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_sample_rate(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.sample_rate(direction(dir), ch)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_sample_rate_range(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        push_ranges(
+            out,
+            &unsafe { device(d) }.sample_rate_range(direction(dir), ch)?,
+        );
+        Ok(())
+    })
+}
+
+/*
+ * Bandwidth
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_bandwidth(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    bandwidth: f64,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        unsafe { device(d) }.set_bandwidth(direction(dir), ch, bandwidth)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_bandwidth(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut f64,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        unsafe { device(d) }.bandwidth(direction(dir), ch)
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_bandwidth_range(
+    d: *const c_void,
+    dir: c_int,
+    ch: usize,
+    out: *mut c_void,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || {
+        push_ranges(
+            out,
+            &unsafe { device(d) }.bandwidth_range(direction(dir), ch)?,
+        );
+        Ok(())
+    })
+}
+
+/*
+ * Time
+ */
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_has_hardware_time(
+    d: *const c_void,
+    what: *const c_char,
+    out: *mut bool,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || unsafe {
+        device(d).has_hardware_time(&c_string(what))
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_hardware_time(
+    d: *const c_void,
+    what: *const c_char,
+    out: *mut c_longlong,
+    err: *mut c_void,
+) -> c_int {
+    value(err, out, || {
+        Ok(unsafe { device(d).hardware_time(&c_string(what)) }?.as_ns())
+    })
+}
+
+#[unsafe(no_mangle)]
+unsafe extern "C" fn soapy_rust_set_hardware_time(
+    d: *const c_void,
+    what: *const c_char,
+    time_ns: c_longlong,
+    err: *mut c_void,
+) -> c_int {
+    status(err, || unsafe {
+        device(d).set_hardware_time(&c_string(what), HardwareTime::from_ns(time_ns))
+    })
+}
@@ -0,0 +1,462 @@
+//! Write [SoapySDR](https://github.com/pothosware/SoapySDR/wiki) driver modules in Rust.
+//!
+//! A driver implements [`Driver`] to discover and open devices, [`DeviceImpl`] for an opened
+//! device, and [`StreamImpl`] for its sample streams. [`register_driver!`] registers the driver
+//! with SoapySDR when the library is loaded. Build the crate as a `cdylib` and install it in a
+//! SoapySDR module directory, or load it with [`soapysdr::modules::load`], and the driver is
+//! found by [`soapysdr::enumerate`] and every other SoapySDR application.
+//!
+//! The traits cover identification, channels, antennas, gains, frequency, sample rate,
+//! bandwidth, hardware time and streaming. Every method has a default matching the behavior of
+//! the C++ `SoapySDR::Device` base class, so a driver only implements what its hardware
+//! supports. Other parts of the SoapySDR API keep the base class behavior.
+//!
+//! # Example
+//! ```no_run
+//! use soapysdr::{Args, Direction, Error};
+//! use soapysdr_driver::{DeviceImpl, Driver, register_driver};
+//!
+//! struct MyDriver;
+//!
+//! impl Driver for MyDriver {
+//!     fn find(&self, _args: &Args) -> Vec<Args> {
+//!         vec!["driver=mine, label=My front end".into()]
+//!     }
+//!
+//!     fn make(&self, _args: &Args) -> Result<Box<dyn DeviceImpl>, Error> {
+//!         Ok(Box::new(MyDevice))
+//!     }
+//! }
+//!
+//! struct MyDevice;
+//!
+//! impl DeviceImpl for MyDevice {
+//!     fn driver_key(&self) -> Result<String, Error> {
+//!         Ok("mine".into())
+//!     }
+//!
+//!     fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+//!         Ok(if direction == Direction::Rx { 1 } else { 0 })
+//!     }
+//! }
+//!
+//! register_driver!("mine", MyDriver);
+//! ```
+
+use soapysdr::{Args, Direction, Error, ErrorCode, Format, HardwareTime, Range};
+use soapysdr::{RxMetadata, StreamFlags};
+
+mod ffi;
+
+/// A driver, which discovers and opens devices.
+///
+/// Register a driver with [`register_driver!`]. SoapySDR calls it from any thread.
+pub trait Driver: Send + Sync + 'static {
+    /// List the devices matching `args`.
+    ///
+    /// Each result should include the keys needed to open that device with
+    /// [`make`](Driver::make), and usually a `label`.
+    fn find(&self, args: &Args) -> Vec<Args>;
+
+    /// Open a device, given one of the results of [`find`](Driver::find) or arguments passed
+    /// by the user.
+    fn make(&self, args: &Args) -> Result<Box<dyn DeviceImpl>, Error>;
+}
+
+fn not_supported(what: &str) -> Error {
+    Error {
+        code: ErrorCode::NotSupported,
+        message: format!("{} not supported by this driver", what),
+    }
+}
+
+/// An opened device. The methods correspond to those of [`soapysdr::Device`].
+///
+/// SoapySDR may call methods concurrently from multiple threads, so implementations use
+/// interior mutability for their state.
+pub trait DeviceImpl: Send + Sync {
+    /// A key that uniquely identifies the device driver.
+    fn driver_key(&self) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    /// A key that uniquely identifies the hardware.
+    fn hardware_key(&self) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    /// Hardware information as key/value pairs.
+    fn hardware_info(&self) -> Result<Args, Error> {
+        Ok(Args::new())
+    }
+
+    /// Get the number of channels in a direction.
+    fn num_channels(&self, _direction: Direction) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    /// Find out if the specified channel is full or half duplex.
+    fn full_duplex(&self, _direction: Direction, _channel: usize) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    /// Get the sample formats supported by streams of a channel.
+    ///
+    /// The default lists only the native format.
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        Ok(vec![self.native_stream_format(direction, channel)?.0])
+    }
+
+    /// Get the hardware's native stream format and its full-scale value.
+    fn native_stream_format(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        Ok((Format::CS16, 32768.0))
+    }
+
+    /// Open a stream on a set of channels, with samples in `format`.
+    ///
+    /// `channels` is never empty; SoapySDR's default of channel 0 has already been applied.
+    fn setup_stream(
+        &self,
+        _direction: Direction,
+        _format: Format,
+        _channels: &[usize],
+        _args: &Args,
+    ) -> Result<Box<dyn StreamImpl>, Error> {
+        Err(not_supported("Streaming"))
+    }
+
+    /// List the antennas of a channel.
+    fn antennas(&self, _direction: Direction, _channel: usize) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Select the antenna of a channel.
+    fn set_antenna(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Get the selected antenna of a channel.
+    fn antenna(&self, _direction: Direction, _channel: usize) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    /// List the names of the amplification elements of a channel, in the order they are
+    /// applied by [`set_gain`](DeviceImpl::set_gain).
+    fn list_gains(&self, _direction: Direction, _channel: usize) -> Result<Vec<String>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Returns true if the channel supports automatic gain control.
+    fn has_gain_mode(&self, _direction: Direction, _channel: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    /// Enable or disable automatic gain control.
+    fn set_gain_mode(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _automatic: bool,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Returns true if automatic gain control is enabled.
+    fn gain_mode(&self, _direction: Direction, _channel: usize) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    /// Set the overall amplification of a channel.
+    ///
+    /// The default distributes the gain over the elements from
+    /// [`list_gains`](DeviceImpl::list_gains), filling each to its maximum in order.
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        let names = self.list_gains(direction, channel)?;
+        let ranges = names
+            .iter()
+            .map(|name| self.gain_element_range(direction, channel, name))
+            .collect::<Result<Vec<_>, _>>()?;
+        for (name, value) in names.iter().zip(distribute_gain(&ranges, gain)) {
+            self.set_gain_element(direction, channel, name, value)?;
+        }
+        Ok(())
+    }
+
+    /// Get the overall amplification of a channel.
+    ///
+    /// The default is the sum of the elements' gains.
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.list_gains(direction, channel)?
+            .iter()
+            .map(|name| self.gain_element(direction, channel, name))
+            .sum()
+    }
+
+    /// Get the overall range of possible gain values.
+    ///
+    /// The default is the sum of the elements' ranges.
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        let mut total = Range::new(0.0, 0.0, 0.0);
+        for name in self.list_gains(direction, channel)? {
+            let range = self.gain_element_range(direction, channel, &name)?;
+            total.minimum += range.minimum;
+            total.maximum += range.maximum;
+        }
+        Ok(total)
+    }
+
+    /// Set the value of an amplification element.
+    fn set_gain_element(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+        _gain: f64,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Get the value of an amplification element.
+    fn gain_element(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+    ) -> Result<f64, Error> {
+        Ok(0.0)
+    }
+
+    /// Get the range of possible values of an amplification element.
+    fn gain_element_range(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _name: &str,
+    ) -> Result<Range, Error> {
+        Ok(Range::new(0.0, 0.0, 0.0))
+    }
+
+    /// Set the center frequency of a channel.
+    fn set_frequency(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _frequency: f64,
+        _args: &Args,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Get the center frequency of a channel.
+    fn frequency(&self, _direction: Direction, _channel: usize) -> Result<f64, Error> {
+        Ok(0.0)
+    }
+
+    /// Get the ranges of tunable frequencies of a channel.
+    fn frequency_range(&self, _direction: Direction, _channel: usize) -> Result<Vec<Range>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Set the sample rate of a channel.
+    fn set_sample_rate(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _rate: f64,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Get the sample rate of a channel.
+    fn sample_rate(&self, _direction: Direction, _channel: usize) -> Result<f64, Error> {
+        Ok(0.0)
+    }
+
+    /// Get the ranges of possible sample rates of a channel.
+    fn sample_rate_range(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Set the baseband filter width of a channel.
+    fn set_bandwidth(
+        &self,
+        _direction: Direction,
+        _channel: usize,
+        _bandwidth: f64,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Get the baseband filter width of a channel.
+    fn bandwidth(&self, _direction: Direction, _channel: usize) -> Result<f64, Error> {
+        Ok(0.0)
+    }
+
+    /// Get the ranges of possible baseband filter widths of a channel.
+    fn bandwidth_range(&self, _direction: Direction, _channel: usize) -> Result<Vec<Range>, Error> {
+        Ok(Vec::new())
+    }
+
+    /// Returns true if the device has the hardware time source `what`, or a default time
+    /// source if `what` is empty.
+    fn has_hardware_time(&self, _what: &str) -> Result<bool, Error> {
+        Ok(false)
+    }
+
+    /// Read the time from the hardware clock `what`.
+    fn hardware_time(&self, _what: &str) -> Result<HardwareTime, Error> {
+        Ok(HardwareTime::ZERO)
+    }
+
+    /// Write the time of the hardware clock `what`.
+    fn set_hardware_time(&self, _what: &str, _time: HardwareTime) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// An open stream, returned by [`DeviceImpl::setup_stream`]. The stream is closed when
+/// dropped.
+///
+/// Buffers hold one slice per channel of the stream, each the same number of samples of the
+/// stream's format, as bytes.
+pub trait StreamImpl: Send {
+    /// Get the number of samples per channel that can be transferred in a single operation.
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(0)
+    }
+
+    /// Activate the stream, at `time` if given. For receive, `num_elems` is the number of
+    /// samples to read before stopping, or 0 to stream continuously.
+    fn activate(
+        &mut self,
+        _flags: StreamFlags,
+        _time: Option<HardwareTime>,
+        _num_elems: usize,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Deactivate the stream, at `time` if given.
+    fn deactivate(
+        &mut self,
+        _flags: StreamFlags,
+        _time: Option<HardwareTime>,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Read samples into `buffers`, waiting up to `timeout_us` microseconds.
+    ///
+    /// Returns the number of samples written to each buffer, and the flags and time of the
+    /// first sample. Return an [`ErrorCode::Timeout`] error if no samples arrive in time.
+    fn read(&mut self, _buffers: &mut [&mut [u8]], _timeout_us: i64) -> Result<RxMetadata, Error> {
+        Err(not_supported("Reading"))
+    }
+
+    /// Write samples from `buffers`, at `time` if given, waiting up to `timeout_us`
+    /// microseconds.
+    ///
+    /// Returns the number of samples consumed from each buffer.
+    fn write(
+        &mut self,
+        _buffers: &[&[u8]],
+        _flags: StreamFlags,
+        _time: Option<HardwareTime>,
+        _timeout_us: i64,
+    ) -> Result<usize, Error> {
+        Err(not_supported("Writing"))
+    }
+}
+
+/// Distribute an overall gain over elements with the given ranges, filling each to its maximum
+/// in order after every element is at its minimum.
+fn distribute_gain(ranges: &[Range], gain: f64) -> Vec<f64> {
+    let mut remaining = gain - ranges.iter().map(|r| r.minimum).sum::<f64>();
+    ranges
+        .iter()
+        .map(|range| {
+            let extra = remaining.clamp(0.0, (range.maximum - range.minimum).max(0.0));
+            remaining -= extra;
+            range.minimum + extra
+        })
+        .collect()
+}
+
+/// Register a [`Driver`] with SoapySDR under `name` when the library containing it is loaded.
+///
+/// Use this once per module, at the top level of a `cdylib` crate. `name` is the driver name
+/// shown by SoapySDR and matched by the `driver=` argument.
+///
+/// ```no_run
+/// # use soapysdr::{Args, Error};
+/// # use soapysdr_driver::{DeviceImpl, Driver};
+/// # struct MyDriver;
+/// # impl Driver for MyDriver {
+/// #     fn find(&self, _args: &Args) -> Vec<Args> { vec![] }
+/// #     fn make(&self, _args: &Args) -> Result<Box<dyn DeviceImpl>, Error> { unimplemented!() }
+/// # }
+/// soapysdr_driver::register_driver!("mine", MyDriver);
+/// ```
+#[macro_export]
+macro_rules! register_driver {
+    ($name:expr, $driver:expr) => {
+        const _: () = {
+            extern "C" fn __soapysdr_driver_init() {
+                $crate::__private::register($name, ::std::boxed::Box::new($driver));
+            }
+
+            #[used]
+            #[cfg_attr(
+                any(
+                    target_os = "linux",
+                    target_os = "android",
+                    target_os = "freebsd",
+                    target_os = "netbsd",
+                    target_os = "openbsd",
+                    target_os = "dragonfly",
+                    target_os = "illumos"
+                ),
+                unsafe(link_section = ".init_array")
+            )]
+            #[cfg_attr(
+                target_vendor = "apple",
+                unsafe(link_section = "__DATA,__mod_init_func")
+            )]
+            #[cfg_attr(windows, unsafe(link_section = ".CRT$XCU"))]
+            static __SOAPYSDR_DRIVER_INIT: extern "C" fn() = __soapysdr_driver_init;
+        };
+    };
+}
+
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::ffi::register;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gain_distribution() {
+        let ranges = [Range::new(0.0, 40.0, 1.0), Range::new(-10.0, 20.0, 1.0)];
+        assert_eq!(distribute_gain(&ranges, 25.0), vec![35.0, -10.0]);
+        assert_eq!(distribute_gain(&ranges, 45.0), vec![40.0, 5.0]);
+        assert_eq!(distribute_gain(&ranges, 100.0), vec![40.0, 20.0]);
+        assert_eq!(distribute_gain(&ranges, -20.0), vec![0.0, -10.0]);
+        assert_eq!(distribute_gain(&[], 10.0), Vec::<f64>::new());
+    }
+}
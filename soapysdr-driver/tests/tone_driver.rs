@@ -0,0 +1,50 @@
+//! Load the `tone_driver` example module into SoapySDR and read from it through the
+//! `soapysdr` API. Run with `cargo test -p soapysdr-driver --features module-tests`.
+
+use std::env::consts::{DLL_PREFIX, DLL_SUFFIX};
+use std::path::PathBuf;
+use std::process::Command;
+
+use num_complex::Complex;
+use soapysdr::Device;
+
+/// Build the example module and return the path of the library.
+fn build_module() -> PathBuf {
+    let mut cargo = Command::new(env!("CARGO"));
+    cargo.args(["build", "-p", "soapysdr-driver", "--example", "tone_driver"]);
+    if !cfg!(debug_assertions) {
+        cargo.arg("--release");
+    }
+    let status = cargo.status().expect("failed to run cargo");
+    assert!(status.success(), "failed to build the tone_driver example");
+
+    // The test binary is in `target/<profile>/deps`, and examples in `target/<profile>/examples`.
+    let exe = std::env::current_exe().unwrap();
+    let profile_dir = exe.parent().and_then(|deps| deps.parent()).unwrap();
+    profile_dir
+        .join("examples")
+        .join(format!("{DLL_PREFIX}tone_driver{DLL_SUFFIX}"))
+}
+
+#[test]
+fn read_tone() {
+    soapysdr::modules::load(build_module()).expect("failed to load module");
+
+    let devices = soapysdr::enumerate("driver=rust_tone").unwrap();
+    assert_eq!(devices.len(), 1);
+    assert_eq!(devices[0].get("label"), Some("Rust tone generator"));
+
+    let dev = Device::new("driver=rust_tone").unwrap();
+    assert_eq!(dev.driver_key().unwrap(), "rust_tone");
+
+    let mut stream = dev.rx_stream::<Complex<f32>>(&[0]).unwrap();
+    let mut buf = vec![Complex::new(0.0, 0.0); stream.mtu().unwrap()];
+    stream.activate(None).unwrap();
+    let n = stream.read(&mut [&mut buf[..]], 100_000).unwrap();
+    stream.deactivate(None).unwrap();
+
+    assert!(n > 0);
+    for sample in &buf[..n] {
+        assert!((sample.norm() - 1.0).abs() < 1e-3, "{sample}");
+    }
+}
@@ -7,3 +7,14 @@
 //! for API documentation.
 
 include!("bindings.rs");
+
+impl SoapySDRRange {
+    /// Create a range from its minimum, maximum and step.
+    pub const fn new(minimum: f64, maximum: f64, step: f64) -> SoapySDRRange {
+        SoapySDRRange {
+            minimum,
+            maximum,
+            step,
+        }
+    }
+}
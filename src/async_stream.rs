@@ -0,0 +1,530 @@
+use futures::channel::{mpsc, oneshot};
+use futures::executor::block_on;
+use futures::{FutureExt, Sink, SinkExt, Stream, StreamExt};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+
+use crate::device::write_all;
+use crate::{
+    Error, ErrorCode, HardwareTime, RxMetadata, RxStream, StatusEvent, StreamSample, TxStream,
+};
+
+/// Timeout for each blocking stream call on the I/O thread, which bounds how long it takes to
+/// notice that the async handle was dropped.
+const IO_TIMEOUT_US: i64 = 100_000;
+
+/// A block of samples received by an [`AsyncRxStream`].
+#[derive(Clone, Debug)]
+pub struct RxPacket<E> {
+    /// The received samples for each channel of the stream.
+    pub channels: Vec<Vec<E>>,
+
+    /// Flags and timestamp reported by the driver.
+    pub meta: RxMetadata,
+}
+
+/// A block of samples to be transmitted by an [`AsyncTxStream`].
+#[derive(Clone, Debug)]
+pub struct TxPacket<E> {
+    /// The samples for each channel of the stream. All channels must be the same length.
+    pub channels: Vec<Vec<E>>,
+
+    /// Optional device timestamp at which to begin transmitting the packet.
+    pub at: Option<HardwareTime>,
+
+    /// Whether this packet ends a burst transmission.
+    pub end_burst: bool,
+}
+
+/// The receive stream calls made by the I/O thread.
+trait RxIo<E> {
+    fn num_channels(&self) -> usize;
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error>;
+}
+
+impl<E: StreamSample> RxIo<E> for RxStream<E> {
+    fn num_channels(&self) -> usize {
+        RxStream::num_channels(self)
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        RxStream::read_with_meta(self, buffers, timeout_us)
+    }
+}
+
+/// The transmit stream calls made by the I/O thread.
+trait TxIo<E> {
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error>;
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error>;
+}
+
+impl<E: StreamSample> TxIo<E> for TxStream<E> {
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        TxStream::write(self, buffers, at, end_burst, timeout_us)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        TxStream::read_status_event(self, timeout_us)
+    }
+}
+
+fn disconnected() -> Error {
+    Error {
+        code: ErrorCode::StreamError,
+        message: "Stream I/O thread has exited".into(),
+    }
+}
+
+/// An [`RxStream`] read on a dedicated thread, as a [`futures::Stream`] of received packets.
+///
+/// To obtain an AsyncRxStream, call [`RxStream::into_async`]. Errors such as overflows are
+/// yielded as `Err` items; the stream ends after any other error.
+///
+/// Dropping the AsyncRxStream signals the thread to stop without waiting for it. The thread
+/// closes the underlying stream once its current read finishes, within 100ms.
+pub struct AsyncRxStream<E> {
+    receiver: mpsc::Receiver<Result<RxPacket<E>, Error>>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<E: StreamSample + Copy + Default + Send + 'static> RxStream<E> {
+    /// Move the stream to a dedicated thread and receive from it asynchronously.
+    ///
+    /// The stream should be [activated](RxStream::activate) first. The thread reads packets of
+    /// up to `packet_len` samples per channel (see [`mtu`](RxStream::mtu)) and queues up to
+    /// `queue_depth` of them. If the consumer falls behind, packets are dropped while the queue
+    /// is full, and an [`ErrorCode::Overflow`] error is yielded before the next packet.
+    pub fn into_async(self, packet_len: usize, queue_depth: usize) -> AsyncRxStream<E> {
+        AsyncRxStream::spawn(self, packet_len, queue_depth)
+    }
+}
+
+impl<E: Copy + Default + Send + 'static> AsyncRxStream<E> {
+    fn spawn(
+        stream: impl RxIo<E> + Send + 'static,
+        packet_len: usize,
+        queue_depth: usize,
+    ) -> AsyncRxStream<E> {
+        let (sender, receiver) = mpsc::channel(queue_depth);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        thread::spawn(move || rx_thread(stream, packet_len, sender, thread_stop));
+
+        AsyncRxStream { receiver, stop }
+    }
+}
+
+fn queue_overflow() -> Error {
+    Error {
+        code: ErrorCode::Overflow,
+        message: "Receive queue full, packets dropped".into(),
+    }
+}
+
+fn rx_thread<E: Copy + Default>(
+    mut stream: impl RxIo<E>,
+    packet_len: usize,
+    mut sender: mpsc::Sender<Result<RxPacket<E>, Error>>,
+    stop: Arc<AtomicBool>,
+) {
+    let nchannels = stream.num_channels();
+    // Whether packets were dropped since the last overflow was reported.
+    let mut dropped = false;
+    // Buffers of a packet that wasn't delivered, to be reused for the next read.
+    let mut spare: Option<Vec<Vec<E>>> = None;
+    while !stop.load(Ordering::Relaxed) {
+        let mut channels = spare.take().unwrap_or_else(|| vec![Vec::new(); nchannels]);
+        for c in &mut channels {
+            c.resize(packet_len, E::default());
+        }
+        let result = {
+            let mut buffers: Vec<&mut [E]> = channels.iter_mut().map(|c| &mut c[..]).collect();
+            stream.read_with_meta(&mut buffers, IO_TIMEOUT_US)
+        };
+
+        let item = match result {
+            Ok(meta) => {
+                for c in &mut channels {
+                    c.truncate(meta.len);
+                }
+                Ok(RxPacket { channels, meta })
+            }
+            Err(e) => {
+                spare = Some(channels);
+                if e.code == ErrorCode::Timeout {
+                    continue;
+                }
+                Err(e)
+            }
+        };
+
+        if matches!(&item, Err(e) if e.code != ErrorCode::Overflow) {
+            // There is nothing left to read, so wait for room to deliver the error. Dropping the
+            // receiver closes the channel and ends the wait.
+            block_on(sender.send(item)).ok();
+            break;
+        }
+
+        if dropped {
+            match sender.try_send(Err(queue_overflow())) {
+                Ok(()) => dropped = false,
+                Err(e) if e.is_disconnected() => break,
+                Err(_) => {
+                    if let Ok(packet) = item {
+                        spare = Some(packet.channels);
+                    }
+                    continue;
+                }
+            }
+        }
+
+        match sender.try_send(item) {
+            Ok(()) => {}
+            Err(e) if e.is_disconnected() => break,
+            Err(e) => {
+                dropped = true;
+                if let Ok(packet) = e.into_inner() {
+                    spare = Some(packet.channels);
+                }
+            }
+        }
+    }
+}
+
+impl<E> Stream for AsyncRxStream<E> {
+    type Item = Result<RxPacket<E>, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.receiver).poll_next(cx)
+    }
+}
+
+impl<E> Drop for AsyncRxStream<E> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        self.receiver.close();
+    }
+}
+
+/// A [`TxStream`] written on a dedicated thread, as a [`futures::Sink`] of packets to transmit.
+///
+/// To obtain an AsyncTxStream, call [`TxStream::into_async`]. Writes that time out are retried,
+/// so each packet is written in full. Other errors writing a packet, and underflows or time
+/// errors reported by the device, are returned from the next call to the sink.
+///
+/// Flushing the sink only waits for packets to be queued for the thread. Closing the sink
+/// (e.g. with [`SinkExt::close`]) also waits for all queued packets to be written and the
+/// underlying stream to be closed. Dropping the AsyncTxStream without closing it doesn't wait;
+/// the thread writes the remaining packets and closes the stream in the background.
+pub struct AsyncTxStream<E> {
+    sender: mpsc::Sender<TxPacket<E>>,
+    error: Arc<Mutex<Option<Error>>>,
+    done: oneshot::Receiver<()>,
+}
+
+impl<E: StreamSample + Send + 'static> TxStream<E> {
+    /// Move the stream to a dedicated thread and transmit on it asynchronously.
+    ///
+    /// The stream should be [activated](TxStream::activate) first. Up to `queue_depth` packets
+    /// are queued for the thread before the sink applies backpressure.
+    pub fn into_async(self, queue_depth: usize) -> AsyncTxStream<E> {
+        AsyncTxStream::spawn(self, queue_depth)
+    }
+}
+
+impl<E: Send + 'static> AsyncTxStream<E> {
+    fn spawn(stream: impl TxIo<E> + Send + 'static, queue_depth: usize) -> AsyncTxStream<E> {
+        let (sender, receiver) = mpsc::channel(queue_depth);
+        let (done_sender, done) = oneshot::channel();
+        let error = Arc::new(Mutex::new(None));
+        let thread_error = error.clone();
+        thread::spawn(move || {
+            tx_thread(stream, receiver, thread_error);
+            done_sender.send(()).ok();
+        });
+
+        AsyncTxStream {
+            sender,
+            error,
+            done,
+        }
+    }
+}
+
+fn tx_thread<E>(
+    mut stream: impl TxIo<E>,
+    mut receiver: mpsc::Receiver<TxPacket<E>>,
+    error: Arc<Mutex<Option<Error>>>,
+) {
+    while let Some(packet) = block_on(receiver.next()) {
+        let buffers: Vec<&[E]> = packet.channels.iter().map(|c| &c[..]).collect();
+        let result = write_all(&buffers, packet.at, 1, |buffers, at| {
+            loop {
+                // Retry rather than drop the rest of the packet, which may end a burst.
+                match stream.write(buffers, at, packet.end_burst, IO_TIMEOUT_US) {
+                    Err(e) if e.code == ErrorCode::Timeout => continue,
+                    result => return result,
+                }
+            }
+        });
+        if let Err(e) = result {
+            error.lock().unwrap().get_or_insert(e);
+        }
+
+        loop {
+            let e = match stream.read_status_event(0) {
+                Ok(StatusEvent::Underflow { .. }) => Error {
+                    code: ErrorCode::Underflow,
+                    message: "Transmit underflow".into(),
+                },
+                Ok(StatusEvent::TimeError { .. }) => Error {
+                    code: ErrorCode::TimeError,
+                    message: "Transmit time error".into(),
+                },
+                // Drivers that don't report stream status return an error.
+                Ok(StatusEvent::Timeout) | Err(_) => break,
+                Ok(_) => continue,
+            };
+            error.lock().unwrap().get_or_insert(e);
+        }
+    }
+}
+
+impl<E> AsyncTxStream<E> {
+    fn take_error(&self) -> Result<(), Error> {
+        match self.error.lock().unwrap().take() {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<E> Sink<TxPacket<E>> for AsyncTxStream<E> {
+    type Error = Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.take_error()?;
+        Pin::new(&mut self.sender)
+            .poll_ready(cx)
+            .map_err(|_| disconnected())
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: TxPacket<E>) -> Result<(), Error> {
+        Pin::new(&mut self.sender)
+            .start_send(item)
+            .map_err(|_| disconnected())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.take_error()?;
+        Pin::new(&mut self.sender)
+            .poll_flush(cx)
+            .map_err(|_| disconnected())
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        self.take_error()?;
+        self.sender.close_channel();
+        // The thread exits once it has written every queued packet.
+        if self.done.poll_unpin(cx).is_pending() {
+            return Poll::Pending;
+        }
+        self.take_error()?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<E> Drop for AsyncTxStream<E> {
+    fn drop(&mut self) {
+        self.sender.close_channel();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChannelSet, StreamFlags};
+    use std::collections::VecDeque;
+    use std::sync::mpsc as std_mpsc;
+    use std::time::Duration;
+
+    fn error(code: ErrorCode) -> Error {
+        Error {
+            code,
+            message: String::new(),
+        }
+    }
+
+    /// Reports that a test stream was dropped.
+    struct Dropped(std_mpsc::Sender<()>);
+
+    impl Drop for Dropped {
+        fn drop(&mut self) {
+            self.0.send(()).ok();
+        }
+    }
+
+    /// A receive stream that returns scripted reads, then times out.
+    struct ScriptRx {
+        reads: VecDeque<Result<usize, Error>>,
+        _dropped: Dropped,
+    }
+
+    impl RxIo<i16> for ScriptRx {
+        fn num_channels(&self) -> usize {
+            1
+        }
+
+        fn read_with_meta(
+            &mut self,
+            buffers: &mut [&mut [i16]],
+            _timeout_us: i64,
+        ) -> Result<RxMetadata, Error> {
+            let Some(read) = self.reads.pop_front() else {
+                thread::sleep(Duration::from_millis(1));
+                return Err(error(ErrorCode::Timeout));
+            };
+            let len = read?;
+            buffers[0][..len].fill(len as i16);
+            Ok(RxMetadata {
+                len,
+                flags: StreamFlags::empty(),
+                raw_time: HardwareTime::ZERO,
+            })
+        }
+    }
+
+    /// A transmit stream that accepts every write and reports scripted status events.
+    struct ScriptTx {
+        events: VecDeque<StatusEvent>,
+        written: Arc<Mutex<Vec<i16>>>,
+        _dropped: Dropped,
+    }
+
+    impl TxIo<i16> for ScriptTx {
+        fn write(
+            &mut self,
+            buffers: &[&[i16]],
+            _at: Option<HardwareTime>,
+            _end_burst: bool,
+            _timeout_us: i64,
+        ) -> Result<usize, Error> {
+            self.written.lock().unwrap().extend_from_slice(buffers[0]);
+            Ok(buffers[0].len())
+        }
+
+        fn read_status_event(&mut self, _timeout_us: i64) -> Result<StatusEvent, Error> {
+            Ok(self.events.pop_front().unwrap_or(StatusEvent::Timeout))
+        }
+    }
+
+    #[test]
+    fn rx_overflow() {
+        let (dropped, was_dropped) = std_mpsc::channel();
+        let stream = ScriptRx {
+            reads: VecDeque::from([Ok(2), Err(error(ErrorCode::Overflow)), Ok(3)]),
+            _dropped: Dropped(dropped),
+        };
+        let mut rx = AsyncRxStream::spawn(stream, 4, 8);
+
+        let packet = block_on(rx.next()).unwrap().unwrap();
+        assert_eq!(packet.channels, [vec![2, 2]]);
+        let err = block_on(rx.next()).unwrap().unwrap_err();
+        assert_eq!(err.code, ErrorCode::Overflow);
+        let packet = block_on(rx.next()).unwrap().unwrap();
+        assert_eq!(packet.channels, [vec![3, 3, 3]]);
+
+        drop(rx);
+        was_dropped.recv_timeout(Duration::from_secs(1)).unwrap();
+    }
+
+    #[test]
+    fn rx_error_ends_stream() {
+        let (dropped, was_dropped) = std_mpsc::channel();
+        let stream = ScriptRx {
+            reads: VecDeque::from([Err(error(ErrorCode::StreamError)), Ok(1)]),
+            _dropped: Dropped(dropped),
+        };
+        let mut rx = AsyncRxStream::spawn(stream, 4, 8);
+
+        let err = block_on(rx.next()).unwrap().unwrap_err();
+        assert_eq!(err.code, ErrorCode::StreamError);
+        assert!(block_on(rx.next()).is_none());
+        was_dropped.recv_timeout(Duration::from_secs(1)).unwrap();
+    }
+
+    #[test]
+    fn tx_underflow() {
+        let (dropped, was_dropped) = std_mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let stream = ScriptTx {
+            events: VecDeque::from([StatusEvent::Underflow {
+                channels: ChannelSet::from_mask(1),
+                time: None,
+            }]),
+            written: written.clone(),
+            _dropped: Dropped(dropped),
+        };
+        let mut tx = AsyncTxStream::spawn(stream, 4);
+
+        let packet = TxPacket {
+            channels: vec![vec![1, 2, 3]],
+            at: None,
+            end_burst: true,
+        };
+        block_on(tx.send(packet)).unwrap();
+        let err = block_on(tx.close()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Underflow);
+        assert_eq!(*written.lock().unwrap(), [1, 2, 3]);
+        was_dropped.recv_timeout(Duration::from_secs(1)).unwrap();
+    }
+
+    #[test]
+    fn tx_drop() {
+        let (dropped, was_dropped) = std_mpsc::channel();
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let stream = ScriptTx {
+            events: VecDeque::new(),
+            written: written.clone(),
+            _dropped: Dropped(dropped),
+        };
+        let mut tx = AsyncTxStream::spawn(stream, 4);
+
+        let packet = TxPacket {
+            channels: vec![vec![4, 5]],
+            at: None,
+            end_burst: false,
+        };
+        block_on(tx.feed(packet)).unwrap();
+        drop(tx);
+
+        // The queued packet is still written before the stream is closed.
+        was_dropped.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(*written.lock().unwrap(), [4, 5]);
+    }
+}
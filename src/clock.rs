@@ -0,0 +1,140 @@
+use crate::{Device, Error, ErrorCode, Range};
+
+/// Relative tolerance for treating a floating point ratio as an integer.
+const TOLERANCE: f64 = 1e-9;
+
+/// Maximum number of candidate rates to test in a single stepped range.
+const MAX_CANDIDATES: f64 = 1e6;
+
+impl Device {
+    /// Find the highest master clock rate supported by the device that is an exact integer
+    /// multiple of `sample_rate`, so that the sample rate is reached by integer decimation or
+    /// interpolation.
+    ///
+    /// The factor between the two rates is at most `max_factor`. SoapySDR doesn't report the
+    /// factors a device's DSP chain supports, so this must come from its documentation; for
+    /// example, a USRP B2xx decimates by up to 512.
+    ///
+    /// The rate is chosen from [`get_master_clock_rates`](Device::get_master_clock_rates), and
+    /// is not applied; pass it to [`set_master_clock_rate`](Device::set_master_clock_rate) before
+    /// setting the sample rate. Returns [ErrorCode::NotSupported] if no supported rate is an
+    /// exact multiple within the factor.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+    /// let rate = 1.92e6;
+    /// dev.set_master_clock_rate(dev.master_clock_rate_for(rate, 512).unwrap()).unwrap();
+    /// dev.set_sample_rate(soapysdr::Direction::Rx, 0, rate).unwrap();
+    /// ```
+    pub fn master_clock_rate_for(&self, sample_rate: f64, max_factor: u32) -> Result<f64, Error> {
+        let ranges = self.get_master_clock_rates()?;
+        exact_multiple(&ranges, sample_rate, max_factor).ok_or_else(|| Error {
+            code: ErrorCode::NotSupported,
+            message: format!(
+                "No supported master clock rate is an integer multiple of {} Hz up to {} times",
+                sample_rate, max_factor
+            ),
+        })
+    }
+}
+
+fn is_integer(x: f64) -> bool {
+    (x - x.round()).abs() <= TOLERANCE * x.abs().max(1.0)
+}
+
+/// The highest rate within `ranges` that is `rate` multiplied by an integer between 1 and
+/// `max_factor`.
+fn exact_multiple(ranges: &[Range], rate: f64, max_factor: u32) -> Option<f64> {
+    if rate.is_nan() || rate <= 0.0 {
+        return None;
+    }
+    ranges
+        .iter()
+        .filter_map(|range| exact_multiple_in(range, rate, max_factor.into()))
+        .max_by(|a, b| a.total_cmp(b))
+}
+
+fn exact_multiple_in(range: &Range, rate: f64, max_factor: f64) -> Option<f64> {
+    let min_factor = (range.minimum / rate - TOLERANCE).ceil().max(1.0);
+    let max_factor = (range.maximum / rate + TOLERANCE).floor().min(max_factor);
+    if max_factor < min_factor {
+        return None;
+    }
+
+    if range.maximum <= range.minimum {
+        // A single value, which the factors above show is a multiple.
+        return Some(range.minimum);
+    }
+    if range.step <= 0.0 {
+        return Some(max_factor * rate);
+    }
+
+    let on_grid = |value: f64| is_integer((value - range.minimum) / range.step);
+    let num_multiples = max_factor - min_factor + 1.0;
+    // Only grid points up to the highest allowed multiple are candidates.
+    let maximum = range.maximum.min(max_factor * rate);
+    let num_steps = ((maximum - range.minimum) / range.step + TOLERANCE).floor() + 1.0;
+
+    // Search whichever of the multiples or the grid points is smaller, from the top.
+    if num_multiples <= num_steps {
+        (0..num_multiples.min(MAX_CANDIDATES) as u64)
+            .map(|i| (max_factor - i as f64) * rate)
+            .find(|&value| on_grid(value))
+    } else {
+        (0..num_steps.min(MAX_CANDIDATES) as u64)
+            .map(|i| range.minimum + (num_steps - 1.0 - i as f64) * range.step)
+            .find(|&value| is_integer(value / rate))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn continuous_range() {
+        // B2xx-style continuous master clock range.
+        let ranges = [Range::new(220e3, 61.44e6, 0.0)];
+        assert_eq!(exact_multiple(&ranges, 1.92e6, u32::MAX), Some(61.44e6));
+        assert_eq!(exact_multiple(&ranges, 1e6, u32::MAX), Some(61e6));
+        assert_eq!(exact_multiple(&ranges, 100e6, u32::MAX), None);
+        assert_eq!(exact_multiple(&ranges, 0.0, u32::MAX), None);
+    }
+
+    #[test]
+    fn max_factor() {
+        let ranges = [Range::new(220e3, 61.44e6, 0.0)];
+        assert_eq!(exact_multiple(&ranges, 1e3, 512), Some(512e3));
+        assert_eq!(exact_multiple(&ranges, 1e3, 100), None);
+        assert_eq!(exact_multiple(&ranges, 1.92e6, 16), Some(30.72e6));
+
+        let ranges = [Range::new(10e6, 40e6, 3e6)];
+        assert_eq!(exact_multiple(&ranges, 1e6, 30), Some(28e6));
+    }
+
+    #[test]
+    fn discrete_rates() {
+        let ranges = [
+            Range::new(100e6, 100e6, 0.0),
+            Range::new(122.88e6, 122.88e6, 0.0),
+            Range::new(125e6, 125e6, 0.0),
+        ];
+        assert_eq!(exact_multiple(&ranges, 1.92e6, u32::MAX), Some(122.88e6));
+        assert_eq!(exact_multiple(&ranges, 5e6, u32::MAX), Some(125e6));
+        assert_eq!(exact_multiple(&ranges, 1.024e6, u32::MAX), Some(122.88e6));
+        assert_eq!(exact_multiple(&ranges, 7e6, u32::MAX), None);
+    }
+
+    #[test]
+    fn stepped_range() {
+        let ranges = [Range::new(10e6, 40e6, 3e6)];
+        // Grid is 10, 13, ..., 37, 40 MHz.
+        assert_eq!(exact_multiple(&ranges, 5e6, u32::MAX), Some(40e6));
+        assert_eq!(exact_multiple(&ranges, 2e6, u32::MAX), Some(40e6));
+        assert_eq!(exact_multiple(&ranges, 11e6, u32::MAX), Some(22e6));
+        assert_eq!(exact_multiple(&ranges, 12e6, u32::MAX), None);
+        assert_eq!(exact_multiple(&ranges, 3.25e6, u32::MAX), Some(13e6));
+        assert_eq!(exact_multiple(&ranges, 1e3, u32::MAX), Some(40e6));
+    }
+}
@@ -0,0 +1,743 @@
+//! Conversion of sample buffers between stream formats.
+//!
+//! [`convert`] handles any pair of [`Format`]s with the same number of components, such as
+//! converting from the device's [native format](crate::Device::native_stream_format) to the
+//! format used by an application. The typed functions are faster paths for the most common
+//! conversions, and are written so the compiler can vectorize them.
+//!
+//! Integer formats are scaled so that their full-scale range maps to -1.0 to 1.0 in float
+//! formats. Unsigned formats are offset-binary, with the midpoint of the range (e.g. 128 for
+//! `CU8`) representing zero. Conversions to a narrower integer format round toward negative
+//! infinity, matching a right shift, and conversions from float saturate at full scale, with
+//! NaN converting to zero.
+
+use num_complex::Complex;
+
+use crate::{Error, ErrorCode, Format};
+
+/// Number of samples converted at a time through the intermediate buffer.
+const CHUNK: usize = 256;
+
+/// Storage of one component of a sample.
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum Elem {
+    F64,
+    F32,
+    S32,
+    U32,
+    S16,
+    U16,
+    S8,
+    U8,
+    /// Two 12-bit components packed into 3 bytes
+    S12Pair,
+    U12Pair,
+    /// Two 4-bit components packed into 1 byte
+    S4Pair,
+    U4Pair,
+}
+
+/// Returns the component storage, and the number of components per sample.
+fn layout(format: Format) -> (Elem, usize) {
+    match format {
+        Format::CF64 => (Elem::F64, 2),
+        Format::CF32 => (Elem::F32, 2),
+        Format::CS32 => (Elem::S32, 2),
+        Format::CU32 => (Elem::U32, 2),
+        Format::CS16 => (Elem::S16, 2),
+        Format::CU16 => (Elem::U16, 2),
+        Format::CS12 => (Elem::S12Pair, 2),
+        Format::CU12 => (Elem::U12Pair, 2),
+        Format::CS8 => (Elem::S8, 2),
+        Format::CU8 => (Elem::U8, 2),
+        Format::CS4 => (Elem::S4Pair, 2),
+        Format::CU4 => (Elem::U4Pair, 2),
+        Format::F64 => (Elem::F64, 1),
+        Format::F32 => (Elem::F32, 1),
+        Format::S32 => (Elem::S32, 1),
+        Format::U32 => (Elem::U32, 1),
+        Format::S16 => (Elem::S16, 1),
+        Format::U16 => (Elem::U16, 1),
+        Format::S8 => (Elem::S8, 1),
+        Format::U8 => (Elem::U8, 1),
+    }
+}
+
+/// Get the size in bytes of one sample in `format`.
+///
+/// This is the same as [`Format::size`], but does not call into SoapySDR.
+fn sample_size(format: Format) -> usize {
+    let (elem, components) = layout(format);
+    match elem {
+        Elem::F64 => 8 * components,
+        Elem::F32 | Elem::S32 | Elem::U32 => 4 * components,
+        Elem::S16 | Elem::U16 => 2 * components,
+        Elem::S8 | Elem::U8 => components,
+        Elem::S12Pair | Elem::U12Pair => 3,
+        Elem::S4Pair | Elem::U4Pair => 1,
+    }
+}
+
+/// Convert a float to an integer type, saturating at its limits and mapping NaN to zero.
+///
+/// This is what `as` does for float to integer casts, but spelled out so it doesn't depend on
+/// the reader knowing that.
+macro_rules! saturate {
+    ($x:expr, $t:ty) => {{
+        let x = $x;
+        if x.is_nan() {
+            0
+        } else {
+            x.clamp(<$t>::MIN as _, <$t>::MAX as _) as $t
+        }
+    }};
+}
+
+macro_rules! decode_int {
+    ($src:expr, $dst:expr, $t:ty, $offset:expr) => {{
+        const N: usize = std::mem::size_of::<$t>();
+        const SCALE: f64 = 1.0 / (1u64 << (N * 8 - 1)) as f64;
+        for (s, d) in $src.chunks_exact(N).zip($dst.iter_mut()) {
+            let v = <$t>::from_ne_bytes(s.try_into().unwrap());
+            *d = (v as f64 - $offset) * SCALE;
+        }
+    }};
+}
+
+macro_rules! encode_int {
+    ($src:expr, $dst:expr, $t:ty, $offset:expr) => {{
+        const N: usize = std::mem::size_of::<$t>();
+        const SCALE: f64 = (1u64 << (N * 8 - 1)) as f64;
+        for (&s, d) in $src.iter().zip($dst.chunks_exact_mut(N)) {
+            let s = if s.is_nan() { 0.0 } else { s };
+            let v = saturate!((s * SCALE).floor() + $offset, $t);
+            d.copy_from_slice(&v.to_ne_bytes());
+        }
+    }};
+}
+
+/// Decode the components of whole samples in `src` to normalized floats.
+fn decode(elem: Elem, src: &[u8], dst: &mut [f64]) {
+    match elem {
+        Elem::F64 => {
+            for (s, d) in src.chunks_exact(8).zip(dst.iter_mut()) {
+                *d = f64::from_ne_bytes(s.try_into().unwrap());
+            }
+        }
+        Elem::F32 => {
+            for (s, d) in src.chunks_exact(4).zip(dst.iter_mut()) {
+                *d = f32::from_ne_bytes(s.try_into().unwrap()) as f64;
+            }
+        }
+        Elem::S32 => decode_int!(src, dst, i32, 0.0),
+        Elem::U32 => decode_int!(src, dst, u32, 2147483648.0),
+        Elem::S16 => decode_int!(src, dst, i16, 0.0),
+        Elem::U16 => decode_int!(src, dst, u16, 32768.0),
+        Elem::S8 => decode_int!(src, dst, i8, 0.0),
+        Elem::U8 => decode_int!(src, dst, u8, 128.0),
+        Elem::S12Pair | Elem::U12Pair => {
+            // Flipping the sign bit converts offset binary to two's complement.
+            let offset = if elem == Elem::U12Pair { i16::MIN } else { 0 };
+            for (s, d) in src.chunks_exact(3).zip(dst.chunks_exact_mut(2)) {
+                let v = unpack_cs12(s[0], s[1], s[2]);
+                d[0] = (v.re ^ offset) as f64 / 32768.0;
+                d[1] = (v.im ^ offset) as f64 / 32768.0;
+            }
+        }
+        Elem::S4Pair | Elem::U4Pair => {
+            let offset = if elem == Elem::U4Pair { 0x88 } else { 0 };
+            for (&s, d) in src.iter().zip(dst.chunks_exact_mut(2)) {
+                let v = unpack_cs4(s ^ offset);
+                d[0] = v.re as f64 / 32768.0;
+                d[1] = v.im as f64 / 32768.0;
+            }
+        }
+    }
+}
+
+/// Encode normalized float components to whole samples in `dst`.
+fn encode(elem: Elem, src: &[f64], dst: &mut [u8]) {
+    match elem {
+        Elem::F64 => {
+            for (s, d) in src.iter().zip(dst.chunks_exact_mut(8)) {
+                d.copy_from_slice(&s.to_ne_bytes());
+            }
+        }
+        Elem::F32 => {
+            for (s, d) in src.iter().zip(dst.chunks_exact_mut(4)) {
+                d.copy_from_slice(&(*s as f32).to_ne_bytes());
+            }
+        }
+        Elem::S32 => encode_int!(src, dst, i32, 0.0),
+        Elem::U32 => encode_int!(src, dst, u32, 2147483648.0),
+        Elem::S16 => encode_int!(src, dst, i16, 0.0),
+        Elem::U16 => encode_int!(src, dst, u16, 32768.0),
+        Elem::S8 => encode_int!(src, dst, i8, 0.0),
+        Elem::U8 => encode_int!(src, dst, u8, 128.0),
+        Elem::S12Pair | Elem::U12Pair => {
+            let offset = if elem == Elem::U12Pair { 0x800 } else { 0 };
+            for (s, d) in src.chunks_exact(2).zip(dst.chunks_exact_mut(3)) {
+                let i = (quantize(s[0], 12) as u16 & 0xfff) ^ offset;
+                let q = (quantize(s[1], 12) as u16 & 0xfff) ^ offset;
+                d[0] = i as u8;
+                d[1] = ((i >> 8) | (q << 4)) as u8;
+                d[2] = (q >> 4) as u8;
+            }
+        }
+        Elem::S4Pair | Elem::U4Pair => {
+            let offset = if elem == Elem::U4Pair { 0x88 } else { 0 };
+            for (s, d) in src.chunks_exact(2).zip(dst.iter_mut()) {
+                let i = quantize(s[0], 4) as u8 & 0xf;
+                let q = quantize(s[1], 4) as u8 & 0xf;
+                *d = (i | (q << 4)) ^ offset;
+            }
+        }
+    }
+}
+
+/// Scale a normalized float to a signed integer of `bits` bits, saturating at full scale.
+fn quantize(x: f64, bits: u32) -> i32 {
+    let scale = (1 << (bits - 1)) as f64;
+    let x = (x * scale).floor();
+    if x.is_nan() {
+        0
+    } else {
+        x.clamp(-scale, scale - 1.0) as i32
+    }
+}
+
+/// Convert samples from one format to another.
+///
+/// `src` and `dst` contain samples in `src_format` and `dst_format` respectively, in native byte
+/// order, as read from or written to a [`RawRxStream`](crate::RawRxStream) or
+/// [`RawTxStream`](crate::RawTxStream).
+///
+/// Returns the number of samples converted, which is the smaller of the number of whole samples
+/// in `src` and `dst`.
+///
+/// Returns an error with [ErrorCode::NotSupported] if one format is complex and the other is
+/// real.
+///
+/// Conversions between 16-bit or 8-bit integers and 32-bit floats use the same vectorizable
+/// loops as the typed functions when the buffers are aligned for their sample types, as buffers
+/// allocated for those types are. Other conversions go through an intermediate 64-bit float
+/// buffer.
+///
+/// # Example
+/// ```
+/// use soapysdr::Format;
+/// let src: Vec<u8> = [0x4000i16, -0x8000].iter().flat_map(|x| x.to_ne_bytes()).collect();
+/// let mut dst = [0u8; 8];
+/// let n = soapysdr::convert::convert(Format::CS16, &src, Format::CF32, &mut dst).unwrap();
+/// assert_eq!(n, 1);
+/// assert_eq!(f32::from_ne_bytes(dst[0..4].try_into().unwrap()), 0.5);
+/// assert_eq!(f32::from_ne_bytes(dst[4..8].try_into().unwrap()), -1.0);
+/// ```
+pub fn convert(
+    src_format: Format,
+    src: &[u8],
+    dst_format: Format,
+    dst: &mut [u8],
+) -> Result<usize, Error> {
+    let (src_elem, src_components) = layout(src_format);
+    let (dst_elem, dst_components) = layout(dst_format);
+
+    if src_components != dst_components {
+        return Err(Error {
+            code: ErrorCode::NotSupported,
+            message: format!("Cannot convert from {} to {}", src_format, dst_format),
+        });
+    }
+
+    let src_size = sample_size(src_format);
+    let dst_size = sample_size(dst_format);
+    let n = usize::min(src.len() / src_size, dst.len() / dst_size);
+
+    if src_format == dst_format {
+        dst[..n * dst_size].copy_from_slice(&src[..n * src_size]);
+        return Ok(n);
+    }
+
+    if convert_direct(src_format, src, dst_format, dst, n * src_components) {
+        return Ok(n);
+    }
+
+    let mut tmp = [0f64; CHUNK * 2];
+    let mut start = 0;
+    while start < n {
+        let len = usize::min(CHUNK, n - start);
+        let tmp = &mut tmp[..len * src_components];
+        decode(
+            src_elem,
+            &src[start * src_size..(start + len) * src_size],
+            tmp,
+        );
+        encode(
+            dst_elem,
+            tmp,
+            &mut dst[start * dst_size..(start + len) * dst_size],
+        );
+        start += len;
+    }
+
+    Ok(n)
+}
+
+/// Convert `len` components of the most common format pairs with a loop over typed slices,
+/// which the compiler can vectorize.
+///
+/// Returns false if the pair has no direct path, or the buffers are not aligned for their types,
+/// in which case the caller uses the generic path.
+fn convert_direct(
+    src_format: Format,
+    src: &[u8],
+    dst_format: Format,
+    dst: &mut [u8],
+    len: usize,
+) -> bool {
+    use Format::*;
+    match (src_format, dst_format) {
+        (CS16, CF32) | (S16, F32) => convert_slice(src, dst, len, s16_to_f32),
+        (CF32, CS16) | (F32, S16) => convert_slice(src, dst, len, f32_to_s16),
+        (CS8, CF32) | (S8, F32) => convert_slice(src, dst, len, s8_to_f32),
+        (CU8, CF32) | (U8, F32) => convert_slice(src, dst, len, u8_to_f32),
+        _ => false,
+    }
+}
+
+/// A primitive type that is valid for any bit pattern, so a byte buffer can be viewed as a
+/// slice of it.
+///
+/// # Safety
+/// Every bit pattern of the type's size must be a valid value.
+unsafe trait Plain: Copy {}
+unsafe impl Plain for i8 {}
+unsafe impl Plain for u8 {}
+unsafe impl Plain for i16 {}
+unsafe impl Plain for f32 {}
+
+fn convert_slice<S: Plain, D: Plain>(
+    src: &[u8],
+    dst: &mut [u8],
+    len: usize,
+    f: impl Fn(S) -> D,
+) -> bool {
+    // SAFETY: `Plain` types are valid for any bit pattern.
+    let (src_prefix, src, _) = unsafe { src.align_to::<S>() };
+    let (dst_prefix, dst, _) = unsafe { dst.align_to_mut::<D>() };
+    if !src_prefix.is_empty() || !dst_prefix.is_empty() {
+        return false;
+    }
+    for (d, &s) in dst[..len].iter_mut().zip(&src[..len]) {
+        *d = f(s);
+    }
+    true
+}
+
+#[inline]
+fn s16_to_f32(x: i16) -> f32 {
+    x as f32 / 32768.0
+}
+
+#[inline]
+fn f32_to_s16(x: f32) -> i16 {
+    saturate!((x * 32768.0).floor(), i16)
+}
+
+#[inline]
+fn s8_to_f32(x: i8) -> f32 {
+    x as f32 / 128.0
+}
+
+#[inline]
+fn u8_to_f32(x: u8) -> f32 {
+    (x as f32 - 128.0) / 128.0
+}
+
+/// Convert [CS16](Format::CS16) samples to [CF32](Format::CF32), scaled to the range -1.0 to 1.0.
+///
+/// Returns the number of samples converted, which is the smaller of the lengths of `src` and
+/// `dst`.
+pub fn cs16_to_cf32(src: &[Complex<i16>], dst: &mut [Complex<f32>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (s, d) in src[..n].iter().zip(dst[..n].iter_mut()) {
+        *d = Complex::new(s16_to_f32(s.re), s16_to_f32(s.im));
+    }
+    n
+}
+
+/// Convert [CF32](Format::CF32) samples in the range -1.0 to 1.0 to [CS16](Format::CS16).
+///
+/// Values outside the range saturate, and NaN converts to zero. Returns the number of samples converted, which is the
+/// smaller of the lengths of `src` and `dst`.
+pub fn cf32_to_cs16(src: &[Complex<f32>], dst: &mut [Complex<i16>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (s, d) in src[..n].iter().zip(dst[..n].iter_mut()) {
+        *d = Complex::new(f32_to_s16(s.re), f32_to_s16(s.im));
+    }
+    n
+}
+
+/// Convert [CS8](Format::CS8) samples to [CF32](Format::CF32), scaled to the range -1.0 to 1.0.
+///
+/// Returns the number of samples converted, which is the smaller of the lengths of `src` and
+/// `dst`.
+pub fn cs8_to_cf32(src: &[Complex<i8>], dst: &mut [Complex<f32>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (s, d) in src[..n].iter().zip(dst[..n].iter_mut()) {
+        *d = Complex::new(s8_to_f32(s.re), s8_to_f32(s.im));
+    }
+    n
+}
+
+/// Convert [CU8](Format::CU8) samples, as produced by rtl-sdr, to [CF32](Format::CF32).
+///
+/// The offset of 128 is removed and the result scaled to the range -1.0 to 1.0.
+/// Returns the number of samples converted, which is the smaller of the lengths of `src` and
+/// `dst`.
+///
+/// # Example
+/// ```
+/// use num_complex::Complex;
+/// let mut out = [Complex::new(0.0, 0.0); 1];
+/// soapysdr::convert::cu8_to_cf32(&[Complex::new(192, 0)], &mut out);
+/// assert_eq!(out[0], Complex::new(0.5, -1.0));
+/// ```
+pub fn cu8_to_cf32(src: &[Complex<u8>], dst: &mut [Complex<f32>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (s, d) in src[..n].iter().zip(dst[..n].iter_mut()) {
+        *d = Complex::new(u8_to_f32(s.re), u8_to_f32(s.im));
+    }
+    n
+}
+
+/// Unpack [CS12](crate::Format::CS12) samples to 16-bit complex integers.
+///
+/// Each sample is three bytes, with the 12-bit I component in the low bits. The resulting values
+/// are scaled to the full 16-bit range, matching SoapySDR's own converter.
+///
+/// Returns the number of samples converted, which is the smaller of the number of whole samples
+/// in `src` and the length of `dst`.
+///
+/// # Example
+/// ```
+/// use num_complex::Complex;
+/// let mut out = [Complex::new(0, 0); 1];
+/// assert_eq!(soapysdr::convert::cs12_to_cs16(&[0xff, 0x07, 0x80], &mut out), 1);
+/// assert_eq!(out[0], Complex::new(0x7ff0, -0x8000));
+/// ```
+pub fn cs12_to_cs16(src: &[u8], dst: &mut [Complex<i16>]) -> usize {
+    let n = usize::min(src.len() / 3, dst.len());
+    for (s, d) in src.chunks_exact(3).zip(dst[..n].iter_mut()) {
+        *d = unpack_cs12(s[0], s[1], s[2]);
+    }
+    n
+}
+
+/// Unpack [CS12](crate::Format::CS12) samples to complex floats, scaled to the range -1.0 to 1.0.
+///
+/// Returns the number of samples converted, which is the smaller of the number of whole samples
+/// in `src` and the length of `dst`.
+pub fn cs12_to_cf32(src: &[u8], dst: &mut [Complex<f32>]) -> usize {
+    let n = usize::min(src.len() / 3, dst.len());
+    for (s, d) in src.chunks_exact(3).zip(dst[..n].iter_mut()) {
+        let v = unpack_cs12(s[0], s[1], s[2]);
+        *d = Complex::new(v.re as f32 / 32768.0, v.im as f32 / 32768.0);
+    }
+    n
+}
+
+/// Unpack one CS12 sample, scaling each 12-bit component to the full 16-bit range.
+fn unpack_cs12(b0: u8, b1: u8, b2: u8) -> Complex<i16> {
+    let (b0, b1, b2) = (b0 as u16, b1 as u16, b2 as u16);
+    Complex::new(
+        ((b1 << 12) | (b0 << 4)) as i16,
+        ((b2 << 8) | (b1 & 0xf0)) as i16,
+    )
+}
+
+/// Unpack [CS4](crate::Format::CS4) samples to 16-bit complex integers.
+///
+/// Each sample is one byte, with the 4-bit I component in the low nibble. The resulting values
+/// are scaled to the full 16-bit range.
+///
+/// Returns the number of samples converted, which is the smaller of the lengths of `src` and
+/// `dst`.
+///
+/// # Example
+/// ```
+/// use num_complex::Complex;
+/// let mut out = [Complex::new(0, 0); 1];
+/// assert_eq!(soapysdr::convert::cs4_to_cs16(&[0x87], &mut out), 1);
+/// assert_eq!(out[0], Complex::new(0x7000, -0x8000));
+/// ```
+pub fn cs4_to_cs16(src: &[u8], dst: &mut [Complex<i16>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (&s, d) in src.iter().zip(dst[..n].iter_mut()) {
+        *d = unpack_cs4(s);
+    }
+    n
+}
+
+/// Unpack [CS4](crate::Format::CS4) samples to complex floats, scaled to the range -1.0 to 1.0.
+///
+/// Returns the number of samples converted, which is the smaller of the lengths of `src` and
+/// `dst`.
+pub fn cs4_to_cf32(src: &[u8], dst: &mut [Complex<f32>]) -> usize {
+    let n = usize::min(src.len(), dst.len());
+    for (&s, d) in src.iter().zip(dst[..n].iter_mut()) {
+        let v = unpack_cs4(s);
+        *d = Complex::new(v.re as f32 / 32768.0, v.im as f32 / 32768.0);
+    }
+    n
+}
+
+/// Unpack one CS4 sample, scaling each 4-bit component to the full 16-bit range.
+fn unpack_cs4(b: u8) -> Complex<i16> {
+    let b = b as u16;
+    Complex::new((b << 12) as i16, ((b & 0xf0) << 8) as i16)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+
+    const FORMATS: [Format; 20] = [
+        Format::CF64,
+        Format::CF32,
+        Format::CS32,
+        Format::CU32,
+        Format::CS16,
+        Format::CU16,
+        Format::CS12,
+        Format::CU12,
+        Format::CS8,
+        Format::CU8,
+        Format::CS4,
+        Format::CU4,
+        Format::F64,
+        Format::F32,
+        Format::S32,
+        Format::U32,
+        Format::S16,
+        Format::U16,
+        Format::S8,
+        Format::U8,
+    ];
+
+    /// Convert `samples` to `via` and back, and check that they are unchanged.
+    fn assert_round_trip(format: Format, samples: &[u8], via: Format) {
+        let n = samples.len() / sample_size(format);
+        let mut mid = vec![0u8; n * sample_size(via)];
+        assert_eq!(convert(format, samples, via, &mut mid).unwrap(), n);
+        let mut out = vec![0u8; samples.len()];
+        assert_eq!(convert(via, &mid, format, &mut out).unwrap(), n);
+        if let Some(i) = (0..samples.len()).find(|&i| out[i] != samples[i]) {
+            let size = sample_size(format);
+            let sample = i / size * size;
+            panic!(
+                "{} via {}: {:02x?} became {:02x?}",
+                format,
+                via,
+                &samples[sample..sample + size],
+                &out[sample..sample + size]
+            );
+        }
+    }
+
+    #[test]
+    fn round_trip_8_bit() {
+        let samples: Vec<u8> = (0..=u16::MAX).flat_map(|x| x.to_ne_bytes()).collect();
+        for format in [Format::CS8, Format::CU8] {
+            for via in [Format::CF32, Format::CF64, Format::CS16, Format::CS32] {
+                assert_round_trip(format, &samples, via);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_4_bit() {
+        let samples: Vec<u8> = (0..=u8::MAX).collect();
+        for format in [Format::CS4, Format::CU4] {
+            for via in [Format::CF32, Format::CF64, Format::CS8, Format::CS16] {
+                assert_round_trip(format, &samples, via);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_12_bit() {
+        // Every value of each component, against a spread of values of the other that covers
+        // every nibble of the shared middle byte.
+        let pairs =
+            (0..4096u16).flat_map(|x| (0..4096).step_by(61).flat_map(move |y| [(x, y), (y, x)]));
+        let samples: Vec<u8> = pairs
+            .flat_map(|(i, q)| [i as u8, ((i >> 8) | (q << 4)) as u8, (q >> 4) as u8])
+            .collect();
+        for format in [Format::CS12, Format::CU12] {
+            for via in [Format::CF32, Format::CF64, Format::CS16] {
+                assert_round_trip(format, &samples, via);
+            }
+        }
+    }
+
+    #[test]
+    fn round_trip_16_bit() {
+        // Every value of each component, paired with a different value of the other.
+        let samples: Vec<u8> = (0..=u16::MAX)
+            .flat_map(|x| [x.to_ne_bytes(), (!x).rotate_left(3).to_ne_bytes()])
+            .flatten()
+            .collect();
+        for format in [Format::CS16, Format::CU16] {
+            for via in [Format::CF32, Format::CF64, Format::CS32] {
+                assert_round_trip(format, &samples, via);
+            }
+        }
+    }
+
+    #[test]
+    fn float_to_int_saturates() {
+        let src = [
+            1.0f32,
+            -1.0,
+            2.0,
+            -2.0,
+            f32::INFINITY,
+            f32::NEG_INFINITY,
+            f32::NAN,
+            0.5,
+        ];
+        let src: Vec<Complex<f32>> = src.chunks(2).map(|c| Complex::new(c[0], c[1])).collect();
+        let mut dst = [Complex::new(0i16, 0); 4];
+        cf32_to_cs16(&src, &mut dst);
+        assert_eq!(
+            dst,
+            [
+                Complex::new(i16::MAX, i16::MIN),
+                Complex::new(i16::MAX, i16::MIN),
+                Complex::new(i16::MAX, i16::MIN),
+                Complex::new(0, 0x4000),
+            ]
+        );
+
+        let src: Vec<u8> = [1.5f64, -1.5, f64::NAN, -0.5]
+            .iter()
+            .flat_map(|x| x.to_ne_bytes())
+            .collect();
+        let mut dst = [0u8; 4];
+        convert(Format::CF64, &src, Format::CU8, &mut dst).unwrap();
+        assert_eq!(dst, [255, 0, 128, 64]);
+        let mut dst = [0u8; 2];
+        convert(Format::CF64, &src, Format::CS4, &mut dst).unwrap();
+        assert_eq!(dst, [0x87, 0xc0]);
+    }
+
+    #[test]
+    fn complex_to_real_is_not_supported() {
+        let err = convert(Format::CS16, &[0; 4], Format::F32, &mut [0; 4]).unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+    }
+
+    /// Check that two buffers in `format` hold the same samples, treating all NaNs as equal.
+    fn assert_same_samples(format: Format, a: &[u8], b: &[u8]) {
+        match layout(format).0 {
+            Elem::F64 => {
+                for (x, y) in a.chunks_exact(8).zip(b.chunks_exact(8)) {
+                    let x = f64::from_ne_bytes(x.try_into().unwrap());
+                    let y = f64::from_ne_bytes(y.try_into().unwrap());
+                    assert!(x == y || (x.is_nan() && y.is_nan()), "{} != {}", x, y);
+                }
+            }
+            Elem::F32 => {
+                for (x, y) in a.chunks_exact(4).zip(b.chunks_exact(4)) {
+                    let x = f32::from_ne_bytes(x.try_into().unwrap());
+                    let y = f32::from_ne_bytes(y.try_into().unwrap());
+                    assert!(x == y || (x.is_nan() && y.is_nan()), "{} != {}", x, y);
+                }
+            }
+            _ => assert_eq!(a, b),
+        }
+    }
+
+    /// An 8-byte aligned buffer, so tests control whether the direct paths can be used.
+    fn aligned(bytes: &[u8]) -> (Vec<u64>, usize) {
+        let mut buf = vec![0u64; bytes.len().div_ceil(8) + 1];
+        // SAFETY: any bytes are valid u64s.
+        unsafe { buf.align_to_mut::<u8>().1[..bytes.len()].copy_from_slice(bytes) };
+        (buf, bytes.len())
+    }
+
+    fn bytes(buf: &[u64], len: usize) -> &[u8] {
+        &unsafe { buf.align_to::<u8>() }.1[..len]
+    }
+
+    fn bytes_mut(buf: &mut [u64], len: usize) -> &mut [u8] {
+        &mut unsafe { buf.align_to_mut::<u8>() }.1[..len]
+    }
+
+    proptest! {
+        #[test]
+        fn converts_whole_samples(
+            src_format in 0..FORMATS.len(),
+            dst_format in 0..FORMATS.len(),
+            src in vec(any::<u8>(), 0..128),
+            dst_len in 0..256usize,
+        ) {
+            let (src_format, dst_format) = (FORMATS[src_format], FORMATS[dst_format]);
+            let mut dst = vec![0xa5u8; dst_len];
+            match convert(src_format, &src, dst_format, &mut dst) {
+                Ok(n) => {
+                    let dst_size = sample_size(dst_format);
+                    prop_assert_eq!(
+                        n,
+                        usize::min(src.len() / sample_size(src_format), dst_len / dst_size)
+                    );
+                    prop_assert!(dst[n * dst_size..].iter().all(|&b| b == 0xa5));
+                }
+                Err(e) => {
+                    prop_assert_eq!(e.code, ErrorCode::NotSupported);
+                    prop_assert_ne!(layout(src_format).1, layout(dst_format).1);
+                }
+            }
+        }
+
+        /// Converting directly gives the same result as converting through 64-bit floats, and
+        /// the direct paths for aligned buffers match the generic path for unaligned ones.
+        #[test]
+        fn matches_generic_path(
+            src_format in 0..FORMATS.len(),
+            dst_format in 0..FORMATS.len(),
+            src in vec(any::<u8>(), 0..128),
+        ) {
+            let (src_format, dst_format) = (FORMATS[src_format], FORMATS[dst_format]);
+            let (src_components, dst_components) = (layout(src_format).1, layout(dst_format).1);
+            prop_assume!(src_components == dst_components);
+            let via = if src_components == 2 { Format::CF64 } else { Format::F64 };
+
+            let n = src.len() / sample_size(src_format);
+            let dst_len = n * sample_size(dst_format);
+
+            let (src_buf, src_len) = aligned(&src);
+            let (mut direct, _) = aligned(&vec![0; dst_len]);
+            convert(src_format, bytes(&src_buf, src_len), dst_format, bytes_mut(&mut direct, dst_len))
+                .unwrap();
+            let direct = bytes(&direct, dst_len);
+
+            let mut mid = vec![0u8; n * sample_size(via)];
+            convert(src_format, &src, via, &mut mid).unwrap();
+            let mut two_step = vec![0u8; dst_len];
+            convert(via, &mid, dst_format, &mut two_step).unwrap();
+            assert_same_samples(dst_format, direct, &two_step);
+
+            // Offset both buffers by one byte so they are misaligned for every multi-byte type.
+            let (mut src_buf, _) = aligned(&[&[0][..], &src].concat());
+            let (mut unaligned, _) = aligned(&vec![0; dst_len + 1]);
+            let src_bytes = &bytes_mut(&mut src_buf, src_len + 1)[1..];
+            convert(src_format, src_bytes, dst_format, &mut bytes_mut(&mut unaligned, dst_len + 1)[1..])
+                .unwrap();
+            assert_same_samples(dst_format, direct, &bytes(&unaligned, dst_len + 1)[1..]);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use crate::{Device, Direction, Error};
+
+/// The frontend corrections supported by a channel, returned by [`Corrections::supported`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash, Default)]
+pub struct SupportedCorrections {
+    /// Automatic DC offset correction can be enabled.
+    pub dc_offset_mode: bool,
+
+    /// A manual DC offset correction can be set.
+    pub dc_offset: bool,
+
+    /// Automatic IQ balance correction can be enabled.
+    pub iq_balance_mode: bool,
+
+    /// A manual IQ balance correction can be set.
+    pub iq_balance: bool,
+}
+
+/// The state of a channel's frontend corrections, returned by [`Corrections::snapshot`].
+///
+/// Each field is `None` if the correction is not supported by the channel.
+#[derive(Copy, Clone, PartialEq, Debug, Default)]
+pub struct CorrectionSettings {
+    /// Whether automatic DC offset correction is enabled.
+    pub dc_offset_mode: Option<bool>,
+
+    /// The manual DC offset correction for (I, Q).
+    pub dc_offset: Option<(f64, f64)>,
+
+    /// Whether automatic IQ balance correction is enabled.
+    pub iq_balance_mode: Option<bool>,
+
+    /// The manual IQ balance correction for (I, Q).
+    pub iq_balance: Option<(f64, f64)>,
+}
+
+/// The DC offset and IQ balance corrections of a channel.
+///
+/// To obtain a Corrections view, call [`Device::corrections`].
+///
+/// # Example
+/// ```no_run
+/// # let dev = soapysdr::Device::new("driver=lime").unwrap();
+/// let corrections = dev.corrections(soapysdr::Direction::Rx, 0);
+/// let saved = corrections.snapshot().unwrap();
+/// corrections.set_dc_offset_mode(false).unwrap();
+/// // ... calibrate ...
+/// corrections.restore(&saved).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Corrections {
+    device: Device,
+    direction: Direction,
+    channel: usize,
+}
+
+impl Device {
+    /// Get a view of the frontend corrections of a channel.
+    pub fn corrections(&self, direction: Direction, channel: usize) -> Corrections {
+        Corrections {
+            device: self.clone(),
+            direction,
+            channel,
+        }
+    }
+}
+
+impl Corrections {
+    /// Get the direction of the channel.
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    /// Get the channel number.
+    pub fn channel(&self) -> usize {
+        self.channel
+    }
+
+    /// Query which corrections the channel supports.
+    pub fn supported(&self) -> Result<SupportedCorrections, Error> {
+        let (dev, dir, ch) = (&self.device, self.direction, self.channel);
+        Ok(SupportedCorrections {
+            dc_offset_mode: dev.has_dc_offset_mode(dir, ch)?,
+            dc_offset: dev.has_dc_offset(dir, ch)?,
+            iq_balance_mode: dev.has_iq_balance_mode(dir, ch)?,
+            iq_balance: dev.has_iq_balance(dir, ch)?,
+        })
+    }
+
+    /// Returns true if automatic DC offset correction is enabled.
+    pub fn dc_offset_mode(&self) -> Result<bool, Error> {
+        self.device.dc_offset_mode(self.direction, self.channel)
+    }
+
+    /// Enable or disable automatic DC offset correction.
+    pub fn set_dc_offset_mode(&self, automatic: bool) -> Result<(), Error> {
+        self.device
+            .set_dc_offset_mode(self.direction, self.channel, automatic)
+    }
+
+    /// Get the manual DC offset correction for (I, Q).
+    pub fn dc_offset(&self) -> Result<(f64, f64), Error> {
+        self.device.dc_offset(self.direction, self.channel)
+    }
+
+    /// Set the manual DC offset correction for (I, Q), 1.0 max.
+    pub fn set_dc_offset(&self, offset: (f64, f64)) -> Result<(), Error> {
+        self.device
+            .set_dc_offset(self.direction, self.channel, offset.0, offset.1)
+    }
+
+    /// Returns true if automatic IQ balance correction is enabled.
+    pub fn iq_balance_mode(&self) -> Result<bool, Error> {
+        self.device.iq_balance_mode(self.direction, self.channel)
+    }
+
+    /// Enable or disable automatic IQ balance correction.
+    pub fn set_iq_balance_mode(&self, automatic: bool) -> Result<(), Error> {
+        self.device
+            .set_iq_balance_mode(self.direction, self.channel, automatic)
+    }
+
+    /// Get the manual IQ balance correction for (I, Q).
+    pub fn iq_balance(&self) -> Result<(f64, f64), Error> {
+        self.device.iq_balance(self.direction, self.channel)
+    }
+
+    /// Set the manual IQ balance correction for (I, Q), 1.0 max.
+    pub fn set_iq_balance(&self, balance: (f64, f64)) -> Result<(), Error> {
+        self.device
+            .set_iq_balance(self.direction, self.channel, balance.0, balance.1)
+    }
+
+    /// Read the current state of all supported corrections.
+    pub fn snapshot(&self) -> Result<CorrectionSettings, Error> {
+        let supported = self.supported()?;
+        Ok(CorrectionSettings {
+            dc_offset_mode: supported
+                .dc_offset_mode
+                .then(|| self.dc_offset_mode())
+                .transpose()?,
+            dc_offset: supported.dc_offset.then(|| self.dc_offset()).transpose()?,
+            iq_balance_mode: supported
+                .iq_balance_mode
+                .then(|| self.iq_balance_mode())
+                .transpose()?,
+            iq_balance: supported
+                .iq_balance
+                .then(|| self.iq_balance())
+                .transpose()?,
+        })
+    }
+
+    /// Apply the corrections in `settings`, skipping those that are `None`.
+    ///
+    /// Automatic modes being disabled are turned off before the manual values are set, and
+    /// those being enabled are turned on afterwards, so that the manual values are not
+    /// overwritten by an automatic correction.
+    pub fn restore(&self, settings: &CorrectionSettings) -> Result<(), Error> {
+        if settings.dc_offset_mode == Some(false) {
+            self.set_dc_offset_mode(false)?;
+        }
+        if settings.iq_balance_mode == Some(false) {
+            self.set_iq_balance_mode(false)?;
+        }
+        if let Some(offset) = settings.dc_offset {
+            self.set_dc_offset(offset)?;
+        }
+        if let Some(balance) = settings.iq_balance {
+            self.set_iq_balance(balance)?;
+        }
+        if settings.dc_offset_mode == Some(true) {
+            self.set_dc_offset_mode(true)?;
+        }
+        if settings.iq_balance_mode == Some(true) {
+            self.set_iq_balance_mode(true)?;
+        }
+        Ok(())
+    }
+}
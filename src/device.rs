@@ -3,11 +3,12 @@ use soapysdr_sys::*;
 use std::ffi::{CStr, CString};
 use std::marker::PhantomData;
 use std::os::raw::c_void;
-use std::os::raw::{c_char, c_int};
+use std::os::raw::{c_char, c_int, c_long};
 use std::slice;
 use std::sync::Arc;
+use std::{panic, thread};
 
-use super::{ArgInfo, Args, Format, StreamSample};
+use super::{ArgInfo, Args, Format, GpioBank, HardwareTime, StreamSample, Uart};
 use crate::arginfo::arg_info_from_c;
 
 /// An error code from SoapySDR
@@ -46,7 +47,7 @@ pub enum ErrorCode {
 }
 
 impl ErrorCode {
-    fn from_c(code: c_int) -> ErrorCode {
+    pub(crate) fn from_c(code: c_int) -> ErrorCode {
         match code {
             soapysdr_sys::SOAPY_SDR_TIMEOUT => ErrorCode::Timeout,
             soapysdr_sys::SOAPY_SDR_STREAM_ERROR => ErrorCode::StreamError,
@@ -209,6 +210,19 @@ unsafe fn arg_info_result<F: FnOnce(*mut usize) -> *mut SoapySDRArgInfo>(
     }
 }
 
+/// Convert an `ArgInfo` returned by value, where an empty key means there is no information.
+unsafe fn single_arg_info_result(mut info: SoapySDRArgInfo) -> Result<Option<ArgInfo>, Error> {
+    unsafe {
+        let r = if info.key.is_null() || *info.key == 0 {
+            None
+        } else {
+            Some(arg_info_from_c(&info))
+        };
+        SoapySDRArgInfo_clear(&mut info as *mut _);
+        check_error(r)
+    }
+}
+
 unsafe fn list_result<T: Copy, F: FnOnce(*mut usize) -> *mut T>(f: F) -> Result<Vec<T>, Error> {
     unsafe {
         let mut len: usize = 0;
@@ -269,6 +283,41 @@ impl Device {
         }
     }
 
+    /// Open several devices in parallel.
+    ///
+    /// Returns one result per element of `args`, in the same order.
+    ///
+    /// Each device is opened on its own thread, so an error is reported for the device that
+    /// caused it, and the devices that opened successfully are returned to be used or dropped.
+    ///
+    /// # Example
+    /// ```
+    /// let args: [soapysdr::Args; 2] = ["type=null".into(), "type=null".into()];
+    /// let devices = soapysdr::Device::open_many(&args);
+    /// assert!(devices.iter().all(|d| d.is_ok()));
+    /// ```
+    pub fn open_many(args: &[Args]) -> Vec<Result<Device, Error>> {
+        // `Args` can't be sent to another thread, so copy the pairs and rebuild it there.
+        let owned: Vec<Vec<(String, String)>> = args
+            .iter()
+            .map(|a| {
+                a.iter()
+                    .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                    .collect()
+            })
+            .collect();
+        thread::scope(|s| {
+            let handles: Vec<_> = owned
+                .iter()
+                .map(|a| s.spawn(move || Device::new(a.iter().cloned().collect::<Args>())))
+                .collect();
+            handles
+                .into_iter()
+                .map(|h| h.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+                .collect()
+        })
+    }
+
     #[doc(hidden)]
     pub fn null_device() -> Device {
         Device::new("type=null").unwrap()
@@ -512,26 +561,15 @@ impl Device {
         channels: &[usize],
         args: A,
     ) -> Result<RxStream<E>, Error> {
-        unsafe {
-            let stream = check_error(SoapySDRDevice_setupStream(
-                self.inner.ptr,
-                Direction::Rx.into(),
-                E::STREAM_FORMAT.as_ptr(),
-                channels.as_ptr(),
-                channels.len(),
-                args.into().as_raw_const(),
-            ))?;
-            Ok(RxStream {
-                device: self.clone(),
-                handle: stream,
-                nchannels: channels.len(),
-                flags: 0,
-                time_ns: 0,
-                active: false,
-                buf_ptrs: vec![std::ptr::null_mut(); channels.len()],
-                phantom: PhantomData,
-            })
-        }
+        let handle =
+            StreamHandle::setup(self, Direction::Rx, E::STREAM_FORMAT, channels, args.into())?;
+        Ok(RxStream {
+            handle,
+            flags: 0,
+            time_ns: 0,
+            buf_ptrs: vec![std::ptr::null_mut(); channels.len()],
+            phantom: PhantomData,
+        })
     }
 
     /// Initialize a TX stream given a list of channels and stream arguments.
@@ -545,24 +583,61 @@ impl Device {
         channels: &[usize],
         args: A,
     ) -> Result<TxStream<E>, Error> {
-        unsafe {
-            let stream = check_error(SoapySDRDevice_setupStream(
-                self.inner.ptr,
-                Direction::Tx.into(),
-                E::STREAM_FORMAT.as_ptr(),
-                channels.as_ptr(),
-                channels.len(),
-                args.into().as_raw_const(),
-            ))?;
-            Ok(TxStream {
-                device: self.clone(),
-                handle: stream,
-                nchannels: channels.len(),
-                active: false,
-                buf_ptrs: vec![std::ptr::null_mut(); channels.len()],
-                phantom: PhantomData,
-            })
-        }
+        let handle =
+            StreamHandle::setup(self, Direction::Tx, E::STREAM_FORMAT, channels, args.into())?;
+        Ok(TxStream {
+            handle,
+            buf_ptrs: vec![std::ptr::null(); channels.len()],
+            phantom: PhantomData,
+        })
+    }
+
+    /// Initialize an RX stream with a sample format selected at runtime.
+    ///
+    /// This allows use of formats such as [Format::CS12] that have no corresponding Rust type.
+    pub fn raw_rx_stream(&self, format: Format, channels: &[usize]) -> Result<RawRxStream, Error> {
+        self.raw_rx_stream_args(format, channels, ())
+    }
+
+    /// Initialize an RX stream with a sample format selected at runtime and stream arguments.
+    pub fn raw_rx_stream_args<A: Into<Args>>(
+        &self,
+        format: Format,
+        channels: &[usize],
+        args: A,
+    ) -> Result<RawRxStream, Error> {
+        let handle = StreamHandle::setup(self, Direction::Rx, format, channels, args.into())?;
+        Ok(RawRxStream {
+            handle,
+            format,
+            sample_size: format.size(),
+            flags: 0,
+            time_ns: 0,
+            buf_ptrs: vec![std::ptr::null_mut(); channels.len()],
+        })
+    }
+
+    /// Initialize a TX stream with a sample format selected at runtime.
+    ///
+    /// This allows use of formats such as [Format::CS12] that have no corresponding Rust type.
+    pub fn raw_tx_stream(&self, format: Format, channels: &[usize]) -> Result<RawTxStream, Error> {
+        self.raw_tx_stream_args(format, channels, ())
+    }
+
+    /// Initialize a TX stream with a sample format selected at runtime and stream arguments.
+    pub fn raw_tx_stream_args<A: Into<Args>>(
+        &self,
+        format: Format,
+        channels: &[usize],
+        args: A,
+    ) -> Result<RawTxStream, Error> {
+        let handle = StreamHandle::setup(self, Direction::Tx, format, channels, args.into())?;
+        Ok(RawTxStream {
+            handle,
+            format,
+            sample_size: format.size(),
+            buf_ptrs: vec![std::ptr::null(); channels.len()],
+        })
     }
 
     /// Get a list of available antennas to select on a given chain.
@@ -738,6 +813,85 @@ impl Device {
         }
     }
 
+    /// Does the device support automatic frontend IQ balance correction?
+    ///
+    /// Returns true if automatic IQ balance corrections are supported.
+    pub fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        unsafe {
+            check_error(SoapySDRDevice_hasIQBalanceMode(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+            ))
+        }
+    }
+
+    /// Enable or disable automatic frontend IQ balance correction.
+    pub fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        unsafe {
+            SoapySDRDevice_setIQBalanceMode(self.inner.ptr, direction.into(), channel, automatic);
+            check_error(())
+        }
+    }
+
+    /// Returns true if automatic IQ balance correction is enabled
+    pub fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        unsafe {
+            check_error(SoapySDRDevice_getIQBalanceMode(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+            ))
+        }
+    }
+
+    /// Does the device support frontend frequency correction?
+    ///
+    /// Returns true if frequency corrections are supported. See [`Device::ppm_tuner`] for a
+    /// correction that also works on devices without support.
+    pub fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        unsafe {
+            check_error(SoapySDRDevice_hasFrequencyCorrection(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+            ))
+        }
+    }
+
+    /// Set the frontend frequency correction value in parts per million (PPM).
+    pub fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        unsafe {
+            SoapySDRDevice_setFrequencyCorrection(self.inner.ptr, direction.into(), channel, ppm);
+            check_error(())
+        }
+    }
+
+    /// Get the frontend frequency correction value in parts per million (PPM).
+    pub fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        unsafe {
+            check_error(SoapySDRDevice_getFrequencyCorrection(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+            ))
+        }
+    }
+
     /// List available amplification elements.
     ///
     /// Elements should be in order RF to baseband.
@@ -1097,6 +1251,23 @@ impl Device {
         }
     }
 
+    /// Get the list of discrete baseband sample rates supported by the chain.
+    ///
+    /// This is deprecated in SoapySDR in favor of
+    /// [`get_sample_rate_range`](Device::get_sample_rate_range), but some drivers only
+    /// implement the list.
+    pub fn list_sample_rates(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<f64>, Error> {
+        unsafe {
+            list_result(|len_ptr| {
+                SoapySDRDevice_listSampleRates(self.inner.ptr, direction.into(), channel, len_ptr)
+            })
+        }
+    }
+
     /// Get the baseband filter width of the chain in Hz
     pub fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
         unsafe {
@@ -1134,6 +1305,18 @@ impl Device {
         }
     }
 
+    /// Get the list of discrete baseband filter widths supported by the chain.
+    ///
+    /// This is deprecated in SoapySDR in favor of
+    /// [`bandwidth_range`](Device::bandwidth_range), but some drivers only implement the list.
+    pub fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        unsafe {
+            list_result(|len_ptr| {
+                SoapySDRDevice_listBandwidths(self.inner.ptr, direction.into(), channel, len_ptr)
+            })
+        }
+    }
+
     /// List time sources
     pub fn list_time_sources(&self) -> Result<Vec<String>, Error> {
         unsafe {
@@ -1167,12 +1350,12 @@ impl Device {
         }
     }
 
-    /// Get the current timestamp in ns
-    pub fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<i64, Error> {
+    /// Get the current hardware time
+    pub fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
         let hw_time_source = optional_string_arg(hw_time_source);
         unsafe {
             let tstamp = SoapySDRDevice_getHardwareTime(self.inner.ptr, hw_time_source.as_ptr());
-            check_error(tstamp)
+            check_error(HardwareTime::from_ns(tstamp))
         }
     }
 
@@ -1181,15 +1364,74 @@ impl Device {
     pub fn set_hardware_time(
         &self,
         hw_time_source: Option<&str>,
-        new_time_ns: i64,
+        new_time: HardwareTime,
     ) -> Result<(), Error> {
         let hw_time_source = optional_string_arg(hw_time_source);
         unsafe {
-            SoapySDRDevice_setHardwareTime(self.inner.ptr, new_time_ns, hw_time_source.as_ptr());
+            SoapySDRDevice_setHardwareTime(
+                self.inner.ptr,
+                new_time.as_ns(),
+                hw_time_source.as_ptr(),
+            );
             check_error(())
         }
     }
 
+    /// Set the time at which subsequent control commands, such as tuning or gain changes, take
+    /// effect, until it is [cleared](Device::clear_command_time).
+    ///
+    /// `what` optionally selects a specific command queue. Prefer [`Device::at_time`], which
+    /// clears the command time when done.
+    pub fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        let what = optional_string_arg(what);
+        unsafe {
+            check_ret_error(SoapySDRDevice_setCommandTime(
+                self.inner.ptr,
+                time.as_ns(),
+                what.as_ptr(),
+            ))
+        }
+    }
+
+    /// Clear the command time, so that control commands take effect immediately.
+    pub fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        self.set_command_time(HardwareTime::ZERO, what)
+    }
+
+    /// Run `f` with the command time set to `time`, so that the control commands it issues take
+    /// effect at that hardware time.
+    ///
+    /// The command time is set for the default command queue, and cleared when `f` returns, even
+    /// if it returns an error or panics. An error from `f` takes precedence over an error
+    /// clearing the command time. Use [`set_command_time`](Device::set_command_time) to select a
+    /// specific queue.
+    ///
+    /// Calls should not be nested: the inner call clears the command time when it returns, so
+    /// commands issued after it by the outer `f` take effect immediately.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use soapysdr::Direction::Rx;
+    /// use std::time::Duration;
+    /// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+    /// let t = dev.get_hardware_time(None).unwrap() + Duration::from_millis(10);
+    /// dev.at_time(t, |d| {
+    ///     d.set_frequency(Rx, 0, 915e6, "")?;
+    ///     d.set_gain(Rx, 0, 30.0)
+    /// })
+    /// .unwrap();
+    /// ```
+    pub fn at_time<T, F>(&self, time: HardwareTime, f: F) -> Result<T, Error>
+    where
+        F: FnOnce(&Device) -> Result<T, Error>,
+    {
+        scoped(
+            || self.set_command_time(time, None),
+            || self.clear_command_time(None),
+            || f(self),
+        )
+    }
+
     /// List clock sources
     pub fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
         unsafe {
@@ -1216,6 +1458,43 @@ impl Device {
         unsafe { check_error(SoapySDRDevice_getMasterClockRate(self.inner.ptr)) }
     }
 
+    /// Set the master clock rate of the device in Hz.
+    ///
+    /// See [`Device::master_clock_rate_for`] to find a rate that divides a sample rate exactly.
+    pub fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        unsafe {
+            SoapySDRDevice_setMasterClockRate(self.inner.ptr, rate);
+            check_error(())
+        }
+    }
+
+    /// Get the ranges of available master clock rates in Hz.
+    pub fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        unsafe {
+            list_result(|len_ptr| SoapySDRDevice_getMasterClockRates(self.inner.ptr, len_ptr))
+        }
+    }
+
+    /// Set the reference clock rate of the device in Hz.
+    pub fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        unsafe {
+            SoapySDRDevice_setReferenceClockRate(self.inner.ptr, rate);
+            check_error(())
+        }
+    }
+
+    /// Get the current reference clock rate in Hz
+    pub fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        unsafe { check_error(SoapySDRDevice_getReferenceClockRate(self.inner.ptr)) }
+    }
+
+    /// Get the ranges of available reference clock rates in Hz.
+    pub fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        unsafe {
+            list_result(|len_ptr| SoapySDRDevice_getReferenceClockRates(self.inner.ptr, len_ptr))
+        }
+    }
+
     // TODO: sensors
 
     /// Write a register on device given interface name
@@ -1261,6 +1540,31 @@ impl Device {
         }
     }
 
+    /// Read a memory block of up to `length` words on the device given interface name
+    pub fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        let name = CString::new(name).expect("name must not contain null byte");
+        unsafe {
+            let mut len = length;
+            let ptr = check_error(SoapySDRDevice_readRegisters(
+                self.inner.ptr,
+                name.as_ptr(),
+                address,
+                &mut len as *mut _,
+            ))?;
+            if ptr.is_null() {
+                return Ok(Vec::new());
+            }
+            let ret = slice::from_raw_parts(ptr, len).to_vec();
+            SoapySDR_free(ptr as *mut c_void);
+            Ok(ret)
+        }
+    }
+
     /// Get a list of available register interfaces by name
     pub fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
         unsafe {
@@ -1290,128 +1594,1047 @@ impl Device {
         unsafe { string_result(SoapySDRDevice_readSetting(self.inner.ptr, key.as_ptr())) }
     }
 
-    // TODO: gpio
-
-    // TODO: I2C
-
-    // TODO: SPI
-
-    // TODO: UART
-}
-
-/// A stream open for receiving.
-///
-/// To obtain a RxStream, call [Device::rx_stream]. The type parameter `E` represents the type
-/// of this stream's samples.
-///
-/// Streams may involve multiple channels.
-pub struct RxStream<E: StreamSample> {
-    device: Device,
-    handle: *mut SoapySDRStream,
-    nchannels: usize,
-    flags: i32,
-    time_ns: i64,
-    active: bool,
-    buf_ptrs: Vec<*mut E>,
-    phantom: PhantomData<fn(&mut [E])>,
-}
-
-/// Streams may only be used on one thread at a time but may be sent between threads
-unsafe impl<E: StreamSample> Send for RxStream<E> {}
-
-impl<E: StreamSample> Drop for RxStream<E> {
-    fn drop(&mut self) {
-        unsafe {
-            if self.active {
-                self.deactivate(None).ok();
-            }
-            SoapySDRDevice_closeStream(self.device.inner.ptr, self.handle);
-        }
+    /// Describe the available device settings.
+    pub fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        unsafe { arg_info_result(|len_ptr| SoapySDRDevice_getSettingInfo(self.inner.ptr, len_ptr)) }
     }
-}
 
-impl<E: StreamSample> RxStream<E> {
-    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    /// Describe a device setting.
     ///
-    /// The MTU specifies the maximum payload transfer in a stream operation.
-    /// This value can be used as a stream buffer allocation size that can
-    /// best optimize throughput given the underlying stream implementation.
-    pub fn mtu(&self) -> Result<usize, Error> {
+    /// Returns `None` if the driver has no information about `key`.
+    pub fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        let key_c = CString::new(key).expect("key contains null byte");
         unsafe {
-            check_error(SoapySDRDevice_getStreamMTU(
-                self.device.inner.ptr,
-                self.handle,
+            single_arg_info_result(SoapySDRDevice_getSettingInfoWithKey(
+                self.inner.ptr,
+                key_c.as_ptr(),
             ))
         }
     }
 
-    /// Activate a stream.
-    ///
-    /// Call `activate` to enable a stream before using `read()`
-    ///
-    /// # Arguments:
-    ///   * `time_ns` -- optional activation time in nanoseconds
-    pub fn activate(&mut self, time_ns: Option<i64>) -> Result<(), Error> {
-        if self.active {
-            return Err(Error {
-                code: ErrorCode::Other,
-                message: "Stream is already active".into(),
-            });
-        }
+    /// Describe the available settings of a channel.
+    pub fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
         unsafe {
-            let flags = if time_ns.is_some() {
-                SOAPY_SDR_HAS_TIME as i32
-            } else {
-                0
-            };
-            check_ret_error(SoapySDRDevice_activateStream(
-                self.device.inner.ptr,
-                self.handle,
-                flags,
-                time_ns.unwrap_or(0),
-                0,
-            ))?;
-            self.active = true;
-            Ok(())
+            arg_info_result(|len_ptr| {
+                SoapySDRDevice_getChannelSettingInfo(
+                    self.inner.ptr,
+                    direction.into(),
+                    channel,
+                    len_ptr,
+                )
+            })
         }
     }
 
-    /// Fetch the active state of the stream.
-    pub fn active(&self) -> bool {
-        self.active
-    }
-
-    // TODO: activate_burst()
+    /// Describe a setting of a channel.
+    ///
+    /// Returns `None` if the driver has no information about `key`.
+    pub fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        let key_c = CString::new(key).expect("key contains null byte");
+        unsafe {
+            single_arg_info_result(SoapySDRDevice_getChannelSettingInfoWithKey(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+                key_c.as_ptr(),
+            ))
+        }
+    }
 
-    /// Deactivate a stream.
-    /// The implementation will control switches or halt data flow.
+    /// Write a setting of a channel
+    pub fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        let key = CString::new(key).expect("key must not contain null byte");
+        let value = CString::new(value).expect("value must not contain null byte");
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeChannelSetting(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+                key.as_ptr(),
+                value.as_ptr(),
+            ))
+        }
+    }
+
+    /// Read a setting of a channel
+    pub fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        let key = CString::new(key).expect("key must not contain null byte");
+        unsafe {
+            string_result(SoapySDRDevice_readChannelSetting(
+                self.inner.ptr,
+                direction.into(),
+                channel,
+                key.as_ptr(),
+            ))
+        }
+    }
+
+    /// Get a list of available GPIO banks by name.
+    pub fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        unsafe {
+            string_list_result(|len_ptr| SoapySDRDevice_listGPIOBanks(self.inner.ptr, len_ptr))
+        }
+    }
+
+    /// Get handles to the available GPIO banks.
+    pub fn gpio_banks(&self) -> Result<Vec<GpioBank>, Error> {
+        Ok(self
+            .list_gpio_banks()?
+            .into_iter()
+            .map(|name| GpioBank::new(self.clone(), name))
+            .collect())
+    }
+
+    /// Write the value of a GPIO bank.
+    pub fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeGPIO(
+                self.inner.ptr,
+                bank.as_ptr(),
+                value,
+            ))
+        }
+    }
+
+    /// Write the value of a GPIO bank, modifying only the bits set in `mask`.
+    pub fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeGPIOMasked(
+                self.inner.ptr,
+                bank.as_ptr(),
+                value,
+                mask,
+            ))
+        }
+    }
+
+    /// Read back the value of a GPIO bank.
+    pub fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe { check_error(SoapySDRDevice_readGPIO(self.inner.ptr, bank.as_ptr())) }
+    }
+
+    /// Write the data direction of a GPIO bank.
     ///
-    /// # Arguments:
-    ///   * `time_ns` -- optional deactivation time in nanoseconds
-    pub fn deactivate(&mut self, time_ns: Option<i64>) -> Result<(), Error> {
+    /// 1 bits represent outputs, 0 bits represent inputs.
+    pub fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeGPIODir(
+                self.inner.ptr,
+                bank.as_ptr(),
+                dir,
+            ))
+        }
+    }
+
+    /// Write the data direction of a GPIO bank, modifying only the bits set in `mask`.
+    ///
+    /// 1 bits represent outputs, 0 bits represent inputs.
+    pub fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeGPIODirMasked(
+                self.inner.ptr,
+                bank.as_ptr(),
+                dir,
+                mask,
+            ))
+        }
+    }
+
+    /// Read the data direction of a GPIO bank.
+    ///
+    /// 1 bits represent outputs, 0 bits represent inputs.
+    pub fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = CString::new(bank).expect("bank must not contain null byte");
+        unsafe { check_error(SoapySDRDevice_readGPIODir(self.inner.ptr, bank.as_ptr())) }
+    }
+
+    /// Write bytes to an I2C peripheral.
+    pub fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeI2C(
+                self.inner.ptr,
+                addr,
+                data.as_ptr() as *const c_char,
+                data.len(),
+            ))
+        }
+    }
+
+    /// Read up to `num_bytes` bytes from an I2C peripheral.
+    pub fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        unsafe {
+            let mut len = num_bytes;
+            let ptr = check_error(SoapySDRDevice_readI2C(self.inner.ptr, addr, &mut len))?;
+            if ptr.is_null() {
+                return Ok(Vec::new());
+            }
+            let ret = slice::from_raw_parts(ptr as *const u8, len).to_vec();
+            SoapySDR_free(ptr as *mut c_void);
+            Ok(ret)
+        }
+    }
+
+    /// Perform a SPI transaction, returning the bits read back.
+    ///
+    /// The `num_bits` least significant bits of `data` are clocked out, most significant bit
+    /// first.
+    pub fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        unsafe {
+            check_error(SoapySDRDevice_transactSPI(
+                self.inner.ptr,
+                addr,
+                data,
+                num_bits,
+            ))
+        }
+    }
+
+    /// Get a list of available UART devices by name.
+    pub fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        unsafe { string_list_result(|len_ptr| SoapySDRDevice_listUARTs(self.inner.ptr, len_ptr)) }
+    }
+
+    /// Get handles to the available UART devices.
+    pub fn uarts(&self) -> Result<Vec<Uart>, Error> {
+        Ok(self
+            .list_uarts()?
+            .into_iter()
+            .map(|name| Uart::new(self.clone(), name))
+            .collect())
+    }
+
+    /// Write bytes to a UART device.
+    ///
+    /// SoapySDR passes UART data as a null-terminated string, so `data` must not contain zero
+    /// bytes.
+    pub fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        let which = CString::new(which).expect("which must not contain null byte");
+        let data = CString::new(data).map_err(|_| Error {
+            code: ErrorCode::NotSupported,
+            message: "UART data must not contain null bytes".into(),
+        })?;
+        unsafe {
+            check_ret_error(SoapySDRDevice_writeUART(
+                self.inner.ptr,
+                which.as_ptr(),
+                data.as_ptr(),
+            ))
+        }
+    }
+
+    /// Read the bytes available from a UART device, waiting up to `timeout_us` microseconds for
+    /// data to arrive.
+    ///
+    /// Returns an empty buffer if no data arrived before the timeout. Timeouts that don't fit in a
+    /// C `long`, which is 32 bits on some platforms, are clamped to its range.
+    pub fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        let which = CString::new(which).expect("which must not contain null byte");
+        let timeout_us = c_long::try_from(timeout_us).unwrap_or(if timeout_us < 0 {
+            c_long::MIN
+        } else {
+            c_long::MAX
+        });
+        unsafe {
+            let ptr = check_error(SoapySDRDevice_readUART(
+                self.inner.ptr,
+                which.as_ptr(),
+                timeout_us,
+            ))?;
+            if ptr.is_null() {
+                return Ok(Vec::new());
+            }
+            let ret = CStr::from_ptr(ptr).to_bytes().to_vec();
+            SoapySDR_free(ptr as *mut c_void);
+            Ok(ret)
+        }
+    }
+}
+
+/// Run `f` after `set` succeeds, then `clear`, even if `f` returns an error or panics.
+///
+/// An error from `f` takes precedence over an error from `clear`.
+fn scoped<T>(
+    set: impl FnOnce() -> Result<(), Error>,
+    clear: impl Fn() -> Result<(), Error>,
+    f: impl FnOnce() -> Result<T, Error>,
+) -> Result<T, Error> {
+    /// Calls `clear` if dropped during a panic in `f`.
+    struct Guard<C: Fn() -> Result<(), Error>>(C);
+
+    impl<C: Fn() -> Result<(), Error>> Drop for Guard<C> {
+        fn drop(&mut self) {
+            (self.0)().ok();
+        }
+    }
+
+    set()?;
+    let guard = Guard(&clear);
+    let result = f();
+    std::mem::forget(guard);
+    let cleared = clear();
+    let value = result?;
+    cleared?;
+    Ok(value)
+}
+
+/// Flags describing a stream operation, as passed to and returned from SoapySDR.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct StreamFlags(i32);
+
+impl StreamFlags {
+    /// Indicates the end of a burst transmission or reception.
+    pub const END_BURST: StreamFlags = StreamFlags(SOAPY_SDR_END_BURST as i32);
+
+    /// Indicates that the timestamp is valid.
+    pub const HAS_TIME: StreamFlags = StreamFlags(SOAPY_SDR_HAS_TIME as i32);
+
+    /// Indicates that stream terminated prematurely.
+    pub const END_ABRUPT: StreamFlags = StreamFlags(SOAPY_SDR_END_ABRUPT as i32);
+
+    /// Indicates transmit or receive only a single packet.
+    pub const ONE_PACKET: StreamFlags = StreamFlags(SOAPY_SDR_ONE_PACKET as i32);
+
+    /// Indicates that this read call and the next results in a fragment.
+    pub const MORE_FRAGMENTS: StreamFlags = StreamFlags(SOAPY_SDR_MORE_FRAGMENTS as i32);
+
+    /// Indicates that the stream should wait for an external trigger event.
+    pub const WAIT_TRIGGER: StreamFlags = StreamFlags(SOAPY_SDR_WAIT_TRIGGER as i32);
+
+    /// Driver-specific user flag 0.
+    pub const USER_FLAG0: StreamFlags = StreamFlags(SOAPY_SDR_USER_FLAG0 as i32);
+
+    /// Driver-specific user flag 1.
+    pub const USER_FLAG1: StreamFlags = StreamFlags(SOAPY_SDR_USER_FLAG1 as i32);
+
+    /// Driver-specific user flag 2.
+    pub const USER_FLAG2: StreamFlags = StreamFlags(SOAPY_SDR_USER_FLAG2 as i32);
+
+    /// Driver-specific user flag 3.
+    pub const USER_FLAG3: StreamFlags = StreamFlags(SOAPY_SDR_USER_FLAG3 as i32);
+
+    /// Driver-specific user flag 4.
+    pub const USER_FLAG4: StreamFlags = StreamFlags(SOAPY_SDR_USER_FLAG4 as i32);
+
+    const NAMES: [(StreamFlags, &'static str); 11] = [
+        (StreamFlags::END_BURST, "END_BURST"),
+        (StreamFlags::HAS_TIME, "HAS_TIME"),
+        (StreamFlags::END_ABRUPT, "END_ABRUPT"),
+        (StreamFlags::ONE_PACKET, "ONE_PACKET"),
+        (StreamFlags::MORE_FRAGMENTS, "MORE_FRAGMENTS"),
+        (StreamFlags::WAIT_TRIGGER, "WAIT_TRIGGER"),
+        (StreamFlags::USER_FLAG0, "USER_FLAG0"),
+        (StreamFlags::USER_FLAG1, "USER_FLAG1"),
+        (StreamFlags::USER_FLAG2, "USER_FLAG2"),
+        (StreamFlags::USER_FLAG3, "USER_FLAG3"),
+        (StreamFlags::USER_FLAG4, "USER_FLAG4"),
+    ];
+
+    /// A set containing no flags.
+    pub const fn empty() -> StreamFlags {
+        StreamFlags(0)
+    }
+
+    /// Create a set of flags from the raw SoapySDR bit representation.
+    pub const fn from_bits(bits: i32) -> StreamFlags {
+        StreamFlags(bits)
+    }
+
+    /// Get the raw SoapySDR bit representation.
+    pub const fn bits(&self) -> i32 {
+        self.0
+    }
+
+    /// Returns true if no flags are set.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if all of the flags in `other` are set.
+    ///
+    /// # Example
+    /// ```
+    /// use soapysdr::StreamFlags;
+    /// let flags = StreamFlags::HAS_TIME | StreamFlags::END_BURST;
+    /// assert!(flags.contains(StreamFlags::END_BURST));
+    /// assert!(!flags.contains(StreamFlags::MORE_FRAGMENTS));
+    /// ```
+    pub const fn contains(&self, other: StreamFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl ::std::ops::BitOr for StreamFlags {
+    type Output = StreamFlags;
+    fn bitor(self, rhs: StreamFlags) -> StreamFlags {
+        StreamFlags(self.0 | rhs.0)
+    }
+}
+
+impl ::std::ops::BitOrAssign for StreamFlags {
+    fn bitor_assign(&mut self, rhs: StreamFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl ::std::ops::BitAnd for StreamFlags {
+    type Output = StreamFlags;
+    fn bitand(self, rhs: StreamFlags) -> StreamFlags {
+        StreamFlags(self.0 & rhs.0)
+    }
+}
+
+impl ::std::fmt::Debug for StreamFlags {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        let mut remaining = self.0;
+        let mut first = true;
+        for (flag, name) in StreamFlags::NAMES {
+            if self.contains(flag) {
+                if !first {
+                    write!(f, " | ")?;
+                }
+                write!(f, "{}", name)?;
+                remaining &= !flag.0;
+                first = false;
+            }
+        }
+        if remaining != 0 {
+            if !first {
+                write!(f, " | ")?;
+            }
+            write!(f, "{:#x}", remaining)?;
+        } else if first {
+            write!(f, "(empty)")?;
+        }
+        Ok(())
+    }
+}
+
+/// Metadata returned by [`RxStream::read_with_meta`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub struct RxMetadata {
+    /// Number of samples read into each channel's buffer.
+    pub len: usize,
+
+    /// Flags reported by the driver for this read.
+    pub flags: StreamFlags,
+
+    /// Raw timestamp of the first sample, valid only if `flags` contains
+    /// [`StreamFlags::HAS_TIME`].
+    pub raw_time: HardwareTime,
+}
+
+impl RxMetadata {
+    /// Timestamp of the first sample, if the driver provided one.
+    pub fn time(&self) -> Option<HardwareTime> {
+        if self.flags.contains(StreamFlags::HAS_TIME) {
+            Some(self.raw_time)
+        } else {
+            None
+        }
+    }
+
+    /// Returns true if these samples are the last of a burst.
+    pub fn end_burst(&self) -> bool {
+        self.flags.contains(StreamFlags::END_BURST)
+    }
+
+    /// Returns true if the stream terminated prematurely.
+    pub fn end_abrupt(&self) -> bool {
+        self.flags.contains(StreamFlags::END_ABRUPT)
+    }
+
+    /// Returns true if the packet was fragmented, and the remainder will be returned by the next
+    /// read.
+    pub fn more_fragments(&self) -> bool {
+        self.flags.contains(StreamFlags::MORE_FRAGMENTS)
+    }
+}
+
+/// A set of channels of a stream, by index into the `channels` array used to open the stream.
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default)]
+pub struct ChannelSet(usize);
+
+impl ChannelSet {
+    /// A set containing no channels.
+    pub const fn empty() -> ChannelSet {
+        ChannelSet(0)
+    }
+
+    /// Create a set from a SoapySDR channel mask, where bit `n` represents stream channel `n`.
+    pub const fn from_mask(mask: usize) -> ChannelSet {
+        ChannelSet(mask)
+    }
+
+    /// Get the SoapySDR channel mask.
+    pub const fn mask(&self) -> usize {
+        self.0
+    }
+
+    /// Returns true if the set contains no channels.
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Get the number of channels in the set.
+    pub const fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Returns true if the set contains the specified stream channel index.
+    pub const fn contains(&self, channel: usize) -> bool {
+        channel < usize::BITS as usize && self.0 & (1 << channel) != 0
+    }
+
+    /// Get an iterator over the stream channel indexes in the set.
+    ///
+    /// # Example
+    /// ```
+    /// use soapysdr::ChannelSet;
+    /// let channels = ChannelSet::from_mask(0b101);
+    /// assert_eq!(channels.iter().collect::<Vec<_>>(), vec![0, 2]);
+    /// ```
+    pub fn iter(&self) -> impl Iterator<Item = usize> + use<> {
+        let mask = self.0;
+        (0..usize::BITS as usize).filter(move |&i| mask & (1 << i) != 0)
+    }
+}
+
+impl ::std::fmt::Debug for ChannelSet {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+/// An event reported by [`TxStream::read_status_event`] or [`RxStream::read_status_event`].
+///
+/// `channels` is the set of stream channels the event applies to, and `time` is the device
+/// timestamp of the event, if the driver reported one.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+#[non_exhaustive]
+pub enum StatusEvent {
+    /// No event was reported before the timeout expired.
+    Timeout,
+
+    /// A burst ended with `end_burst` was completely transmitted.
+    BurstAck {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// The device ran out of samples to transmit.
+    Underflow {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// The device had to drop received samples because an internal buffer filled.
+    Overflow {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// The device encountered a stream time which was expired (late) or too early to process.
+    TimeError {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// The driver saw a malformed packet.
+    Corruption {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// A non-specific stream error.
+    StreamError {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+    },
+
+    /// A status report not covered by the other variants, identified by its flags.
+    Other {
+        channels: ChannelSet,
+        time: Option<HardwareTime>,
+        flags: StreamFlags,
+    },
+}
+
+impl StatusEvent {
+    /// Get the set of stream channels the event applies to.
+    pub fn channels(&self) -> ChannelSet {
+        match *self {
+            StatusEvent::Timeout => ChannelSet::empty(),
+            StatusEvent::BurstAck { channels, .. }
+            | StatusEvent::Underflow { channels, .. }
+            | StatusEvent::Overflow { channels, .. }
+            | StatusEvent::TimeError { channels, .. }
+            | StatusEvent::Corruption { channels, .. }
+            | StatusEvent::StreamError { channels, .. }
+            | StatusEvent::Other { channels, .. } => channels,
+        }
+    }
+
+    /// Get the device timestamp of the event, if the driver reported one.
+    pub fn time(&self) -> Option<HardwareTime> {
+        match *self {
+            StatusEvent::Timeout => None,
+            StatusEvent::BurstAck { time, .. }
+            | StatusEvent::Underflow { time, .. }
+            | StatusEvent::Overflow { time, .. }
+            | StatusEvent::TimeError { time, .. }
+            | StatusEvent::Corruption { time, .. }
+            | StatusEvent::StreamError { time, .. }
+            | StatusEvent::Other { time, .. } => time,
+        }
+    }
+}
+
+/// Read a status event from a stream of either direction.
+unsafe fn read_status_event(
+    device: &Device,
+    stream: *mut SoapySDRStream,
+    timeout_us: i64,
+) -> Result<StatusEvent, Error> {
+    // Conversion needed for Windows, which takes an i32 here for some reason.
+    #[allow(clippy::useless_conversion)]
+    let timeout_us = timeout_us.try_into().unwrap();
+    unsafe {
+        let mut chan_mask: usize = 0;
+        let mut flags: i32 = 0;
+        let mut time_ns: i64 = 0;
+        let ret = SoapySDRDevice_readStreamStatus(
+            device.inner.ptr,
+            stream,
+            &mut chan_mask as *mut _,
+            &mut flags as *mut _,
+            &mut time_ns as *mut _,
+            timeout_us,
+        );
+
+        let channels = ChannelSet(chan_mask);
+        let flags = StreamFlags(flags);
+        let time = if flags.contains(StreamFlags::HAS_TIME) {
+            Some(HardwareTime::from_ns(time_ns))
+        } else {
+            None
+        };
+
+        match ret {
+            0 if flags.contains(StreamFlags::END_BURST) => {
+                Ok(StatusEvent::BurstAck { channels, time })
+            }
+            0 => Ok(StatusEvent::Other {
+                channels,
+                time,
+                flags,
+            }),
+            SOAPY_SDR_TIMEOUT => Ok(StatusEvent::Timeout),
+            SOAPY_SDR_UNDERFLOW => Ok(StatusEvent::Underflow { channels, time }),
+            SOAPY_SDR_OVERFLOW => Ok(StatusEvent::Overflow { channels, time }),
+            SOAPY_SDR_TIME_ERROR => Ok(StatusEvent::TimeError { channels, time }),
+            SOAPY_SDR_CORRUPTION => Ok(StatusEvent::Corruption { channels, time }),
+            SOAPY_SDR_STREAM_ERROR => Ok(StatusEvent::StreamError { channels, time }),
+            _ => Err(Error {
+                code: ErrorCode::from_c(ret),
+                message: last_error_str(),
+            }),
+        }
+    }
+}
+
+/// An iterator over the pending status events of a stream.
+///
+/// Returned by [`TxStream::status_events`] and [`RxStream::status_events`]. The iterator ends
+/// when no event is reported before the timeout, or after yielding an error.
+pub struct StatusEvents<'a> {
+    device: &'a Device,
+    handle: *mut SoapySDRStream,
+    timeout_us: i64,
+    done: bool,
+}
+
+impl Iterator for StatusEvents<'_> {
+    type Item = Result<StatusEvent, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match unsafe { read_status_event(self.device, self.handle, self.timeout_us) } {
+            Ok(StatusEvent::Timeout) => {
+                self.done = true;
+                None
+            }
+            Ok(event) => Some(Ok(event)),
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+impl ::std::iter::FusedIterator for StatusEvents<'_> {}
+
+/// The driver's stream handle, with the calls shared by the typed and raw streams.
+///
+/// The stream is deactivated if needed and closed when this is dropped.
+struct StreamHandle {
+    device: Device,
+    ptr: *mut SoapySDRStream,
+    direction: Direction,
+    channels: Vec<usize>,
+    active: bool,
+    /// Whether the stream's format is the device's native format, once checked.
+    native: Option<bool>,
+}
+
+impl StreamHandle {
+    fn setup(
+        device: &Device,
+        direction: Direction,
+        format: Format,
+        channels: &[usize],
+        args: Args,
+    ) -> Result<StreamHandle, Error> {
+        unsafe {
+            let ptr = check_error(SoapySDRDevice_setupStream(
+                device.inner.ptr,
+                direction.into(),
+                format.as_ptr(),
+                channels.as_ptr(),
+                channels.len(),
+                args.as_raw_const(),
+            ))?;
+            Ok(StreamHandle {
+                device: device.clone(),
+                ptr,
+                direction,
+                channels: channels.to_vec(),
+                active: false,
+                native: None,
+            })
+        }
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        unsafe { check_error(SoapySDRDevice_getStreamMTU(self.device.inner.ptr, self.ptr)) }
+    }
+
+    fn activate(
+        &mut self,
+        mut flags: i32,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        if self.active {
+            return Err(Error {
+                code: ErrorCode::Other,
+                message: "Stream is already active".into(),
+            });
+        }
+        if time.is_some() {
+            flags |= SOAPY_SDR_HAS_TIME as i32;
+        }
+        unsafe {
+            check_ret_error(SoapySDRDevice_activateStream(
+                self.device.inner.ptr,
+                self.ptr,
+                flags,
+                time.map_or(0, HardwareTime::as_ns),
+                num_samples,
+            ))?;
+        }
+        self.active = true;
+        Ok(())
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
         if !self.active {
             return Err(Error {
                 code: ErrorCode::Other,
                 message: "Stream is not active".into(),
             });
         }
+        let flags = if time.is_some() {
+            SOAPY_SDR_HAS_TIME as i32
+        } else {
+            0
+        };
         unsafe {
-            let flags = if time_ns.is_some() {
-                SOAPY_SDR_HAS_TIME as i32
-            } else {
-                0
-            };
             check_ret_error(SoapySDRDevice_deactivateStream(
                 self.device.inner.ptr,
-                self.handle,
+                self.ptr,
                 flags,
-                time_ns.unwrap_or(0),
+                time.map_or(0, HardwareTime::as_ns),
             ))?;
-            self.active = false;
-            Ok(())
+        }
+        self.active = false;
+        Ok(())
+    }
+
+    /// Read up to `num_samples` samples per channel into the buffers at `buf_ptrs`.
+    unsafe fn read(
+        &self,
+        buf_ptrs: *const *mut c_void,
+        num_samples: usize,
+        flags: &mut i32,
+        time_ns: &mut i64,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        *flags = 0;
+        let len = len_result(unsafe {
+            SoapySDRDevice_readStream(
+                self.device.inner.ptr,
+                self.ptr,
+                buf_ptrs,
+                num_samples,
+                flags as *mut _,
+                time_ns as *mut _,
+                timeout_us as _,
+            )
+        })?;
+        Ok(len as usize)
+    }
+
+    /// Write up to `num_samples` samples per channel from the buffers at `buf_ptrs`.
+    unsafe fn write(
+        &self,
+        buf_ptrs: *const *const c_void,
+        num_samples: usize,
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        let mut flags = 0;
+
+        if at.is_some() {
+            flags |= SOAPY_SDR_HAS_TIME as i32;
+        }
+
+        if end_burst {
+            flags |= SOAPY_SDR_END_BURST as i32;
+        }
+
+        let len = len_result(unsafe {
+            SoapySDRDevice_writeStream(
+                self.device.inner.ptr,
+                self.ptr,
+                buf_ptrs,
+                num_samples,
+                &mut flags as *mut _,
+                at.map_or(0, HardwareTime::as_ns),
+                timeout_us as _,
+            )
+        })?;
+        Ok(len as usize)
+    }
+
+    fn read_status_event(&self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        unsafe { read_status_event(&self.device, self.ptr, timeout_us) }
+    }
+
+    fn status_events(&self, timeout_us: i64) -> StatusEvents<'_> {
+        StatusEvents {
+            device: &self.device,
+            handle: self.ptr,
+            timeout_us,
+            done: false,
+        }
+    }
+
+    /// Fail unless `format` is the device's native format for every channel of the stream, as
+    /// required for direct buffer access.
+    fn check_native_format(&mut self, format: Format) -> Result<(), Error> {
+        let native = match self.native {
+            Some(native) => native,
+            None => {
+                let mut native = true;
+                for &channel in &self.channels {
+                    let (channel_format, _) =
+                        self.device.native_stream_format(self.direction, channel)?;
+                    native &= channel_format == format;
+                }
+                *self.native.insert(native)
+            }
+        };
+        if !native {
+            return Err(Error {
+                code: ErrorCode::NotSupported,
+                message: format!(
+                    "Direct buffer access requires the native stream format, not {}",
+                    format
+                ),
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Drop for StreamHandle {
+    fn drop(&mut self) {
+        if self.active {
+            self.deactivate(None).ok();
+        }
+        unsafe {
+            SoapySDRDevice_closeStream(self.device.inner.ptr, self.ptr);
+        }
+    }
+}
+
+/// Write all of `buffers`, calling `write` until it has accepted every sample.
+///
+/// `sample_len` is the number of elements of `T` in each sample.
+pub(crate) fn write_all<T>(
+    buffers: &[&[T]],
+    at: Option<HardwareTime>,
+    sample_len: usize,
+    mut write: impl FnMut(&[&[T]], Option<HardwareTime>) -> Result<usize, Error>,
+) -> Result<(), Error> {
+    let mut buffers = buffers.to_owned();
+    let mut at = at;
+
+    while buffers.first().map_or(0, |x| x.len()) > 0 {
+        // The timestamp is only sent on the first write.
+        let written = write(&buffers, at.take())?;
+
+        // Advance the buffer pointers
+        for buf in &mut buffers {
+            *buf = &buf[written * sample_len..];
         }
     }
 
+    Ok(())
+}
+
+/// A stream open for receiving.
+///
+/// To obtain a RxStream, call [Device::rx_stream]. The type parameter `E` represents the type
+/// of this stream's samples.
+///
+/// Streams may involve multiple channels.
+pub struct RxStream<E: StreamSample> {
+    handle: StreamHandle,
+    flags: i32,
+    time_ns: i64,
+    buf_ptrs: Vec<*mut E>,
+    phantom: PhantomData<fn(&mut [E])>,
+}
+
+/// Streams may only be used on one thread at a time but may be sent between threads
+unsafe impl<E: StreamSample> Send for RxStream<E> {}
+
+impl<E: StreamSample> RxStream<E> {
+    /// Get the number of channels in the stream.
+    pub fn num_channels(&self) -> usize {
+        self.handle.channels.len()
+    }
+
+    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    ///
+    /// The MTU specifies the maximum payload transfer in a stream operation.
+    /// This value can be used as a stream buffer allocation size that can
+    /// best optimize throughput given the underlying stream implementation.
+    pub fn mtu(&self) -> Result<usize, Error> {
+        self.handle.mtu()
+    }
+
+    /// Activate a stream.
+    ///
+    /// Call `activate` to enable a stream before using `read()`
+    ///
+    /// # Arguments:
+    ///   * `time` -- optional activation time
+    pub fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.activate(0, time, 0)
+    }
+
+    /// Fetch the active state of the stream.
+    pub fn active(&self) -> bool {
+        self.handle.active
+    }
+
+    /// Activate a stream to receive a finite burst of samples.
+    ///
+    /// The stream will produce `num_samples` samples per channel, starting at `time` if
+    /// specified, and then stop. The final `read()` of the burst sets
+    /// [`burst_ended`](RxStream::burst_ended). The stream remains active until
+    /// [`deactivate`](RxStream::deactivate) is called, which must be done before activating it
+    /// again.
+    ///
+    /// Not all drivers support burst mode; those that don't return [ErrorCode::NotSupported].
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+    /// let mut stream = dev.rx_stream::<num_complex::Complex<f32>>(&[0]).unwrap();
+    /// let mut buf = vec![num_complex::Complex::new(0.0, 0.0); 4096];
+    /// stream.activate_burst(None, buf.len()).unwrap();
+    /// let mut received = 0;
+    /// while !stream.burst_ended() {
+    ///     received += stream.read(&mut [&mut buf[received..]], 1_000_000).unwrap();
+    /// }
+    /// stream.deactivate(None).unwrap();
+    /// ```
+    ///
+    /// # Arguments:
+    ///   * `time` -- optional activation time
+    ///   * `num_samples` -- number of samples per channel in the burst
+    pub fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        self.handle
+            .activate(SOAPY_SDR_END_BURST as i32, time, num_samples)?;
+        self.flags = 0;
+        Ok(())
+    }
+
+    /// Deactivate a stream.
+    /// The implementation will control switches or halt data flow.
+    ///
+    /// # Arguments:
+    ///   * `time` -- optional deactivation time
+    pub fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.deactivate(time)
+    }
+
     /// Read samples from the stream into the provided buffers.
     ///
     /// `buffers` contains one destination slice for each channel of this stream.
@@ -1421,33 +2644,227 @@ impl<E: StreamSample> RxStream<E> {
     /// # Panics
     ///  * If `buffers` is not the same length as the `channels` array passed to `Device::rx_stream`.
     pub fn read(&mut self, buffers: &mut [&mut [E]], timeout_us: i64) -> Result<usize, Error> {
-        unsafe {
-            assert!(buffers.len() == self.nchannels);
+        self.read_with_meta(buffers, timeout_us)
+            .map(|meta| meta.len)
+    }
 
-            let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+    /// Read samples from the stream into the provided buffers, returning the
+    /// number of samples read along with the flags and timestamp reported by the driver.
+    ///
+    /// `buffers` contains one destination slice for each channel of this stream.
+    ///
+    /// # Panics
+    ///  * If `buffers` is not the same length as the `channels` array passed to `Device::rx_stream`.
+    pub fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        assert!(buffers.len() == self.buf_ptrs.len());
 
-            for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers.iter_mut()) {
-                *dst = src.as_mut_ptr();
-            }
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
 
-            self.flags = 0;
-            let len = len_result(SoapySDRDevice_readStream(
-                self.device.inner.ptr,
-                self.handle,
+        for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers.iter_mut()) {
+            *dst = src.as_mut_ptr();
+        }
+
+        let len = unsafe {
+            self.handle.read(
                 self.buf_ptrs.as_ptr() as *const *mut _,
                 num_samples,
-                &mut self.flags as *mut _,
-                &mut self.time_ns as *mut _,
+                &mut self.flags,
+                &mut self.time_ns,
+                timeout_us,
+            )?
+        };
+
+        Ok(RxMetadata {
+            len,
+            flags: StreamFlags(self.flags),
+            raw_time: HardwareTime::from_ns(self.time_ns),
+        })
+    }
+
+    /// Return timestamp of the last successful `read()` operation.
+    pub fn time(&self) -> HardwareTime {
+        HardwareTime::from_ns(self.time_ns)
+    }
+
+    /// Return whether the last successful `read()` operation received the final samples of a
+    /// burst started with [`activate_burst`](RxStream::activate_burst).
+    pub fn burst_ended(&self) -> bool {
+        StreamFlags(self.flags).contains(StreamFlags::END_BURST)
+    }
+
+    /// Wait for a status event on the stream, such as an overflow.
+    ///
+    /// Returns [StatusEvent::Timeout] if no event occurs within `timeout_us`. Drivers that don't
+    /// report stream status return [ErrorCode::NotSupported].
+    ///
+    /// Note that `timeout_us` is only `i32` on Windows and panics if the value is
+    /// too large for `i32` on that platform.
+    pub fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.handle.read_status_event(timeout_us)
+    }
+
+    /// Get an iterator over the pending status events of the stream.
+    ///
+    /// Each call to `next()` waits up to `timeout_us` for an event, and the iterator ends when
+    /// none arrives.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+    /// # let mut stream = dev.rx_stream::<num_complex::Complex<f32>>(&[0]).unwrap();
+    /// for event in stream.status_events(1000) {
+    ///     println!("{:?}", event.unwrap());
+    /// }
+    /// ```
+    pub fn status_events(&mut self, timeout_us: i64) -> StatusEvents<'_> {
+        self.handle.status_events(timeout_us)
+    }
+
+    /// Get the number of direct access buffers the stream can provide.
+    ///
+    /// This is the number of buffers that may be held by the driver's internal ring.
+    /// A return value of 0 means that direct access is not supported.
+    pub fn num_direct_access_buffers(&self) -> usize {
+        unsafe {
+            SoapySDRDevice_getNumDirectAccessBuffers(self.handle.device.inner.ptr, self.handle.ptr)
+        }
+    }
+
+    /// Get the per-channel addresses of a direct access buffer.
+    ///
+    /// `handle` is an index between 0 and
+    /// [`num_direct_access_buffers`](RxStream::num_direct_access_buffers) - 1.
+    /// This can be used to pre-compute per-buffer state once after the stream is created.
+    /// The returned pointers are only valid to dereference while the buffer with that handle is
+    /// acquired.
+    pub fn direct_access_buffer_addrs(&self, handle: usize) -> Result<Vec<*const E>, Error> {
+        unsafe {
+            let mut addrs = vec![std::ptr::null_mut(); self.buf_ptrs.len()];
+            check_ret_error(SoapySDRDevice_getDirectAccessBufferAddrs(
+                self.handle.device.inner.ptr,
+                self.handle.ptr,
+                handle,
+                addrs.as_mut_ptr(),
+            ))?;
+            Ok(addrs.into_iter().map(|p| p as *const E).collect())
+        }
+    }
+
+    /// Acquire a buffer of received samples directly from the driver, without copying.
+    ///
+    /// The stream must be opened with the device's
+    /// [native stream format](Device::native_stream_format), and the driver must support direct
+    /// buffer access (see [`num_direct_access_buffers`](RxStream::num_direct_access_buffers)).
+    ///
+    /// The buffer is released back to the driver when the returned [`ReadBuffer`] is dropped.
+    ///
+    /// Returns [`ErrorCode::NotSupported`] if `E` is not the native format:
+    ///
+    /// ```
+    /// use num_complex::Complex;
+    /// use soapysdr::{Device, Direction, ErrorCode, Format};
+    ///
+    /// let dev = Device::new("type=null").unwrap();
+    /// let (native, _) = dev.native_stream_format(Direction::Rx, 0).unwrap();
+    /// assert_ne!(native, Format::CF64);
+    ///
+    /// let mut stream = dev.rx_stream::<Complex<f64>>(&[0]).unwrap();
+    /// let err = stream.acquire(0).err().unwrap();
+    /// assert_eq!(err.code, ErrorCode::NotSupported);
+    /// ```
+    pub fn acquire(&mut self, timeout_us: i64) -> Result<ReadBuffer<'_, E>, Error> {
+        self.handle.check_native_format(E::STREAM_FORMAT)?;
+        unsafe {
+            let mut handle: usize = 0;
+            let mut flags: i32 = 0;
+            let mut time_ns: i64 = 0;
+            let len = len_result(SoapySDRDevice_acquireReadBuffer(
+                self.handle.device.inner.ptr,
+                self.handle.ptr,
+                &mut handle as *mut _,
+                self.buf_ptrs.as_mut_ptr() as *mut *const _,
+                &mut flags as *mut _,
+                &mut time_ns as *mut _,
                 timeout_us as _,
             ))?;
 
-            Ok(len as usize)
+            Ok(ReadBuffer {
+                stream: self,
+                handle,
+                meta: RxMetadata {
+                    len: len as usize,
+                    flags: StreamFlags(flags),
+                    raw_time: HardwareTime::from_ns(time_ns),
+                },
+            })
         }
     }
+}
 
-    /// Return timestamp of the last successful `read()` operation.
-    pub fn time_ns(&self) -> i64 {
-        self.time_ns
+/// A buffer of received samples borrowed from the driver by [`RxStream::acquire`].
+///
+/// The buffer is released back to the stream when dropped.
+pub struct ReadBuffer<'a, E: StreamSample> {
+    stream: &'a mut RxStream<E>,
+    handle: usize,
+    meta: RxMetadata,
+}
+
+impl<E: StreamSample> ReadBuffer<'_, E> {
+    /// Get the driver's handle for this buffer, as used by
+    /// [`RxStream::direct_access_buffer_addrs`].
+    pub fn handle(&self) -> usize {
+        self.handle
+    }
+
+    /// Get the number of samples available in each channel.
+    pub fn len(&self) -> usize {
+        self.meta.len
+    }
+
+    /// Returns true if the buffer contains no samples.
+    pub fn is_empty(&self) -> bool {
+        self.meta.len == 0
+    }
+
+    /// Get the number of channels in the buffer.
+    pub fn num_channels(&self) -> usize {
+        self.stream.num_channels()
+    }
+
+    /// Get the samples of a channel, by index into the `channels` array passed to
+    /// `Device::rx_stream`.
+    ///
+    /// # Panics
+    ///  * If `channel` is out of range.
+    pub fn channel(&self, channel: usize) -> &[E] {
+        unsafe { slice::from_raw_parts(self.stream.buf_ptrs[channel], self.meta.len) }
+    }
+
+    /// Return the timestamp of the first sample in the buffer.
+    pub fn time(&self) -> HardwareTime {
+        self.meta.raw_time
+    }
+
+    /// Get the flags and timestamp reported by the driver for this buffer.
+    pub fn meta(&self) -> RxMetadata {
+        self.meta
+    }
+}
+
+impl<E: StreamSample> Drop for ReadBuffer<'_, E> {
+    fn drop(&mut self) {
+        unsafe {
+            SoapySDRDevice_releaseReadBuffer(
+                self.stream.handle.device.inner.ptr,
+                self.stream.handle.ptr,
+                self.handle,
+            );
+        }
     }
 }
 
@@ -1458,10 +2875,7 @@ impl<E: StreamSample> RxStream<E> {
 ///
 /// Streams may involve multiple channels.
 pub struct TxStream<E: StreamSample> {
-    device: Device,
-    handle: *mut SoapySDRStream,
-    nchannels: usize,
-    active: bool,
+    handle: StreamHandle,
     buf_ptrs: Vec<*const E>,
     phantom: PhantomData<fn(&[E])>,
 }
@@ -1469,30 +2883,19 @@ pub struct TxStream<E: StreamSample> {
 /// Streams may only be used on one thread at a time but may be sent between threads
 unsafe impl<E: StreamSample> Send for TxStream<E> {}
 
-impl<E: StreamSample> Drop for TxStream<E> {
-    fn drop(&mut self) {
-        unsafe {
-            if self.active {
-                self.deactivate(None).ok();
-            }
-            SoapySDRDevice_closeStream(self.device.inner.ptr, self.handle);
-        }
+impl<E: StreamSample> TxStream<E> {
+    /// Get the number of channels in the stream.
+    pub fn num_channels(&self) -> usize {
+        self.handle.channels.len()
     }
-}
 
-impl<E: StreamSample> TxStream<E> {
     /// Get the stream's maximum transmission unit (MTU) in number of elements.
     ///
     /// The MTU specifies the maximum payload transfer in a stream operation.
-    /// This value can be used as a stream buffer allocation size that can
-    /// best optimize throughput given the underlying stream implementation.
-    pub fn mtu(&self) -> Result<usize, Error> {
-        unsafe {
-            check_error(SoapySDRDevice_getStreamMTU(
-                self.device.inner.ptr,
-                self.handle,
-            ))
-        }
+    /// This value can be used as a stream buffer allocation size that can
+    /// best optimize throughput given the underlying stream implementation.
+    pub fn mtu(&self) -> Result<usize, Error> {
+        self.handle.mtu()
     }
 
     /// Activate a stream.
@@ -1500,64 +2903,23 @@ impl<E: StreamSample> TxStream<E> {
     /// Call `activate` to enable a stream before using `write()`
     ///
     /// # Arguments:
-    ///   * `time_ns` -- optional activation time in nanoseconds
-    pub fn activate(&mut self, time_ns: Option<i64>) -> Result<(), Error> {
-        if self.active {
-            return Err(Error {
-                code: ErrorCode::Other,
-                message: "Stream is already active".into(),
-            });
-        }
-        unsafe {
-            let flags = if time_ns.is_some() {
-                SOAPY_SDR_HAS_TIME as i32
-            } else {
-                0
-            };
-            check_ret_error(SoapySDRDevice_activateStream(
-                self.device.inner.ptr,
-                self.handle,
-                flags,
-                time_ns.unwrap_or(0),
-                0,
-            ))?;
-            self.active = true;
-            Ok(())
-        }
+    ///   * `time` -- optional activation time
+    pub fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.activate(0, time, 0)
     }
 
     /// Fetch the active state of the stream.
     pub fn active(&self) -> bool {
-        self.active
+        self.handle.active
     }
 
     /// Deactivate a stream.
     /// The implementation will control switches or halt data flow.
     ///
     /// # Arguments:
-    ///   * `time_ns` -- optional deactivation time in nanoseconds
-    pub fn deactivate(&mut self, time_ns: Option<i64>) -> Result<(), Error> {
-        if !self.active {
-            return Err(Error {
-                code: ErrorCode::Other,
-                message: "Stream is not active".into(),
-            });
-        }
-        unsafe {
-            let flags = if time_ns.is_some() {
-                SOAPY_SDR_HAS_TIME as i32
-            } else {
-                0
-            };
-            check_ret_error(SoapySDRDevice_deactivateStream(
-                self.device.inner.ptr,
-                self.handle,
-                flags,
-                time_ns.unwrap_or(0),
-            ))?;
-            self.active = false;
-            Ok(())
-        }
+    ///   * `time` -- optional deactivation time
+    pub fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.deactivate(time)
     }
 
     /// Attempt to write samples to the device from the provided buffer.
@@ -1566,8 +2928,8 @@ impl<E: StreamSample> TxStream<E> {
     ///
     /// `buffers` contains one source slice for each channel of the stream.
     ///
-    /// `at_ns` is an optional nanosecond precision device timestamp at which
-    /// the device is to begin the transmission (c.f. [get_hardware_time](Device::get_hardware_time)).
+    /// `at` is an optional device timestamp at which the device is to begin the transmission
+    /// (c.f. [get_hardware_time](Device::get_hardware_time)).
     ///
     /// `end_burst` indicates when this packet ends a burst transmission.
     ///
@@ -1579,43 +2941,29 @@ impl<E: StreamSample> TxStream<E> {
     pub fn write(
         &mut self,
         buffers: &[&[E]],
-        at_ns: Option<i64>,
+        at: Option<HardwareTime>,
         end_burst: bool,
         timeout_us: i64,
     ) -> Result<usize, Error> {
-        unsafe {
-            assert!(
-                buffers.len() == self.nchannels,
-                "Number of buffers must equal number of channels on stream"
-            );
-
-            let num_elems = buffers.first().map_or(0, |x| x.len());
-            for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers) {
-                assert_eq!(src.len(), num_elems, "All buffers must be the same length");
-                *dst = src.as_ptr();
-            }
-
-            let mut flags = 0;
+        assert!(
+            buffers.len() == self.buf_ptrs.len(),
+            "Number of buffers must equal number of channels on stream"
+        );
 
-            if at_ns.is_some() {
-                flags |= SOAPY_SDR_HAS_TIME as i32;
-            }
-
-            if end_burst {
-                flags |= SOAPY_SDR_END_BURST as i32;
-            }
+        let num_elems = buffers.first().map_or(0, |x| x.len());
+        for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers) {
+            assert_eq!(src.len(), num_elems, "All buffers must be the same length");
+            *dst = src.as_ptr();
+        }
 
-            let len = len_result(SoapySDRDevice_writeStream(
-                self.device.inner.ptr,
-                self.handle,
+        unsafe {
+            self.handle.write(
                 self.buf_ptrs.as_ptr() as *const *const _,
                 num_elems,
-                &mut flags as *mut _,
-                at_ns.unwrap_or(0),
-                timeout_us as _,
-            ))?;
-
-            Ok(len as usize)
+                at,
+                end_burst,
+                timeout_us,
+            )
         }
     }
 
@@ -1628,8 +2976,8 @@ impl<E: StreamSample> TxStream<E> {
     ///
     /// `buffers` contains one source slice for each channel of the stream.
     ///
-    /// `at_ns` is an optional nanosecond precision device timestamp at which
-    /// the device is to begin the transmission (c.f. [get_hardware_time](Device::get_hardware_time)).
+    /// `at` is an optional device timestamp at which the device is to begin the transmission
+    /// (c.f. [get_hardware_time](Device::get_hardware_time)).
     ///
     /// `end_burst` indicates when this packet ends a burst transmission.
     ///
@@ -1639,24 +2987,13 @@ impl<E: StreamSample> TxStream<E> {
     pub fn write_all(
         &mut self,
         buffers: &[&[E]],
-        at_ns: Option<i64>,
+        at: Option<HardwareTime>,
         end_burst: bool,
         timeout_us: i64,
     ) -> Result<(), Error> {
-        let mut buffers = buffers.to_owned();
-        let mut at_ns = at_ns;
-
-        while buffers.first().map_or(0, |x| x.len()) > 0 {
-            // The timestamp is only sent on the first write.
-            let written = self.write(&buffers, at_ns.take(), end_burst, timeout_us)?;
-
-            // Advance the buffer pointers
-            for buf in &mut buffers {
-                *buf = &buf[written..];
-            }
-        }
-
-        Ok(())
+        write_all(buffers, at, 1, |buffers, at| {
+            self.write(buffers, at, end_burst, timeout_us)
+        })
     }
 
     /// Read the status of the stream.
@@ -1672,6 +3009,8 @@ impl<E: StreamSample> TxStream<E> {
     ///
     /// [ErrorCode::Timeout] should generally be ignored.
     ///
+    /// See [read_status_event](TxStream::read_status_event) for a typed alternative.
+    ///
     /// Note that `timeout_us` is only `i32` on Windows and panics if the value is
     /// too large for `i32` on that platform.
     pub fn read_status(
@@ -1686,8 +3025,8 @@ impl<E: StreamSample> TxStream<E> {
         let timeout_us = timeout_us.try_into().unwrap();
         unsafe {
             let status = len_result(SoapySDRDevice_readStreamStatus(
-                self.device.inner.ptr,
-                self.handle,
+                self.handle.device.inner.ptr,
+                self.handle.ptr,
                 chan_mask,
                 flags,
                 time_ns,
@@ -1698,5 +3037,544 @@ impl<E: StreamSample> TxStream<E> {
         }
     }
 
-    // TODO: DMA
+    /// Wait for a status event on the stream, such as an underflow or burst completion.
+    ///
+    /// Returns [StatusEvent::Timeout] if no event occurs within `timeout_us`. Drivers that don't
+    /// report stream status return [ErrorCode::NotSupported].
+    ///
+    /// Note that `timeout_us` is only `i32` on Windows and panics if the value is
+    /// too large for `i32` on that platform.
+    pub fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.handle.read_status_event(timeout_us)
+    }
+
+    /// Get an iterator over the pending status events of the stream.
+    ///
+    /// Each call to `next()` waits up to `timeout_us` for an event, and the iterator ends when
+    /// none arrives.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+    /// # let mut stream = dev.tx_stream::<num_complex::Complex<f32>>(&[0]).unwrap();
+    /// for event in stream.status_events(1000) {
+    ///     println!("{:?}", event.unwrap());
+    /// }
+    /// ```
+    pub fn status_events(&mut self, timeout_us: i64) -> StatusEvents<'_> {
+        self.handle.status_events(timeout_us)
+    }
+
+    /// Get the number of direct access buffers the stream can provide.
+    ///
+    /// This is the number of buffers that may be held by the driver's internal ring.
+    /// A return value of 0 means that direct access is not supported.
+    pub fn num_direct_access_buffers(&self) -> usize {
+        unsafe {
+            SoapySDRDevice_getNumDirectAccessBuffers(self.handle.device.inner.ptr, self.handle.ptr)
+        }
+    }
+
+    /// Get the per-channel addresses of a direct access buffer.
+    ///
+    /// `handle` is an index between 0 and
+    /// [`num_direct_access_buffers`](TxStream::num_direct_access_buffers) - 1.
+    /// This can be used to pre-compute per-buffer state once after the stream is created.
+    /// The returned pointers are only valid to dereference while the buffer with that handle is
+    /// acquired.
+    pub fn direct_access_buffer_addrs(&self, handle: usize) -> Result<Vec<*mut E>, Error> {
+        unsafe {
+            let mut addrs = vec![std::ptr::null_mut(); self.buf_ptrs.len()];
+            check_ret_error(SoapySDRDevice_getDirectAccessBufferAddrs(
+                self.handle.device.inner.ptr,
+                self.handle.ptr,
+                handle,
+                addrs.as_mut_ptr(),
+            ))?;
+            Ok(addrs.into_iter().map(|p| p as *mut E).collect())
+        }
+    }
+
+    /// Acquire a buffer directly from the driver to fill with samples for transmission,
+    /// without copying.
+    ///
+    /// The stream must be opened with the device's
+    /// [native stream format](Device::native_stream_format), and the driver must support direct
+    /// buffer access (see [`num_direct_access_buffers`](TxStream::num_direct_access_buffers)).
+    ///
+    /// The buffer is committed for transmission when the returned [`WriteBuffer`] is dropped.
+    /// By default, the whole buffer is sent without a timestamp; use
+    /// [`WriteBuffer::set_len`], [`WriteBuffer::set_time`] and
+    /// [`WriteBuffer::set_end_burst`] to change this before it is committed.
+    ///
+    /// Returns [`ErrorCode::NotSupported`] if `E` is not the native format:
+    ///
+    /// ```
+    /// use num_complex::Complex;
+    /// use soapysdr::{Device, Direction, ErrorCode, Format};
+    ///
+    /// let dev = Device::new("type=null").unwrap();
+    /// let (native, _) = dev.native_stream_format(Direction::Tx, 0).unwrap();
+    /// assert_ne!(native, Format::CF64);
+    ///
+    /// let mut stream = dev.tx_stream::<Complex<f64>>(&[0]).unwrap();
+    /// let err = stream.acquire_write(0).err().unwrap();
+    /// assert_eq!(err.code, ErrorCode::NotSupported);
+    /// ```
+    pub fn acquire_write(&mut self, timeout_us: i64) -> Result<WriteBuffer<'_, E>, Error> {
+        self.handle.check_native_format(E::STREAM_FORMAT)?;
+        unsafe {
+            let mut handle: usize = 0;
+            let len = len_result(SoapySDRDevice_acquireWriteBuffer(
+                self.handle.device.inner.ptr,
+                self.handle.ptr,
+                &mut handle as *mut _,
+                self.buf_ptrs.as_mut_ptr() as *mut *mut _,
+                timeout_us as _,
+            ))?;
+
+            Ok(WriteBuffer {
+                stream: self,
+                handle,
+                capacity: len as usize,
+                len: len as usize,
+                at: None,
+                end_burst: false,
+            })
+        }
+    }
+}
+
+/// A buffer borrowed from the driver by [`TxStream::acquire_write`] to be filled with samples.
+///
+/// The buffer is committed for transmission when dropped.
+pub struct WriteBuffer<'a, E: StreamSample> {
+    stream: &'a mut TxStream<E>,
+    handle: usize,
+    capacity: usize,
+    len: usize,
+    at: Option<HardwareTime>,
+    end_burst: bool,
+}
+
+impl<E: StreamSample> WriteBuffer<'_, E> {
+    /// Get the driver's handle for this buffer, as used by
+    /// [`TxStream::direct_access_buffer_addrs`].
+    pub fn handle(&self) -> usize {
+        self.handle
+    }
+
+    /// Get the number of samples that fit in each channel.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Get the number of samples per channel that will be sent when the buffer is committed.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no samples will be sent when the buffer is committed.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Set the number of samples per channel to send when the buffer is committed.
+    ///
+    /// # Panics
+    ///  * If `len` is larger than [`capacity`](WriteBuffer::capacity).
+    pub fn set_len(&mut self, len: usize) {
+        assert!(len <= self.capacity, "Length exceeds buffer capacity");
+        self.len = len;
+    }
+
+    /// Set an optional device timestamp at which the device is to begin transmitting this
+    /// buffer.
+    pub fn set_time(&mut self, at: Option<HardwareTime>) {
+        self.at = at;
+    }
+
+    /// Set whether this buffer ends a burst transmission.
+    pub fn set_end_burst(&mut self, end_burst: bool) {
+        self.end_burst = end_burst;
+    }
+
+    /// Get the number of channels in the buffer.
+    pub fn num_channels(&self) -> usize {
+        self.stream.num_channels()
+    }
+
+    /// Get the writable samples of a channel, by index into the `channels` array passed to
+    /// `Device::tx_stream`.
+    ///
+    /// # Panics
+    ///  * If `channel` is out of range.
+    pub fn channel_mut(&mut self, channel: usize) -> &mut [E] {
+        unsafe { slice::from_raw_parts_mut(self.stream.buf_ptrs[channel] as *mut E, self.capacity) }
+    }
+
+    /// Commit the buffer for transmission.
+    ///
+    /// This is equivalent to dropping the buffer.
+    pub fn commit(self) {}
+}
+
+impl<E: StreamSample> Drop for WriteBuffer<'_, E> {
+    fn drop(&mut self) {
+        let mut flags = 0;
+
+        if self.at.is_some() {
+            flags |= SOAPY_SDR_HAS_TIME as i32;
+        }
+
+        if self.end_burst {
+            flags |= SOAPY_SDR_END_BURST as i32;
+        }
+
+        unsafe {
+            SoapySDRDevice_releaseWriteBuffer(
+                self.stream.handle.device.inner.ptr,
+                self.stream.handle.ptr,
+                self.handle,
+                self.len,
+                &mut flags as *mut _,
+                self.at.map_or(0, HardwareTime::as_ns),
+            );
+        }
+    }
+}
+
+/// A stream open for receiving, with a sample format selected at runtime.
+///
+/// To obtain a RawRxStream, call [Device::raw_rx_stream]. Samples are read into byte buffers
+/// in the stream's [Format], where each sample occupies [Format::size] bytes.
+///
+/// Streams may involve multiple channels.
+pub struct RawRxStream {
+    handle: StreamHandle,
+    format: Format,
+    sample_size: usize,
+    flags: i32,
+    time_ns: i64,
+    buf_ptrs: Vec<*mut c_void>,
+}
+
+/// Streams may only be used on one thread at a time but may be sent between threads
+unsafe impl Send for RawRxStream {}
+
+impl RawRxStream {
+    /// Get the sample format of the stream.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    ///
+    /// See [RxStream::mtu].
+    pub fn mtu(&self) -> Result<usize, Error> {
+        self.handle.mtu()
+    }
+
+    /// Activate a stream.
+    ///
+    /// See [RxStream::activate].
+    pub fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.activate(0, time, 0)
+    }
+
+    /// Fetch the active state of the stream.
+    pub fn active(&self) -> bool {
+        self.handle.active
+    }
+
+    /// Deactivate a stream.
+    ///
+    /// See [RxStream::deactivate].
+    pub fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.deactivate(time)
+    }
+
+    /// Read samples from the stream into the provided byte buffers.
+    ///
+    /// `buffers` contains one destination slice for each channel of this stream. Each buffer
+    /// holds `len / format.size()` samples; any trailing partial sample is left untouched.
+    ///
+    /// Returns the number of samples read, which may be smaller than the capacity of the passed
+    /// arrays.
+    ///
+    /// # Panics
+    ///  * If `buffers` is not the same length as the `channels` array passed to
+    ///    `Device::raw_rx_stream`.
+    pub fn read(&mut self, buffers: &mut [&mut [u8]], timeout_us: i64) -> Result<usize, Error> {
+        self.read_with_meta(buffers, timeout_us)
+            .map(|meta| meta.len)
+    }
+
+    /// Read samples from the stream into the provided byte buffers, returning the
+    /// number of samples read along with the flags and timestamp reported by the driver.
+    ///
+    /// # Panics
+    ///  * If `buffers` is not the same length as the `channels` array passed to
+    ///    `Device::raw_rx_stream`.
+    pub fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [u8]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        assert!(buffers.len() == self.buf_ptrs.len());
+
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0) / self.sample_size;
+
+        for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers.iter_mut()) {
+            *dst = src.as_mut_ptr() as *mut c_void;
+        }
+
+        let len = unsafe {
+            self.handle.read(
+                self.buf_ptrs.as_ptr(),
+                num_samples,
+                &mut self.flags,
+                &mut self.time_ns,
+                timeout_us,
+            )?
+        };
+
+        Ok(RxMetadata {
+            len,
+            flags: StreamFlags(self.flags),
+            raw_time: HardwareTime::from_ns(self.time_ns),
+        })
+    }
+
+    /// Return timestamp of the last successful `read()` operation.
+    pub fn time(&self) -> HardwareTime {
+        HardwareTime::from_ns(self.time_ns)
+    }
+
+    /// Wait for a status event on the stream, such as an overflow.
+    ///
+    /// See [RxStream::read_status_event].
+    pub fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.handle.read_status_event(timeout_us)
+    }
+
+    /// Get an iterator over the pending status events of the stream.
+    ///
+    /// See [RxStream::status_events].
+    pub fn status_events(&mut self, timeout_us: i64) -> StatusEvents<'_> {
+        self.handle.status_events(timeout_us)
+    }
+}
+
+/// A stream open for transmitting, with a sample format selected at runtime.
+///
+/// To obtain a RawTxStream, call [Device::raw_tx_stream]. Samples are written from byte buffers
+/// in the stream's [Format], where each sample occupies [Format::size] bytes.
+///
+/// Streams may involve multiple channels.
+pub struct RawTxStream {
+    handle: StreamHandle,
+    format: Format,
+    sample_size: usize,
+    buf_ptrs: Vec<*const c_void>,
+}
+
+/// Streams may only be used on one thread at a time but may be sent between threads
+unsafe impl Send for RawTxStream {}
+
+impl RawTxStream {
+    /// Get the sample format of the stream.
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    ///
+    /// See [TxStream::mtu].
+    pub fn mtu(&self) -> Result<usize, Error> {
+        self.handle.mtu()
+    }
+
+    /// Activate a stream.
+    ///
+    /// See [TxStream::activate].
+    pub fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.activate(0, time, 0)
+    }
+
+    /// Fetch the active state of the stream.
+    pub fn active(&self) -> bool {
+        self.handle.active
+    }
+
+    /// Deactivate a stream.
+    ///
+    /// See [TxStream::deactivate].
+    pub fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.handle.deactivate(time)
+    }
+
+    /// Attempt to write samples to the device from the provided byte buffers.
+    ///
+    /// `buffers` contains one source slice for each channel of the stream, each holding a whole
+    /// number of samples of [Format::size] bytes. Otherwise this is the same as
+    /// [TxStream::write].
+    ///
+    /// # Panics
+    ///  * If `buffers` is not the same length as the `channels` array passed to
+    ///    `Device::raw_tx_stream`.
+    ///  * If all the buffers in `buffers` are not the same length.
+    ///  * If the buffer length is not a multiple of the sample size.
+    pub fn write(
+        &mut self,
+        buffers: &[&[u8]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        assert!(
+            buffers.len() == self.buf_ptrs.len(),
+            "Number of buffers must equal number of channels on stream"
+        );
+
+        let num_bytes = buffers.first().map_or(0, |x| x.len());
+        assert!(
+            num_bytes % self.sample_size == 0,
+            "Buffer length must be a multiple of the sample size"
+        );
+        for (dst, src) in self.buf_ptrs.iter_mut().zip(buffers) {
+            assert_eq!(src.len(), num_bytes, "All buffers must be the same length");
+            *dst = src.as_ptr() as *const c_void;
+        }
+
+        unsafe {
+            self.handle.write(
+                self.buf_ptrs.as_ptr(),
+                num_bytes / self.sample_size,
+                at,
+                end_burst,
+                timeout_us,
+            )
+        }
+    }
+
+    /// Write all samples to the device.
+    ///
+    /// See [TxStream::write_all].
+    ///
+    /// # Panics
+    ///  * If `buffers` is not the same length as the `channels` array passed to
+    ///    `Device::raw_tx_stream`.
+    ///  * If all the buffers in `buffers` are not the same length.
+    ///  * If the buffer length is not a multiple of the sample size.
+    pub fn write_all(
+        &mut self,
+        buffers: &[&[u8]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<(), Error> {
+        let sample_size = self.sample_size;
+        write_all(buffers, at, sample_size, |buffers, at| {
+            self.write(buffers, at, end_burst, timeout_us)
+        })
+    }
+
+    /// Wait for a status event on the stream, such as an underflow or burst completion.
+    ///
+    /// See [TxStream::read_status_event].
+    pub fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.handle.read_status_event(timeout_us)
+    }
+
+    /// Get an iterator over the pending status events of the stream.
+    ///
+    /// See [TxStream::status_events].
+    pub fn status_events(&mut self, timeout_us: i64) -> StatusEvents<'_> {
+        self.handle.status_events(timeout_us)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::RefCell;
+    use std::panic::{AssertUnwindSafe, catch_unwind};
+
+    fn error(message: &str) -> Error {
+        Error {
+            code: ErrorCode::Other,
+            message: message.into(),
+        }
+    }
+
+    /// Run `scoped` with `set` and `clear` results, returning its result and the call sequence.
+    fn run_scoped(
+        set: Result<(), Error>,
+        clear: Result<(), Error>,
+        f: impl FnOnce() -> Result<u32, Error>,
+    ) -> (Result<u32, Error>, Vec<&'static str>) {
+        let calls = RefCell::new(Vec::new());
+        let result = scoped(
+            || {
+                calls.borrow_mut().push("set");
+                set
+            },
+            || {
+                calls.borrow_mut().push("clear");
+                clear.clone()
+            },
+            || {
+                calls.borrow_mut().push("f");
+                f()
+            },
+        );
+        (result, calls.into_inner())
+    }
+
+    #[test]
+    fn scoped_ok() {
+        let (result, calls) = run_scoped(Ok(()), Ok(()), || Ok(1));
+        assert_eq!(result.unwrap(), 1);
+        assert_eq!(calls, ["set", "f", "clear"]);
+    }
+
+    #[test]
+    fn scoped_errors() {
+        let (result, calls) = run_scoped(Ok(()), Ok(()), || Err(error("f")));
+        assert_eq!(result.unwrap_err().message, "f");
+        assert_eq!(calls, ["set", "f", "clear"]);
+
+        let (result, calls) = run_scoped(Ok(()), Err(error("clear")), || Err(error("f")));
+        assert_eq!(result.unwrap_err().message, "f");
+        assert_eq!(calls, ["set", "f", "clear"]);
+
+        let (result, calls) = run_scoped(Ok(()), Err(error("clear")), || Ok(1));
+        assert_eq!(result.unwrap_err().message, "clear");
+        assert_eq!(calls, ["set", "f", "clear"]);
+
+        let (result, calls) = run_scoped(Err(error("set")), Ok(()), || Ok(1));
+        assert_eq!(result.unwrap_err().message, "set");
+        assert_eq!(calls, ["set"]);
+    }
+
+    #[test]
+    fn scoped_panic() {
+        let calls = RefCell::new(Vec::new());
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            scoped(
+                || {
+                    calls.borrow_mut().push("set");
+                    Ok(())
+                },
+                || {
+                    calls.borrow_mut().push("clear");
+                    Ok(())
+                },
+                || -> Result<(), Error> { panic!("f") },
+            )
+        }));
+        assert!(result.is_err());
+        assert_eq!(calls.into_inner(), ["set", "clear"]);
+    }
 }
@@ -2,6 +2,7 @@ use num_complex::Complex;
 use soapysdr_sys::*;
 use std::os::raw::c_char;
 use std::str::FromStr;
+use std::{mem, slice};
 
 /// Data format of samples
 #[derive(Copy, Clone, Eq, PartialEq, Hash)]
@@ -164,6 +165,18 @@ pub unsafe trait StreamSample {
     const STREAM_FORMAT: Format;
 }
 
+/// View samples as the bytes of their stream format.
+pub(crate) fn sample_bytes<E: StreamSample>(samples: &[E]) -> &[u8] {
+    // Stream formats are plain data without padding.
+    unsafe { slice::from_raw_parts(samples.as_ptr() as *const u8, mem::size_of_val(samples)) }
+}
+
+/// View samples as mutable bytes of their stream format.
+pub(crate) fn sample_bytes_mut<E: StreamSample>(samples: &mut [E]) -> &mut [u8] {
+    // Stream formats are plain data valid for any bit pattern.
+    unsafe { slice::from_raw_parts_mut(samples.as_mut_ptr() as *mut u8, mem::size_of_val(samples)) }
+}
+
 unsafe impl StreamSample for u8 {
     const STREAM_FORMAT: Format = Format::U8;
 }
@@ -188,22 +201,22 @@ unsafe impl StreamSample for f32 {
 unsafe impl StreamSample for f64 {
     const STREAM_FORMAT: Format = Format::F64;
 }
-//unsupported CU4
+//unsupported CU4, see Device::raw_rx_stream
 unsafe impl StreamSample for Complex<u8> {
     const STREAM_FORMAT: Format = Format::CU8;
 }
-//unsupported CU12
+//unsupported CU12, see Device::raw_rx_stream
 unsafe impl StreamSample for Complex<u16> {
     const STREAM_FORMAT: Format = Format::CU16;
 }
 unsafe impl StreamSample for Complex<u32> {
     const STREAM_FORMAT: Format = Format::CU32;
 }
-//unsupported CS4
+//unsupported CS4, see Device::raw_rx_stream
 unsafe impl StreamSample for Complex<i8> {
     const STREAM_FORMAT: Format = Format::CS8;
 }
-//unsupported CS12
+//unsupported CS12, see Device::raw_rx_stream
 unsafe impl StreamSample for Complex<i16> {
     const STREAM_FORMAT: Format = Format::CS16;
 }
@@ -0,0 +1,121 @@
+use crate::{Device, Error};
+
+/// Data direction of a GPIO pin.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Hash)]
+pub enum GpioDirection {
+    /// The pin is an input.
+    Input,
+
+    /// The pin is driven as an output.
+    Output,
+}
+
+/// A bank of GPIO pins on a device.
+///
+/// To obtain a GpioBank, call [Device::gpio_banks]. Pin numbers are bit positions within the
+/// bank's 32-bit value.
+///
+/// # Example
+/// ```no_run
+/// use soapysdr::GpioDirection;
+/// # let dev = soapysdr::Device::new("driver=lime").unwrap();
+/// let bank = dev.gpio_banks().unwrap().into_iter().next().unwrap();
+/// bank.set_pin_direction(3, GpioDirection::Output).unwrap();
+/// bank.set_pin(3, true).unwrap();
+/// ```
+#[derive(Clone)]
+pub struct GpioBank {
+    device: Device,
+    name: String,
+}
+
+impl GpioBank {
+    pub(crate) fn new(device: Device, name: String) -> GpioBank {
+        GpioBank { device, name }
+    }
+
+    /// Get the name of the bank.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Read the value of all pins in the bank.
+    pub fn read(&self) -> Result<u32, Error> {
+        self.device.read_gpio(self.name.as_str())
+    }
+
+    /// Write the value of all pins in the bank.
+    pub fn write(&self, value: u32) -> Result<(), Error> {
+        self.device.write_gpio(self.name.as_str(), value)
+    }
+
+    /// Write the value of the pins selected by the 1 bits in `mask`, leaving others unchanged.
+    pub fn write_masked(&self, value: u32, mask: u32) -> Result<(), Error> {
+        self.device
+            .write_gpio_masked(self.name.as_str(), value, mask)
+    }
+
+    /// Read the data direction of all pins in the bank, where 1 bits represent outputs.
+    pub fn direction(&self) -> Result<u32, Error> {
+        self.device.read_gpio_dir(self.name.as_str())
+    }
+
+    /// Write the data direction of all pins in the bank, where 1 bits represent outputs.
+    pub fn set_direction(&self, dir: u32) -> Result<(), Error> {
+        self.device.write_gpio_dir(self.name.as_str(), dir)
+    }
+
+    /// Write the data direction of the pins selected by the 1 bits in `mask`, leaving others
+    /// unchanged.
+    pub fn set_direction_masked(&self, dir: u32, mask: u32) -> Result<(), Error> {
+        self.device
+            .write_gpio_dir_masked(self.name.as_str(), dir, mask)
+    }
+
+    /// Read the level of a single pin.
+    ///
+    /// # Panics
+    ///  * If `pin` is not less than 32.
+    pub fn pin(&self, pin: u32) -> Result<bool, Error> {
+        Ok(self.read()? & pin_mask(pin) != 0)
+    }
+
+    /// Set the level of a single pin.
+    ///
+    /// # Panics
+    ///  * If `pin` is not less than 32.
+    pub fn set_pin(&self, pin: u32, high: bool) -> Result<(), Error> {
+        let mask = pin_mask(pin);
+        self.write_masked(if high { mask } else { 0 }, mask)
+    }
+
+    /// Read the data direction of a single pin.
+    ///
+    /// # Panics
+    ///  * If `pin` is not less than 32.
+    pub fn pin_direction(&self, pin: u32) -> Result<GpioDirection, Error> {
+        if self.direction()? & pin_mask(pin) != 0 {
+            Ok(GpioDirection::Output)
+        } else {
+            Ok(GpioDirection::Input)
+        }
+    }
+
+    /// Set the data direction of a single pin.
+    ///
+    /// # Panics
+    ///  * If `pin` is not less than 32.
+    pub fn set_pin_direction(&self, pin: u32, dir: GpioDirection) -> Result<(), Error> {
+        let mask = pin_mask(pin);
+        let dir = match dir {
+            GpioDirection::Input => 0,
+            GpioDirection::Output => mask,
+        };
+        self.set_direction_masked(dir, mask)
+    }
+}
+
+fn pin_mask(pin: u32) -> u32 {
+    assert!(pin < 32, "GPIO pin number out of range");
+    1 << pin
+}
@@ -7,14 +7,69 @@ mod args;
 pub use args::{Args, ArgsIterator};
 
 mod arginfo;
-pub use arginfo::ArgInfo;
+pub use arginfo::{ArgInfo, ArgType};
 
 mod device;
-pub use device::{Device, Direction, Error, ErrorCode, Range, RxStream, TxStream, enumerate};
+pub use device::{
+    ChannelSet, Device, Direction, Error, ErrorCode, Range, RawRxStream, RawTxStream, ReadBuffer,
+    RxMetadata, RxStream, StatusEvent, StatusEvents, StreamFlags, TxStream, WriteBuffer, enumerate,
+};
+
+mod clock;
+
+mod corrections;
+pub use corrections::{CorrectionSettings, Corrections, SupportedCorrections};
 
 mod format;
 pub use format::{Format, StreamSample};
 
+mod gpio;
+pub use gpio::{GpioBank, GpioDirection};
+
+mod ppm;
+pub use ppm::PpmTuner;
+
+mod registers;
+pub use registers::{Bitfield, Register, RegisterBank, RegisterDump, RegisterMap};
+
+mod sdr;
+pub use sdr::{SdrDevice, SdrRxStream, SdrTxStream};
+
+mod settings;
+pub use settings::SettingValue;
+
+mod uart;
+pub use uart::Uart;
+
+mod time;
+pub use time::HardwareTime;
+
+mod version;
+pub use version::{BINDINGS_API_VERSION, LibraryVersion, Version, version};
+
+mod rate_plan;
+pub use rate_plan::RatePlan;
+
+mod reader;
+pub use reader::{RxChunk, RxReader};
+
+mod ring;
+
+pub mod convert;
+
+pub mod mock;
+
+pub mod sim;
+
+pub mod modules;
+
+pub mod record;
+
+#[cfg(feature = "async")]
+mod async_stream;
+#[cfg(feature = "async")]
+pub use async_stream::{AsyncRxStream, AsyncTxStream, RxPacket, TxPacket};
+
 /// Configures SoapySDR to log to the Rust `log` facility.
 ///
 /// With `env_logger`, use e.g `RUST_LOG=soapysdr=info` to control the log level.
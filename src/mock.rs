@@ -0,0 +1,2072 @@
+//! An in-memory [`SdrDevice`] for testing application code without hardware.
+//!
+//! [`MockDevice`] stores the settings applied through the [`SdrDevice`] methods so they read back
+//! as a driver would report them, and records every call it receives. Tests script the samples
+//! returned by receive streams, inspect the samples written to transmit streams, and make any
+//! call fail with [`MockDevice::inject_error`].
+//!
+//! A `MockDevice` is a cheap handle to shared state, so a test can keep a clone to script and
+//! inspect the device while the code under test owns another.
+//!
+//! # Example
+//! ```
+//! use soapysdr::mock::MockDevice;
+//! use soapysdr::{ErrorCode, SdrDevice, SdrRxStream};
+//!
+//! let dev = MockDevice::new();
+//! dev.queue_rx(&[&[1i16, 2, 3, 4][..]]);
+//! dev.inject_error("rx.read", 2, ErrorCode::Overflow);
+//!
+//! let mut stream = dev.rx_stream::<i16>(&[0]).unwrap();
+//! stream.activate(None).unwrap();
+//! let mut buf = [0; 4];
+//! assert_eq!(stream.read(&mut [&mut buf], 1000).unwrap(), 4);
+//! assert_eq!(stream.read(&mut [&mut buf], 1000).unwrap_err().code, ErrorCode::Overflow);
+//! ```
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::{mem, ptr};
+
+use crate::format::{sample_bytes, sample_bytes_mut};
+use crate::{
+    ArgInfo, ArgType, Args, Direction, Error, ErrorCode, Format, HardwareTime, Range, RxMetadata,
+    SdrDevice, SdrRxStream, SdrTxStream, StatusEvent, StreamFlags, StreamSample,
+};
+
+fn index(direction: Direction) -> usize {
+    match direction {
+        Direction::Tx => 0,
+        Direction::Rx => 1,
+    }
+}
+
+fn string<S: Into<Vec<u8>>>(s: S) -> String {
+    String::from_utf8_lossy(&s.into()).into_owned()
+}
+
+fn other(message: String) -> Error {
+    Error {
+        code: ErrorCode::Other,
+        message,
+    }
+}
+
+fn not_supported(what: &str) -> Error {
+    Error {
+        code: ErrorCode::NotSupported,
+        message: format!("{what} not supported"),
+    }
+}
+
+fn timeout() -> Error {
+    Error {
+        code: ErrorCode::Timeout,
+        message: "No samples queued".into(),
+    }
+}
+
+fn from_bytes<E: StreamSample>(bytes: &[u8]) -> Vec<E> {
+    let len = bytes.len() / mem::size_of::<E>();
+    let mut samples = Vec::with_capacity(len);
+    unsafe {
+        ptr::copy_nonoverlapping(
+            bytes.as_ptr(),
+            samples.as_mut_ptr() as *mut u8,
+            len * mem::size_of::<E>(),
+        );
+        samples.set_len(len);
+    }
+    samples
+}
+
+#[derive(Clone)]
+struct Gain {
+    name: String,
+    range: Range,
+    value: f64,
+}
+
+#[derive(Clone)]
+struct Tunable {
+    name: String,
+    ranges: Vec<Range>,
+    value: f64,
+}
+
+#[derive(Clone)]
+struct Channel {
+    antennas: Vec<String>,
+    antenna: String,
+    gains: Vec<Gain>,
+    gain_mode: Option<bool>,
+    tunables: Vec<Tunable>,
+    sample_rate: f64,
+    sample_rate_range: Vec<Range>,
+    bandwidth: f64,
+    bandwidth_range: Vec<Range>,
+    dc_offset_mode: Option<bool>,
+    dc_offset: Option<(f64, f64)>,
+    iq_balance_mode: Option<bool>,
+    iq_balance: Option<(f64, f64)>,
+    frequency_correction: Option<f64>,
+    sensors: BTreeMap<String, String>,
+    settings: BTreeMap<String, String>,
+}
+
+impl Channel {
+    fn new(direction: Direction) -> Channel {
+        let antenna = match direction {
+            Direction::Tx => "TX",
+            Direction::Rx => "RX",
+        };
+        Channel {
+            antennas: vec![antenna.into()],
+            antenna: antenna.into(),
+            gains: vec![Gain {
+                name: "PGA".into(),
+                range: Range::new(0.0, 60.0, 1.0),
+                value: 0.0,
+            }],
+            gain_mode: None,
+            tunables: vec![Tunable {
+                name: "RF".into(),
+                ranges: vec![Range::new(1e6, 6e9, 0.0)],
+                value: 100e6,
+            }],
+            sample_rate: 1e6,
+            sample_rate_range: vec![Range::new(100e3, 10e6, 0.0)],
+            bandwidth: 1e6,
+            bandwidth_range: vec![Range::new(200e3, 20e6, 0.0)],
+            dc_offset_mode: None,
+            dc_offset: None,
+            iq_balance_mode: None,
+            iq_balance: None,
+            frequency_correction: None,
+            sensors: BTreeMap::new(),
+            settings: BTreeMap::new(),
+        }
+    }
+
+    fn gain_mut(&mut self, name: &str) -> Result<&mut Gain, Error> {
+        self.gains
+            .iter_mut()
+            .find(|g| g.name == name)
+            .ok_or_else(|| other(format!("Unknown gain element {name}")))
+    }
+
+    fn tunable_mut(&mut self, name: &str) -> Result<&mut Tunable, Error> {
+        self.tunables
+            .iter_mut()
+            .find(|t| t.name == name)
+            .ok_or_else(|| other(format!("Unknown tunable element {name}")))
+    }
+}
+
+enum RxEntry {
+    Samples {
+        format: Format,
+        buffers: Vec<Vec<u8>>,
+        flags: StreamFlags,
+        time: Option<HardwareTime>,
+    },
+    Error(ErrorCode),
+}
+
+struct TxEntry {
+    format: Format,
+    buffers: Vec<Vec<u8>>,
+    at: Option<HardwareTime>,
+    end_burst: bool,
+}
+
+struct Failure {
+    method: String,
+    remaining: usize,
+    code: ErrorCode,
+}
+
+struct State {
+    driver_key: String,
+    hardware_key: String,
+    hardware_info: Vec<(String, String)>,
+    frontend_mapping: [String; 2],
+    channels: [Vec<Channel>; 2],
+    mtu: usize,
+    sensors: BTreeMap<String, String>,
+    settings: BTreeMap<String, String>,
+    clock_sources: Vec<String>,
+    clock_source: String,
+    master_clock_rate: f64,
+    reference_clock_rate: f64,
+    time_sources: Vec<String>,
+    time_source: String,
+    hardware_time: BTreeMap<String, HardwareTime>,
+    registers: BTreeMap<String, BTreeMap<u32, u32>>,
+    gpio: BTreeMap<String, (u32, u32)>,
+    uarts: BTreeMap<String, VecDeque<u8>>,
+    calls: Vec<String>,
+    failures: Vec<Failure>,
+    rx: VecDeque<RxEntry>,
+    tx: Vec<TxEntry>,
+    status: [VecDeque<StatusEvent>; 2],
+}
+
+impl State {
+    fn channel_mut(&mut self, direction: Direction, channel: usize) -> Result<&mut Channel, Error> {
+        self.channels[index(direction)]
+            .get_mut(channel)
+            .ok_or_else(|| other(format!("Invalid {direction:?} channel {channel}")))
+    }
+
+    fn registers_mut(&mut self, name: &str) -> Result<&mut BTreeMap<u32, u32>, Error> {
+        self.registers
+            .get_mut(name)
+            .ok_or_else(|| not_supported(&format!("Register interface {name}")))
+    }
+
+    fn gpio_mut(&mut self, bank: &str) -> Result<&mut (u32, u32), Error> {
+        self.gpio
+            .get_mut(bank)
+            .ok_or_else(|| not_supported(&format!("GPIO bank {bank}")))
+    }
+
+    fn uart_mut(&mut self, which: &str) -> Result<&mut VecDeque<u8>, Error> {
+        self.uarts
+            .get_mut(which)
+            .ok_or_else(|| not_supported(&format!("UART {which}")))
+    }
+}
+
+/// A single write to a [`MockTxStream`], as returned by [`MockDevice::take_tx`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TxWrite<E> {
+    /// The samples written to each channel of the stream.
+    pub buffers: Vec<Vec<E>>,
+
+    /// The timestamp passed to the write, if any.
+    pub at: Option<HardwareTime>,
+
+    /// Whether the write ended a burst.
+    pub end_burst: bool,
+}
+
+/// An in-memory device implementing [`SdrDevice`].
+///
+/// A new device has one channel in each direction, each with a single antenna, a `PGA` gain
+/// element and an `RF` tunable element. The `with_*` methods change this configuration. Features
+/// that haven't been enabled, such as automatic gain control, return
+/// [`ErrorCode::NotSupported`] when set.
+#[derive(Clone)]
+pub struct MockDevice {
+    state: Arc<Mutex<State>>,
+}
+
+impl Default for MockDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockDevice {
+    /// Create a mock device with the default configuration.
+    pub fn new() -> MockDevice {
+        MockDevice {
+            state: Arc::new(Mutex::new(State {
+                driver_key: "mock".into(),
+                hardware_key: "mock".into(),
+                hardware_info: Vec::new(),
+                frontend_mapping: [String::new(), String::new()],
+                channels: [
+                    vec![Channel::new(Direction::Tx)],
+                    vec![Channel::new(Direction::Rx)],
+                ],
+                mtu: 4096,
+                sensors: BTreeMap::new(),
+                settings: BTreeMap::new(),
+                clock_sources: vec!["internal".into()],
+                clock_source: "internal".into(),
+                master_clock_rate: 0.0,
+                reference_clock_rate: 0.0,
+                time_sources: vec!["internal".into()],
+                time_source: "internal".into(),
+                hardware_time: BTreeMap::new(),
+                registers: BTreeMap::new(),
+                gpio: BTreeMap::new(),
+                uarts: BTreeMap::new(),
+                calls: Vec::new(),
+                failures: Vec::new(),
+                rx: VecDeque::new(),
+                tx: Vec::new(),
+                status: [VecDeque::new(), VecDeque::new()],
+            })),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap()
+    }
+
+    fn configure(self, direction: Direction, f: impl Fn(&mut Channel)) -> MockDevice {
+        self.lock().channels[index(direction)]
+            .iter_mut()
+            .for_each(f);
+        self
+    }
+
+    /// Set the driver key.
+    pub fn with_driver_key<S: Into<String>>(self, key: S) -> MockDevice {
+        self.lock().driver_key = key.into();
+        self
+    }
+
+    /// Set the hardware key.
+    pub fn with_hardware_key<S: Into<String>>(self, key: S) -> MockDevice {
+        self.lock().hardware_key = key.into();
+        self
+    }
+
+    /// Add an entry to the hardware info.
+    pub fn with_hardware_info<K: Into<String>, V: Into<String>>(
+        self,
+        key: K,
+        value: V,
+    ) -> MockDevice {
+        self.lock().hardware_info.push((key.into(), value.into()));
+        self
+    }
+
+    /// Set the number of channels in a direction.
+    ///
+    /// New channels copy the configuration of channel 0.
+    pub fn with_channels(self, direction: Direction, num_channels: usize) -> MockDevice {
+        {
+            let mut state = self.lock();
+            let channels = &mut state.channels[index(direction)];
+            let template = channels
+                .first()
+                .cloned()
+                .unwrap_or_else(|| Channel::new(direction));
+            channels.resize(num_channels, template);
+        }
+        self
+    }
+
+    /// Set the antennas of every channel in a direction, selecting the first.
+    pub fn with_antennas(self, direction: Direction, antennas: &[&str]) -> MockDevice {
+        self.configure(direction, |ch| {
+            ch.antennas = antennas.iter().map(|&a| a.into()).collect();
+            ch.antenna = ch.antennas.first().cloned().unwrap_or_default();
+        })
+    }
+
+    /// Set the gain elements of every channel in a direction, each starting at its minimum.
+    pub fn with_gain_elements(self, direction: Direction, gains: &[(&str, Range)]) -> MockDevice {
+        self.configure(direction, |ch| {
+            ch.gains = gains
+                .iter()
+                .map(|&(name, range)| Gain {
+                    name: name.into(),
+                    range,
+                    value: range.minimum,
+                })
+                .collect();
+        })
+    }
+
+    /// Set the tunable range of every channel in a direction.
+    pub fn with_frequency_range(self, direction: Direction, ranges: Vec<Range>) -> MockDevice {
+        self.configure(direction, |ch| ch.tunables[0].ranges = ranges.clone())
+    }
+
+    /// Set the sample rate range of every channel in a direction.
+    pub fn with_sample_rate_range(self, direction: Direction, ranges: Vec<Range>) -> MockDevice {
+        self.configure(direction, |ch| ch.sample_rate_range = ranges.clone())
+    }
+
+    /// Set the bandwidth range of every channel in a direction.
+    pub fn with_bandwidth_range(self, direction: Direction, ranges: Vec<Range>) -> MockDevice {
+        self.configure(direction, |ch| ch.bandwidth_range = ranges.clone())
+    }
+
+    /// Support automatic gain control on every channel in a direction.
+    pub fn with_gain_mode(self, direction: Direction) -> MockDevice {
+        self.configure(direction, |ch| ch.gain_mode = Some(false))
+    }
+
+    /// Support DC offset, IQ balance and frequency corrections on every channel in a direction.
+    pub fn with_corrections(self, direction: Direction) -> MockDevice {
+        self.configure(direction, |ch| {
+            ch.dc_offset_mode = Some(false);
+            ch.dc_offset = Some((0.0, 0.0));
+            ch.iq_balance_mode = Some(false);
+            ch.iq_balance = Some((0.0, 0.0));
+            ch.frequency_correction = Some(0.0);
+        })
+    }
+
+    /// Set the MTU of streams, which also limits the number of samples accepted per write.
+    pub fn with_mtu(self, mtu: usize) -> MockDevice {
+        self.lock().mtu = mtu;
+        self
+    }
+
+    /// Set the available clock sources, selecting the first.
+    pub fn with_clock_sources(self, sources: &[&str]) -> MockDevice {
+        {
+            let mut state = self.lock();
+            state.clock_sources = sources.iter().map(|&s| s.into()).collect();
+            state.clock_source = state.clock_sources.first().cloned().unwrap_or_default();
+        }
+        self
+    }
+
+    /// Set the available time sources, selecting the first.
+    pub fn with_time_sources(self, sources: &[&str]) -> MockDevice {
+        {
+            let mut state = self.lock();
+            state.time_sources = sources.iter().map(|&s| s.into()).collect();
+            state.time_source = state.time_sources.first().cloned().unwrap_or_default();
+        }
+        self
+    }
+
+    /// Add a register interface, with all registers initially zero.
+    pub fn with_register_interface<S: Into<String>>(self, name: S) -> MockDevice {
+        self.lock().registers.insert(name.into(), BTreeMap::new());
+        self
+    }
+
+    /// Add a GPIO bank.
+    pub fn with_gpio_bank<S: Into<String>>(self, name: S) -> MockDevice {
+        self.lock().gpio.insert(name.into(), (0, 0));
+        self
+    }
+
+    /// Add a UART.
+    pub fn with_uart<S: Into<String>>(self, name: S) -> MockDevice {
+        self.lock().uarts.insert(name.into(), VecDeque::new());
+        self
+    }
+
+    /// Set the value of a device sensor, adding it if it doesn't exist.
+    pub fn set_sensor<K: Into<String>, V: Into<String>>(&self, key: K, value: V) {
+        self.lock().sensors.insert(key.into(), value.into());
+    }
+
+    /// Set the value of a sensor on a channel, adding it if it doesn't exist.
+    ///
+    /// # Panics
+    ///  * If the channel doesn't exist.
+    pub fn set_channel_sensor<K: Into<String>, V: Into<String>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: K,
+        value: V,
+    ) {
+        self.lock()
+            .channel_mut(direction, channel)
+            .unwrap()
+            .sensors
+            .insert(key.into(), value.into());
+    }
+
+    /// Queue bytes to be returned by reads from a UART.
+    ///
+    /// # Panics
+    ///  * If the UART doesn't exist.
+    pub fn queue_uart_rx(&self, which: &str, data: &[u8]) {
+        self.lock().uart_mut(which).unwrap().extend(data);
+    }
+
+    /// Queue samples to be returned by receive stream reads, one buffer per stream channel.
+    ///
+    /// Each queued entry is returned by one or more reads, depending on the size of the buffers
+    /// passed to the read. Reads with no queued entries fail with [`ErrorCode::Timeout`].
+    pub fn queue_rx<E: StreamSample>(&self, buffers: &[&[E]]) {
+        self.queue_rx_with_meta(buffers, StreamFlags::empty(), None);
+    }
+
+    /// Queue samples to be returned by receive stream reads with the given flags and timestamp.
+    ///
+    /// If the samples are returned by several reads, the timestamp of each read advances by the
+    /// number of samples already read at the channel's sample rate, and `END_BURST` is only
+    /// reported by the last.
+    pub fn queue_rx_with_meta<E: StreamSample>(
+        &self,
+        buffers: &[&[E]],
+        flags: StreamFlags,
+        time: Option<HardwareTime>,
+    ) {
+        let flags = match time {
+            Some(_) => flags | StreamFlags::HAS_TIME,
+            None => flags,
+        };
+        self.lock().rx.push_back(RxEntry::Samples {
+            format: E::STREAM_FORMAT,
+            buffers: buffers.iter().map(|b| sample_bytes(b).to_vec()).collect(),
+            flags,
+            time,
+        });
+    }
+
+    /// Queue an error to be returned by a receive stream read, in order with queued samples.
+    pub fn queue_rx_error(&self, code: ErrorCode) {
+        self.lock().rx.push_back(RxEntry::Error(code));
+    }
+
+    /// Queue an event to be returned by `read_status_event` on streams in a direction.
+    ///
+    /// Reads with no queued events return [`StatusEvent::Timeout`].
+    pub fn queue_status_event(&self, direction: Direction, event: StatusEvent) {
+        self.lock().status[index(direction)].push_back(event);
+    }
+
+    /// Remove and return the writes made to transmit streams so far.
+    ///
+    /// # Panics
+    ///  * If a write was made to a stream with a sample type other than `E`.
+    pub fn take_tx<E: StreamSample>(&self) -> Vec<TxWrite<E>> {
+        self.lock()
+            .tx
+            .drain(..)
+            .map(|w| {
+                assert!(
+                    w.format == E::STREAM_FORMAT,
+                    "Transmitted samples are {}, not {}",
+                    w.format,
+                    E::STREAM_FORMAT
+                );
+                TxWrite {
+                    buffers: w.buffers.iter().map(|b| from_bytes(b)).collect(),
+                    at: w.at,
+                    end_burst: w.end_burst,
+                }
+            })
+            .collect()
+    }
+
+    /// Make the `nth` call to `method` from now on fail with `code`, where `nth` starts at 1.
+    ///
+    /// `method` is the name of an [`SdrDevice`] method, such as `"set_frequency"`, or a stream
+    /// method prefixed with `rx.` or `tx.`, such as `"rx.read"`. Receive stream reads are named
+    /// `rx.read` regardless of whether they return metadata.
+    ///
+    /// # Panics
+    ///  * If `nth` is 0.
+    pub fn inject_error(&self, method: &str, nth: usize, code: ErrorCode) {
+        assert!(nth > 0, "nth counts from 1");
+        self.lock().failures.push(Failure {
+            method: method.into(),
+            remaining: nth,
+            code,
+        });
+    }
+
+    /// Get the log of calls made to the device and its streams.
+    ///
+    /// Each entry is the method name followed by its arguments, e.g.
+    /// `set_frequency(Rx, 0, 100000000.0, "")`.
+    pub fn calls(&self) -> Vec<String> {
+        self.lock().calls.clone()
+    }
+
+    /// Clear the call log.
+    pub fn clear_calls(&self) {
+        self.lock().calls.clear();
+    }
+
+    /// Log a call and check whether it should fail, returning the locked state.
+    fn enter(&self, method: &str, args: &[&dyn Debug]) -> Result<MutexGuard<'_, State>, Error> {
+        let mut state = self.lock();
+        let args: Vec<String> = args.iter().map(|a| format!("{a:?}")).collect();
+        state.calls.push(format!("{method}({})", args.join(", ")));
+
+        let mut failure = None;
+        state.failures.retain_mut(|f| {
+            if f.method != method || failure.is_some() {
+                return true;
+            }
+            f.remaining -= 1;
+            if f.remaining == 0 {
+                failure = Some(f.code);
+            }
+            f.remaining > 0
+        });
+
+        match failure {
+            Some(code) => Err(Error {
+                code,
+                message: format!("Injected error in {method}"),
+            }),
+            None => Ok(state),
+        }
+    }
+
+    /// Log a call on a channel and run `f` on the channel's state.
+    fn channel_call<T>(
+        &self,
+        method: &str,
+        direction: Direction,
+        channel: usize,
+        args: &[&dyn Debug],
+        f: impl FnOnce(&mut Channel) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let mut all: Vec<&dyn Debug> = vec![&direction, &channel];
+        all.extend_from_slice(args);
+        let mut state = self.enter(method, &all)?;
+        f(state.channel_mut(direction, channel)?)
+    }
+
+    pub(crate) fn stream_channels(
+        &self,
+        direction: Direction,
+        channels: &[usize],
+    ) -> Result<Vec<usize>, Error> {
+        let num_channels = self.lock().channels[index(direction)].len();
+        if channels.is_empty() {
+            return Ok(vec![0]);
+        }
+        match channels.iter().find(|&&c| c >= num_channels) {
+            Some(c) => Err(other(format!("Invalid {direction:?} channel {c}"))),
+            None => Ok(channels.to_vec()),
+        }
+    }
+}
+
+impl MockDevice {
+    // Hooks for streams implemented outside this module, such as the simulator's.
+
+    pub(crate) fn log_call(&self, method: &str, args: &[&dyn Debug]) -> Result<(), Error> {
+        self.enter(method, args).map(drop)
+    }
+
+    pub(crate) fn mtu(&self) -> usize {
+        self.lock().mtu
+    }
+
+    pub(crate) fn rx_tuning(&self, channel: usize) -> Result<Tuning, Error> {
+        let mut state = self.lock();
+        let ch = state.channel_mut(Direction::Rx, channel)?;
+        Ok(Tuning {
+            frequency: ch.tunables.iter().map(|t| t.value).sum(),
+            gain: ch.gains.iter().map(|g| g.value).sum(),
+            sample_rate: ch.sample_rate,
+            bandwidth: ch.bandwidth,
+        })
+    }
+
+    pub(crate) fn time(&self) -> HardwareTime {
+        let state = self.lock();
+        state
+            .hardware_time
+            .get("")
+            .copied()
+            .unwrap_or(HardwareTime::ZERO)
+    }
+
+    pub(crate) fn set_time(&self, time: HardwareTime) {
+        self.lock().hardware_time.insert(String::new(), time);
+    }
+
+    pub(crate) fn next_status_event(&self, direction: Direction) -> StatusEvent {
+        let event = self.lock().status[index(direction)].pop_front();
+        event.unwrap_or(StatusEvent::Timeout)
+    }
+}
+
+/// The receive settings of a channel.
+pub(crate) struct Tuning {
+    pub frequency: f64,
+    pub gain: f64,
+    pub sample_rate: f64,
+    pub bandwidth: f64,
+}
+
+impl SdrDevice for MockDevice {
+    type RxStream<E: StreamSample> = MockRxStream<E>;
+    type TxStream<E: StreamSample> = MockTxStream<E>;
+
+    fn driver_key(&self) -> Result<String, Error> {
+        Ok(self.enter("driver_key", &[])?.driver_key.clone())
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        Ok(self.enter("hardware_key", &[])?.hardware_key.clone())
+    }
+
+    fn hardware_info(&self) -> Result<Args, Error> {
+        let state = self.enter("hardware_info", &[])?;
+        Ok(state
+            .hardware_info
+            .iter()
+            .map(|(k, v)| (k.as_str(), v.as_str()))
+            .collect())
+    }
+
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error> {
+        let state = self.enter("frontend_mapping", &[&direction])?;
+        Ok(state.frontend_mapping[index(direction)].clone())
+    }
+
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error> {
+        let mapping = string(mapping);
+        let mut state = self.enter("set_frontend_mapping", &[&direction, &mapping])?;
+        state.frontend_mapping[index(direction)] = mapping;
+        Ok(())
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        Ok(self.enter("num_channels", &[&direction])?.channels[index(direction)].len())
+    }
+
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error> {
+        self.channel_call("channel_info", direction, channel, &[], |_| Ok(Args::new()))
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("full_duplex", direction, channel, &[], |_| Ok(true))
+    }
+
+    fn list_sensors(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .enter("list_sensors", &[])?
+            .sensors
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error> {
+        let state = self.enter("get_sensor_info", &[&key])?;
+        let value = state
+            .sensors
+            .get(key)
+            .ok_or_else(|| other(format!("Unknown sensor {key}")))?;
+        Ok(sensor_info(key, value))
+    }
+
+    fn read_sensor(&self, key: &str) -> Result<String, Error> {
+        let state = self.enter("read_sensor", &[&key])?;
+        state
+            .sensors
+            .get(key)
+            .cloned()
+            .ok_or_else(|| other(format!("Unknown sensor {key}")))
+    }
+
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.channel_call("list_channel_sensors", dir, channel, &[], |ch| {
+            Ok(ch.sensors.keys().cloned().collect())
+        })
+    }
+
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error> {
+        self.channel_call("get_channel_sensor_info", dir, channel, &[&key], |ch| {
+            let value = ch
+                .sensors
+                .get(key)
+                .ok_or_else(|| other(format!("Unknown sensor {key}")))?;
+            Ok(sensor_info(key, value))
+        })
+    }
+
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error> {
+        self.channel_call("read_channel_sensor", dir, channel, &[&key], |ch| {
+            ch.sensors
+                .get(key)
+                .cloned()
+                .ok_or_else(|| other(format!("Unknown sensor {key}")))
+        })
+    }
+
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        self.channel_call("stream_formats", direction, channel, &[], |_| {
+            Ok(vec![Format::CS16, Format::CF32])
+        })
+    }
+
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        self.channel_call("native_stream_format", direction, channel, &[], |_| {
+            Ok((Format::CS16, 32768.0))
+        })
+    }
+
+    fn stream_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.channel_call("stream_args_info", direction, channel, &[], |_| {
+            Ok(Vec::new())
+        })
+    }
+
+    fn rx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<MockRxStream<E>, Error> {
+        self.rx_stream_args(channels, ())
+    }
+
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<MockRxStream<E>, Error> {
+        let args = args.into().to_string();
+        drop(self.enter("rx_stream", &[&E::STREAM_FORMAT, &channels, &args])?);
+        Ok(MockRxStream {
+            device: self.clone(),
+            channels: self.stream_channels(Direction::Rx, channels)?,
+            active: false,
+            burst: None,
+            last: RxMetadata {
+                len: 0,
+                flags: StreamFlags::empty(),
+                raw_time: HardwareTime::ZERO,
+            },
+            phantom: PhantomData,
+        })
+    }
+
+    fn tx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<MockTxStream<E>, Error> {
+        self.tx_stream_args(channels, ())
+    }
+
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<MockTxStream<E>, Error> {
+        let args = args.into().to_string();
+        drop(self.enter("tx_stream", &[&E::STREAM_FORMAT, &channels, &args])?);
+        Ok(MockTxStream {
+            device: self.clone(),
+            channels: self.stream_channels(Direction::Tx, channels)?,
+            active: false,
+            phantom: PhantomData,
+        })
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.channel_call("antennas", direction, channel, &[], |ch| {
+            Ok(ch.antennas.clone())
+        })
+    }
+
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error> {
+        let name = string(name);
+        self.channel_call("set_antenna", direction, channel, &[&name], |ch| {
+            if !ch.antennas.contains(&name) {
+                return Err(other(format!("Unknown antenna {name}")));
+            }
+            ch.antenna = name.clone();
+            Ok(())
+        })
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        self.channel_call("antenna", direction, channel, &[], |ch| {
+            Ok(ch.antenna.clone())
+        })
+    }
+
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("has_dc_offset_mode", direction, channel, &[], |ch| {
+            Ok(ch.dc_offset_mode.is_some())
+        })
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.channel_call(
+            "set_dc_offset_mode",
+            direction,
+            channel,
+            &[&automatic],
+            |ch| {
+                let mode = ch
+                    .dc_offset_mode
+                    .as_mut()
+                    .ok_or_else(|| not_supported("DC offset mode"))?;
+                *mode = automatic;
+                Ok(())
+            },
+        )
+    }
+
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("dc_offset_mode", direction, channel, &[], |ch| {
+            Ok(ch.dc_offset_mode.unwrap_or(false))
+        })
+    }
+
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("has_dc_offset", direction, channel, &[], |ch| {
+            Ok(ch.dc_offset.is_some())
+        })
+    }
+
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error> {
+        self.channel_call(
+            "set_dc_offset",
+            direction,
+            channel,
+            &[&offset_i, &offset_q],
+            |ch| {
+                let offset = ch
+                    .dc_offset
+                    .as_mut()
+                    .ok_or_else(|| not_supported("DC offset"))?;
+                *offset = (offset_i, offset_q);
+                Ok(())
+            },
+        )
+    }
+
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.channel_call("dc_offset", direction, channel, &[], |ch| {
+            Ok(ch.dc_offset.unwrap_or((0.0, 0.0)))
+        })
+    }
+
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("has_iq_balance", direction, channel, &[], |ch| {
+            Ok(ch.iq_balance.is_some())
+        })
+    }
+
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error> {
+        self.channel_call(
+            "set_iq_balance",
+            direction,
+            channel,
+            &[&balance_i, &balance_q],
+            |ch| {
+                let balance = ch
+                    .iq_balance
+                    .as_mut()
+                    .ok_or_else(|| not_supported("IQ balance"))?;
+                *balance = (balance_i, balance_q);
+                Ok(())
+            },
+        )
+    }
+
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.channel_call("iq_balance", direction, channel, &[], |ch| {
+            Ok(ch.iq_balance.unwrap_or((0.0, 0.0)))
+        })
+    }
+
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("has_iq_balance_mode", direction, channel, &[], |ch| {
+            Ok(ch.iq_balance_mode.is_some())
+        })
+    }
+
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.channel_call(
+            "set_iq_balance_mode",
+            direction,
+            channel,
+            &[&automatic],
+            |ch| {
+                let mode = ch
+                    .iq_balance_mode
+                    .as_mut()
+                    .ok_or_else(|| not_supported("IQ balance mode"))?;
+                *mode = automatic;
+                Ok(())
+            },
+        )
+    }
+
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("iq_balance_mode", direction, channel, &[], |ch| {
+            Ok(ch.iq_balance_mode.unwrap_or(false))
+        })
+    }
+
+    fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        self.channel_call("has_frequency_correction", direction, channel, &[], |ch| {
+            Ok(ch.frequency_correction.is_some())
+        })
+    }
+
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        self.channel_call(
+            "set_frequency_correction",
+            direction,
+            channel,
+            &[&ppm],
+            |ch| {
+                let correction = ch
+                    .frequency_correction
+                    .as_mut()
+                    .ok_or_else(|| not_supported("Frequency correction"))?;
+                *correction = ppm;
+                Ok(())
+            },
+        )
+    }
+
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.channel_call("frequency_correction", direction, channel, &[], |ch| {
+            Ok(ch.frequency_correction.unwrap_or(0.0))
+        })
+    }
+
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.channel_call("list_gains", direction, channel, &[], |ch| {
+            Ok(ch.gains.iter().map(|g| g.name.clone()).collect())
+        })
+    }
+
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("has_gain_mode", direction, channel, &[], |ch| {
+            Ok(ch.gain_mode.is_some())
+        })
+    }
+
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.channel_call("set_gain_mode", direction, channel, &[&automatic], |ch| {
+            let mode = ch
+                .gain_mode
+                .as_mut()
+                .ok_or_else(|| not_supported("Automatic gain control"))?;
+            *mode = automatic;
+            Ok(())
+        })
+    }
+
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.channel_call("gain_mode", direction, channel, &[], |ch| {
+            Ok(ch.gain_mode.unwrap_or(false))
+        })
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        self.channel_call("set_gain", direction, channel, &[&gain], |ch| {
+            // Like SoapySDR's default implementation, fill the receive elements in order and the
+            // transmit elements in reverse.
+            let mut remaining = gain - ch.gains.iter().map(|g| g.range.minimum).sum::<f64>();
+            let mut fill = |g: &mut Gain| {
+                let step = remaining.clamp(0.0, g.range.maximum - g.range.minimum);
+                g.value = g.range.minimum + step;
+                remaining -= step;
+            };
+            match direction {
+                Direction::Rx => ch.gains.iter_mut().for_each(&mut fill),
+                Direction::Tx => ch.gains.iter_mut().rev().for_each(&mut fill),
+            }
+            Ok(())
+        })
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.channel_call("gain", direction, channel, &[], |ch| {
+            Ok(ch.gains.iter().map(|g| g.value).sum())
+        })
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        self.channel_call("gain_range", direction, channel, &[], |ch| {
+            Ok(Range::new(
+                ch.gains.iter().map(|g| g.range.minimum).sum(),
+                ch.gains.iter().map(|g| g.range.maximum).sum(),
+                0.0,
+            ))
+        })
+    }
+
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error> {
+        let name = string(name);
+        self.channel_call(
+            "set_gain_element",
+            direction,
+            channel,
+            &[&name, &gain],
+            |ch| {
+                let element = ch.gain_mut(&name)?;
+                element.value = gain.clamp(element.range.minimum, element.range.maximum);
+                Ok(())
+            },
+        )
+    }
+
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = string(name);
+        self.channel_call("gain_element", direction, channel, &[&name], |ch| {
+            Ok(ch.gain_mut(&name)?.value)
+        })
+    }
+
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error> {
+        let name = string(name);
+        self.channel_call("gain_element_range", direction, channel, &[&name], |ch| {
+            Ok(ch.gain_mut(&name)?.range)
+        })
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.channel_call("frequency_range", direction, channel, &[], |ch| {
+            Ok(ch.tunables[0].ranges.clone())
+        })
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.channel_call("frequency", direction, channel, &[], |ch| {
+            Ok(ch.tunables.iter().map(|t| t.value).sum())
+        })
+    }
+
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let args = args.into().to_string();
+        self.channel_call(
+            "set_frequency",
+            direction,
+            channel,
+            &[&frequency, &args],
+            |ch| {
+                // Tune the first element, leaving the offsets of the others in place.
+                let offset: f64 = ch.tunables[1..].iter().map(|t| t.value).sum();
+                ch.tunables[0].value = frequency - offset;
+                Ok(())
+            },
+        )
+    }
+
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.channel_call("list_frequencies", direction, channel, &[], |ch| {
+            Ok(ch.tunables.iter().map(|t| t.name.clone()).collect())
+        })
+    }
+
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error> {
+        let name = string(name);
+        self.channel_call(
+            "component_frequency_range",
+            direction,
+            channel,
+            &[&name],
+            |ch| Ok(ch.tunable_mut(&name)?.ranges.clone()),
+        )
+    }
+
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = string(name);
+        self.channel_call("component_frequency", direction, channel, &[&name], |ch| {
+            Ok(ch.tunable_mut(&name)?.value)
+        })
+    }
+
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let name = string(name);
+        let args = args.into().to_string();
+        self.channel_call(
+            "set_component_frequency",
+            direction,
+            channel,
+            &[&name, &frequency, &args],
+            |ch| {
+                ch.tunable_mut(&name)?.value = frequency;
+                Ok(())
+            },
+        )
+    }
+
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.channel_call("frequency_args_info", direction, channel, &[], |_| {
+            Ok(Vec::new())
+        })
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.channel_call("sample_rate", direction, channel, &[], |ch| {
+            Ok(ch.sample_rate)
+        })
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        self.channel_call("set_sample_rate", direction, channel, &[&rate], |ch| {
+            ch.sample_rate = rate;
+            Ok(())
+        })
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        self.channel_call("get_sample_rate_range", direction, channel, &[], |ch| {
+            Ok(ch.sample_rate_range.clone())
+        })
+    }
+
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.channel_call("list_sample_rates", direction, channel, &[], |_| {
+            Ok(Vec::new())
+        })
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.channel_call("bandwidth", direction, channel, &[], |ch| Ok(ch.bandwidth))
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error> {
+        self.channel_call("set_bandwidth", direction, channel, &[&bandwidth], |ch| {
+            ch.bandwidth = bandwidth;
+            Ok(())
+        })
+    }
+
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.channel_call("bandwidth_range", direction, channel, &[], |ch| {
+            Ok(ch.bandwidth_range.clone())
+        })
+    }
+
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.channel_call("list_bandwidths", direction, channel, &[], |_| {
+            Ok(Vec::new())
+        })
+    }
+
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
+        Ok(self.enter("list_clock_sources", &[])?.clock_sources.clone())
+    }
+
+    fn get_clock_source(&self) -> Result<String, Error> {
+        Ok(self.enter("get_clock_source", &[])?.clock_source.clone())
+    }
+
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error> {
+        let clock_source = string(clock_source);
+        let mut state = self.enter("set_clock_source", &[&clock_source])?;
+        if !state.clock_sources.contains(&clock_source) {
+            return Err(other(format!("Unknown clock source {clock_source}")));
+        }
+        state.clock_source = clock_source;
+        Ok(())
+    }
+
+    fn get_master_clock_rate(&self) -> Result<f64, Error> {
+        Ok(self.enter("get_master_clock_rate", &[])?.master_clock_rate)
+    }
+
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.enter("set_master_clock_rate", &[&rate])?
+            .master_clock_rate = rate;
+        Ok(())
+    }
+
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        drop(self.enter("get_master_clock_rates", &[])?);
+        Ok(Vec::new())
+    }
+
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.enter("set_reference_clock_rate", &[&rate])?
+            .reference_clock_rate = rate;
+        Ok(())
+    }
+
+    fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        Ok(self
+            .enter("get_reference_clock_rate", &[])?
+            .reference_clock_rate)
+    }
+
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        drop(self.enter("get_reference_clock_rates", &[])?);
+        Ok(Vec::new())
+    }
+
+    fn list_time_sources(&self) -> Result<Vec<String>, Error> {
+        Ok(self.enter("list_time_sources", &[])?.time_sources.clone())
+    }
+
+    fn get_time_source(&self) -> Result<String, Error> {
+        Ok(self.enter("get_time_source", &[])?.time_source.clone())
+    }
+
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error> {
+        let time_source = string(time_source);
+        let mut state = self.enter("set_time_source", &[&time_source])?;
+        if !state.time_sources.contains(&time_source) {
+            return Err(other(format!("Unknown time source {time_source}")));
+        }
+        state.time_source = time_source;
+        Ok(())
+    }
+
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error> {
+        let state = self.enter("has_hardware_time", &[&hw_time_source])?;
+        Ok(match hw_time_source {
+            None => true,
+            Some(source) => state.hardware_time.contains_key(source),
+        })
+    }
+
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
+        let state = self.enter("get_hardware_time", &[&hw_time_source])?;
+        let time = state.hardware_time.get(hw_time_source.unwrap_or(""));
+        Ok(time.copied().unwrap_or(HardwareTime::ZERO))
+    }
+
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error> {
+        let mut state = self.enter("set_hardware_time", &[&hw_time_source, &new_time])?;
+        state
+            .hardware_time
+            .insert(hw_time_source.unwrap_or("").into(), new_time);
+        Ok(())
+    }
+
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        drop(self.enter("set_command_time", &[&time, &what])?);
+        Ok(())
+    }
+
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        drop(self.enter("clear_command_time", &[&what])?);
+        Ok(())
+    }
+
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
+        let state = self.enter("list_register_interfaces", &[])?;
+        Ok(state.registers.keys().cloned().collect())
+    }
+
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error> {
+        let name = string(name);
+        let mut state = self.enter("write_register", &[&name, &address, &value])?;
+        state.registers_mut(&name)?.insert(address, value);
+        Ok(())
+    }
+
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error> {
+        let name = string(name);
+        let mut state = self.enter("read_register", &[&name, &address])?;
+        Ok(state
+            .registers_mut(&name)?
+            .get(&address)
+            .copied()
+            .unwrap_or(0))
+    }
+
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error> {
+        let name = string(name);
+        let mut state = self.enter("write_registers", &[&name, &address, &value])?;
+        let registers = state.registers_mut(&name)?;
+        for (offset, &v) in value.iter().enumerate() {
+            registers.insert(address + offset as u32, v);
+        }
+        Ok(())
+    }
+
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        let name = string(name);
+        let mut state = self.enter("read_registers", &[&name, &address, &length])?;
+        let registers = state.registers_mut(&name)?;
+        Ok((0..length as u32)
+            .map(|offset| registers.get(&(address + offset)).copied().unwrap_or(0))
+            .collect())
+    }
+
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error> {
+        let (key, value) = (string(key), string(value));
+        let mut state = self.enter("write_setting", &[&key, &value])?;
+        state.settings.insert(key, value);
+        Ok(())
+    }
+
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error> {
+        let key = string(key);
+        let state = self.enter("read_setting", &[&key])?;
+        Ok(state.settings.get(&key).cloned().unwrap_or_default())
+    }
+
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        drop(self.enter("setting_info", &[])?);
+        Ok(Vec::new())
+    }
+
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        drop(self.enter("setting_info_with_key", &[&key])?);
+        Ok(None)
+    }
+
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.channel_call("channel_setting_info", direction, channel, &[], |_| {
+            Ok(Vec::new())
+        })
+    }
+
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        self.channel_call(
+            "channel_setting_info_with_key",
+            direction,
+            channel,
+            &[&key],
+            |_| Ok(None),
+        )
+    }
+
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        let (key, value) = (string(key), string(value));
+        self.channel_call(
+            "write_channel_setting",
+            direction,
+            channel,
+            &[&key, &value],
+            |ch| {
+                ch.settings.insert(key.clone(), value.clone());
+                Ok(())
+            },
+        )
+    }
+
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        let key = string(key);
+        self.channel_call("read_channel_setting", direction, channel, &[&key], |ch| {
+            Ok(ch.settings.get(&key).cloned().unwrap_or_default())
+        })
+    }
+
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .enter("list_gpio_banks", &[])?
+            .gpio
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        let bank = string(bank);
+        let mut state = self.enter("write_gpio", &[&bank, &value])?;
+        state.gpio_mut(&bank)?.0 = value;
+        Ok(())
+    }
+
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = string(bank);
+        let mut state = self.enter("write_gpio_masked", &[&bank, &value, &mask])?;
+        let gpio = state.gpio_mut(&bank)?;
+        gpio.0 = (gpio.0 & !mask) | (value & mask);
+        Ok(())
+    }
+
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = string(bank);
+        let mut state = self.enter("read_gpio", &[&bank])?;
+        Ok(state.gpio_mut(&bank)?.0)
+    }
+
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        let bank = string(bank);
+        let mut state = self.enter("write_gpio_dir", &[&bank, &dir])?;
+        state.gpio_mut(&bank)?.1 = dir;
+        Ok(())
+    }
+
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = string(bank);
+        let mut state = self.enter("write_gpio_dir_masked", &[&bank, &dir, &mask])?;
+        let gpio = state.gpio_mut(&bank)?;
+        gpio.1 = (gpio.1 & !mask) | (dir & mask);
+        Ok(())
+    }
+
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = string(bank);
+        let mut state = self.enter("read_gpio_dir", &[&bank])?;
+        Ok(state.gpio_mut(&bank)?.1)
+    }
+
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        drop(self.enter("write_i2c", &[&addr, &data])?);
+        Ok(())
+    }
+
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        drop(self.enter("read_i2c", &[&addr, &num_bytes])?);
+        Ok(vec![0; num_bytes])
+    }
+
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        drop(self.enter("transact_spi", &[&addr, &data, &num_bits])?);
+        Ok(0)
+    }
+
+    fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        Ok(self
+            .enter("list_uarts", &[])?
+            .uarts
+            .keys()
+            .cloned()
+            .collect())
+    }
+
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        let which = string(which);
+        let mut state = self.enter("write_uart", &[&which, &data])?;
+        state.uart_mut(&which)?;
+        Ok(())
+    }
+
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        let which = string(which);
+        let mut state = self.enter("read_uart", &[&which, &timeout_us])?;
+        Ok(state.uart_mut(&which)?.drain(..).collect())
+    }
+}
+
+fn sensor_info(key: &str, value: &str) -> ArgInfo {
+    ArgInfo {
+        key: key.into(),
+        value: value.into(),
+        name: None,
+        description: None,
+        units: None,
+        data_type: ArgType::String,
+        options: Vec::new(),
+    }
+}
+
+/// A receive stream of a [`MockDevice`], returning the samples queued with
+/// [`MockDevice::queue_rx`].
+pub struct MockRxStream<E: StreamSample> {
+    device: MockDevice,
+    channels: Vec<usize>,
+    active: bool,
+    burst: Option<usize>,
+    last: RxMetadata,
+    phantom: PhantomData<fn(&mut [E])>,
+}
+
+impl<E: StreamSample> SdrRxStream<E> for MockRxStream<E> {
+    fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(self.device.enter("rx.mtu", &[])?.mtu)
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        drop(self.device.enter("rx.activate", &[&time])?);
+        self.active = true;
+        self.burst = None;
+        Ok(())
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        drop(
+            self.device
+                .enter("rx.activate_burst", &[&time, &num_samples])?,
+        );
+        self.active = true;
+        self.burst = Some(num_samples);
+        self.last.flags = StreamFlags::empty();
+        Ok(())
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        drop(self.device.enter("rx.deactivate", &[&time])?);
+        self.active = false;
+        self.burst = None;
+        Ok(())
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        assert!(buffers.len() == self.channels.len());
+
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        let mut state = self.device.enter("rx.read", &[&num_samples, &timeout_us])?;
+        if !self.active {
+            return Err(Error {
+                code: ErrorCode::StreamError,
+                message: "Stream is not active".into(),
+            });
+        }
+        if self.burst == Some(0) {
+            return Err(timeout());
+        }
+
+        let (format, data, flags, time) = match state.rx.pop_front().ok_or_else(timeout)? {
+            RxEntry::Samples {
+                format,
+                buffers,
+                flags,
+                time,
+            } => (format, buffers, flags, time),
+            RxEntry::Error(code) => {
+                return Err(Error {
+                    code,
+                    message: "Queued read error".into(),
+                });
+            }
+        };
+        if format != E::STREAM_FORMAT {
+            return Err(other(format!(
+                "Queued {format} samples read from a {} stream",
+                E::STREAM_FORMAT
+            )));
+        }
+        if data.len() != buffers.len() {
+            return Err(other(format!(
+                "Queued {} channels read from a {} channel stream",
+                data.len(),
+                buffers.len()
+            )));
+        }
+
+        let size = mem::size_of::<E>();
+        let available = data.first().map_or(0, |d| d.len() / size);
+        let mut len = num_samples.min(available);
+        let mut end_burst = false;
+        if let Some(remaining) = &mut self.burst {
+            len = len.min(*remaining);
+            *remaining -= len;
+            end_burst = *remaining == 0;
+        }
+        for (dst, src) in buffers.iter_mut().zip(&data) {
+            sample_bytes_mut(&mut dst[..len]).copy_from_slice(&src[..len * size]);
+        }
+
+        let mut flags = flags;
+        if len < available {
+            // Leave the rest of the entry for the next read.
+            let rate = state.channels[index(Direction::Rx)][self.channels[0]].sample_rate;
+            let offset = HardwareTime::from_secs_f64(len as f64 / rate).as_ns();
+            state.rx.push_front(RxEntry::Samples {
+                format,
+                buffers: data.iter().map(|d| d[len * size..].to_vec()).collect(),
+                flags,
+                time: time.map(|t| HardwareTime::from_ns(t.as_ns() + offset)),
+            });
+            flags = StreamFlags::from_bits(flags.bits() & !StreamFlags::END_BURST.bits());
+        }
+        if end_burst {
+            flags |= StreamFlags::END_BURST;
+        }
+
+        self.last = RxMetadata {
+            len,
+            flags,
+            raw_time: time.unwrap_or(HardwareTime::ZERO),
+        };
+        Ok(self.last)
+    }
+
+    fn time(&self) -> HardwareTime {
+        self.last.raw_time
+    }
+
+    fn burst_ended(&self) -> bool {
+        self.last.flags.contains(StreamFlags::END_BURST)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        let mut state = self.device.enter("rx.read_status_event", &[&timeout_us])?;
+        let event = state.status[index(Direction::Rx)].pop_front();
+        Ok(event.unwrap_or(StatusEvent::Timeout))
+    }
+}
+
+/// A transmit stream of a [`MockDevice`], recording the samples written for
+/// [`MockDevice::take_tx`].
+pub struct MockTxStream<E: StreamSample> {
+    device: MockDevice,
+    channels: Vec<usize>,
+    active: bool,
+    phantom: PhantomData<fn(&[E])>,
+}
+
+impl<E: StreamSample> SdrTxStream<E> for MockTxStream<E> {
+    fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        Ok(self.device.enter("tx.mtu", &[])?.mtu)
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        drop(self.device.enter("tx.activate", &[&time])?);
+        self.active = true;
+        Ok(())
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        drop(self.device.enter("tx.deactivate", &[&time])?);
+        self.active = false;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        assert!(
+            buffers.len() == self.channels.len(),
+            "Number of buffers must equal number of channels on stream"
+        );
+        let num_elems = buffers.first().map_or(0, |x| x.len());
+        for buf in buffers {
+            assert_eq!(buf.len(), num_elems, "All buffers must be the same length");
+        }
+
+        let mut state = self
+            .device
+            .enter("tx.write", &[&num_elems, &at, &end_burst, &timeout_us])?;
+        if !self.active {
+            return Err(Error {
+                code: ErrorCode::StreamError,
+                message: "Stream is not active".into(),
+            });
+        }
+
+        let len = num_elems.min(state.mtu);
+        state.tx.push(TxEntry {
+            format: E::STREAM_FORMAT,
+            buffers: buffers
+                .iter()
+                .map(|b| sample_bytes(&b[..len]).to_vec())
+                .collect(),
+            at,
+            end_burst: end_burst && len == num_elems,
+        });
+        Ok(len)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        let mut state = self.device.enter("tx.read_status_event", &[&timeout_us])?;
+        let event = state.status[index(Direction::Tx)].pop_front();
+        Ok(event.unwrap_or(StatusEvent::Timeout))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn control_state() {
+        let dev = MockDevice::new()
+            .with_antennas(Direction::Rx, &["RX1", "RX2"])
+            .with_gain_elements(
+                Direction::Rx,
+                &[
+                    ("LNA", Range::new(0.0, 30.0, 1.0)),
+                    ("VGA", Range::new(0.0, 20.0, 1.0)),
+                ],
+            );
+
+        dev.set_antenna(Direction::Rx, 0, "RX2").unwrap();
+        assert_eq!(dev.antenna(Direction::Rx, 0).unwrap(), "RX2");
+        assert!(dev.set_antenna(Direction::Rx, 0, "TX").is_err());
+
+        dev.set_gain(Direction::Rx, 0, 40.0).unwrap();
+        assert_eq!(dev.gain_element(Direction::Rx, 0, "LNA").unwrap(), 30.0);
+        assert_eq!(dev.gain_element(Direction::Rx, 0, "VGA").unwrap(), 10.0);
+        assert_eq!(dev.gain(Direction::Rx, 0).unwrap(), 40.0);
+
+        dev.set_sample_rate(Direction::Rx, 0, 2e6).unwrap();
+        assert_eq!(dev.sample_rate(Direction::Rx, 0).unwrap(), 2e6);
+        assert!(dev.sample_rate(Direction::Rx, 1).is_err());
+
+        let err = dev.set_gain_mode(Direction::Rx, 0, true).unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+
+        assert_eq!(
+            dev.calls()[..2],
+            [r#"set_antenna(Rx, 0, "RX2")"#, "antenna(Rx, 0)"]
+        );
+    }
+
+    #[test]
+    fn injected_errors() {
+        let dev = MockDevice::new();
+        dev.inject_error("sample_rate", 2, ErrorCode::Timeout);
+
+        assert!(dev.sample_rate(Direction::Rx, 0).is_ok());
+        let err = dev.sample_rate(Direction::Rx, 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Timeout);
+        assert!(dev.sample_rate(Direction::Rx, 0).is_ok());
+    }
+
+    #[test]
+    fn rx_script() {
+        let dev = MockDevice::new();
+        dev.set_sample_rate(Direction::Rx, 0, 1e3).unwrap();
+        let samples: Vec<i16> = (0..10).collect();
+        dev.queue_rx_with_meta(
+            &[&samples],
+            StreamFlags::END_BURST,
+            Some(HardwareTime::from_ns(1_000_000_000)),
+        );
+        dev.queue_rx_error(ErrorCode::Overflow);
+
+        let mut stream = dev.rx_stream::<i16>(&[0]).unwrap();
+        let mut buf = [0i16; 6];
+        let err = stream.read(&mut [&mut buf], 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::StreamError);
+
+        stream.activate(None).unwrap();
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        assert_eq!(meta.len, 6);
+        assert_eq!(buf, [0, 1, 2, 3, 4, 5]);
+        assert_eq!(meta.time(), Some(HardwareTime::from_ns(1_000_000_000)));
+        assert!(!meta.end_burst());
+
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        assert_eq!(meta.len, 4);
+        assert_eq!(buf[..4], [6, 7, 8, 9]);
+        assert_eq!(meta.time(), Some(HardwareTime::from_ns(1_006_000_000)));
+        assert!(meta.end_burst());
+
+        let err = stream.read(&mut [&mut buf], 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Overflow);
+        let err = stream.read(&mut [&mut buf], 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Timeout);
+
+        let mut wrong = dev.rx_stream::<f32>(&[0]).unwrap();
+        dev.queue_rx(&[&samples]);
+        wrong.activate(None).unwrap();
+        assert!(wrong.read(&mut [&mut [0.0; 4]], 0).is_err());
+    }
+
+    #[test]
+    fn rx_burst() {
+        let dev = MockDevice::new();
+        let samples = [1u8; 8];
+        dev.queue_rx(&[&samples]);
+
+        let mut stream = dev.rx_stream::<u8>(&[0]).unwrap();
+        stream.activate_burst(None, 5).unwrap();
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut [&mut buf], 0).unwrap(), 4);
+        assert!(!stream.burst_ended());
+        assert_eq!(stream.read(&mut [&mut buf], 0).unwrap(), 1);
+        assert!(stream.burst_ended());
+        let err = stream.read(&mut [&mut buf], 0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Timeout);
+    }
+
+    #[test]
+    fn tx_capture() {
+        let dev = MockDevice::new()
+            .with_channels(Direction::Tx, 2)
+            .with_mtu(3);
+        let mut stream = dev.tx_stream::<u16>(&[0, 1]).unwrap();
+        stream.activate(None).unwrap();
+
+        let a = [1u16, 2, 3, 4, 5];
+        let b = [6u16, 7, 8, 9, 10];
+        let at = Some(HardwareTime::from_ns(500));
+        stream.write_all(&[&a, &b], at, true, 0).unwrap();
+
+        let writes = dev.take_tx::<u16>();
+        assert_eq!(
+            writes,
+            [
+                TxWrite {
+                    buffers: vec![vec![1, 2, 3], vec![6, 7, 8]],
+                    at,
+                    end_burst: false,
+                },
+                TxWrite {
+                    buffers: vec![vec![4, 5], vec![9, 10]],
+                    at: None,
+                    end_burst: true,
+                },
+            ]
+        );
+        assert!(dev.take_tx::<u16>().is_empty());
+        assert!(dev.tx_stream::<u16>(&[2]).is_err());
+    }
+}
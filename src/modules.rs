@@ -0,0 +1,228 @@
+//! Discovery and loading of SoapySDR driver modules.
+//!
+//! SoapySDR loads the modules installed in its [search paths](search_paths) automatically the
+//! first time devices are enumerated or opened. These functions allow listing those modules,
+//! loading modules from other locations, and inspecting why a module's drivers failed to
+//! register.
+//!
+//! # Example
+//! ```no_run
+//! for module in soapysdr::modules::list_info() {
+//!     println!("{} {}", module.path.display(), module.version.as_deref().unwrap_or("?"));
+//!     for (driver, err) in &module.loader_result {
+//!         if !err.is_empty() {
+//!             println!("  {driver} failed to load: {err}");
+//!         }
+//!     }
+//! }
+//! ```
+
+use soapysdr_sys::*;
+#[cfg(unix)]
+use std::ffi::OsStr;
+use std::ffi::{CStr, CString};
+use std::os::raw::{c_char, c_void};
+#[cfg(unix)]
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::slice;
+
+use crate::{Args, Error, ErrorCode};
+
+fn invalid_path(path: &Path, problem: &str) -> Error {
+    Error {
+        code: ErrorCode::Other,
+        message: format!("Module path {} {}", path.display(), problem),
+    }
+}
+
+/// Convert a path to the string passed to SoapySDR.
+fn path_cstring(path: &Path) -> Result<CString, Error> {
+    #[cfg(unix)]
+    let bytes = path.as_os_str().as_bytes().to_vec();
+    #[cfg(not(unix))]
+    let bytes = path
+        .to_str()
+        .ok_or_else(|| invalid_path(path, "is not valid UTF-8"))?
+        .as_bytes()
+        .to_vec();
+    CString::new(bytes).map_err(|_| invalid_path(path, "contains a null byte"))
+}
+
+/// Convert a path returned by SoapySDR.
+fn c_path(s: &CStr) -> PathBuf {
+    #[cfg(unix)]
+    let path = OsStr::from_bytes(s.to_bytes()).into();
+    #[cfg(not(unix))]
+    let path = s.to_string_lossy().into_owned().into();
+    path
+}
+
+/// Take ownership of a string returned by SoapySDR, freeing it.
+unsafe fn owned_string(ptr: *mut c_char) -> String {
+    if ptr.is_null() {
+        return String::new();
+    }
+    unsafe {
+        let ret = CStr::from_ptr(ptr).to_string_lossy().into();
+        SoapySDR_free(ptr as *mut c_void);
+        ret
+    }
+}
+
+/// Take ownership of a list of paths returned by SoapySDR, freeing it.
+unsafe fn owned_path_list<F: FnOnce(*mut usize) -> *mut *mut c_char>(f: F) -> Vec<PathBuf> {
+    unsafe {
+        let mut len: usize = 0;
+        let mut ptr = f(&mut len as *mut _);
+        if ptr.is_null() {
+            return Vec::new();
+        }
+        let ret = slice::from_raw_parts(ptr, len)
+            .iter()
+            .map(|&p| c_path(CStr::from_ptr(p)))
+            .collect();
+        SoapySDRStrings_clear(&mut ptr as *mut _, len);
+        ret
+    }
+}
+
+/// Convert an error message returned by SoapySDR, empty on success, into a `Result`.
+fn message_result(message: String) -> Result<(), Error> {
+    if message.is_empty() {
+        Ok(())
+    } else {
+        Err(Error {
+            code: ErrorCode::Other,
+            message,
+        })
+    }
+}
+
+/// Get the root installation path of the SoapySDR library.
+pub fn root_path() -> PathBuf {
+    unsafe { c_path(CStr::from_ptr(SoapySDR_getRootPath())) }
+}
+
+/// Get the list of directories searched by [`load_all`].
+pub fn search_paths() -> Vec<PathBuf> {
+    unsafe { owned_path_list(|len_ptr| SoapySDR_listSearchPaths(len_ptr)) }
+}
+
+/// List the paths of all modules found in the [search paths](search_paths).
+pub fn list() -> Vec<PathBuf> {
+    unsafe { owned_path_list(|len_ptr| SoapySDR_listModules(len_ptr)) }
+}
+
+/// List the paths of all modules found in a directory.
+///
+/// Returns an empty list if the path can't be passed to SoapySDR, e.g. because it contains a
+/// null byte.
+pub fn list_in<P: AsRef<Path>>(dir: P) -> Vec<PathBuf> {
+    let Ok(dir) = path_cstring(dir.as_ref()) else {
+        return Vec::new();
+    };
+    unsafe { owned_path_list(|len_ptr| SoapySDR_listModulesPath(dir.as_ptr(), len_ptr)) }
+}
+
+/// Load a single module from its file path.
+pub fn load<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    let path = path_cstring(path.as_ref())?;
+    message_result(unsafe { owned_string(SoapySDR_loadModule(path.as_ptr())) })
+}
+
+/// Unload a module that was loaded with [`load`].
+pub fn unload<P: AsRef<Path>>(path: P) -> Result<(), Error> {
+    let path = path_cstring(path.as_ref())?;
+    message_result(unsafe { owned_string(SoapySDR_unloadModule(path.as_ptr())) })
+}
+
+/// Get the registration results of a loaded module.
+///
+/// The keys are the names of the drivers provided by the module, and each value is an error
+/// message, or empty if the driver registered successfully. Empty if the module is not loaded.
+pub fn loader_result<P: AsRef<Path>>(path: P) -> Args {
+    let Ok(path) = path_cstring(path.as_ref()) else {
+        return Args::new();
+    };
+    unsafe { Args::from_raw(SoapySDR_getLoaderResult(path.as_ptr())) }
+}
+
+/// Get the version string of a loaded module, if it provides one.
+pub fn version<P: AsRef<Path>>(path: P) -> Option<String> {
+    let path = path_cstring(path.as_ref()).ok()?;
+    let version = unsafe { owned_string(SoapySDR_getModuleVersion(path.as_ptr())) };
+    if version.is_empty() {
+        None
+    } else {
+        Some(version)
+    }
+}
+
+/// Load the modules installed in the [search paths](search_paths).
+///
+/// This only performs the load once; subsequent calls do nothing.
+pub fn load_all() {
+    unsafe { SoapySDR_loadModules() }
+}
+
+/// Unload all currently loaded modules.
+pub fn unload_all() {
+    unsafe { SoapySDR_unloadModules() }
+}
+
+/// Information about an installed module, returned by [`list_info`].
+pub struct ModuleInfo {
+    /// File path of the module.
+    pub path: PathBuf,
+
+    /// Version string of the module, if it provides one.
+    pub version: Option<String>,
+
+    /// Registration results of the module, as returned by [`loader_result`].
+    pub loader_result: Args,
+}
+
+impl ModuleInfo {
+    /// Returns true if every driver provided by the module registered successfully.
+    pub fn is_ok(&self) -> bool {
+        self.loader_result.iter().all(|(_, err)| err.is_empty())
+    }
+}
+
+/// Load the installed modules, and list them with their versions and registration results.
+pub fn list_info() -> Vec<ModuleInfo> {
+    load_all();
+    list()
+        .into_iter()
+        .map(|path| ModuleInfo {
+            version: version(&path),
+            loader_result: loader_result(&path),
+            path,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_with_null_byte() {
+        let err = path_cstring(Path::new("/usr/lib/lib\0.so")).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Other);
+        assert!(err.message.contains("null byte"), "{}", err);
+        assert!(load("/usr/lib/lib\0.so").is_err());
+        assert!(list_in("/usr/lib\0").is_empty());
+        assert_eq!(version("/usr/lib/lib\0.so"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path() {
+        let path = Path::new(OsStr::from_bytes(b"/opt/\xff/libmod.so"));
+        let c = path_cstring(path).unwrap();
+        assert_eq!(c.as_bytes(), b"/opt/\xff/libmod.so");
+        assert_eq!(c_path(&c), path);
+    }
+}
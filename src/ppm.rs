@@ -0,0 +1,136 @@
+use crate::{Args, Device, Direction, Error};
+
+/// Tuning of a channel with a reference clock error correction in parts per million (PPM).
+///
+/// To obtain a PpmTuner, call [`Device::ppm_tuner`]. If the driver supports
+/// [frequency correction](Device::has_frequency_correction), the correction is applied by the
+/// driver. Otherwise, it is applied in software by adjusting the frequency passed to
+/// [`Device::set_frequency`] and the value read back from [`Device::frequency`], so the
+/// frequencies seen through the PpmTuner are corrected either way.
+///
+/// A positive `ppm` means the device's reference clock runs fast, so an uncorrected device
+/// tunes above the requested frequency.
+///
+/// # Example
+/// ```no_run
+/// # let dev = soapysdr::Device::new("driver=rtlsdr").unwrap();
+/// # let ppm_for_serial = |_: Option<String>| 0.0;
+/// let serial = dev.hardware_info().unwrap().get("serial").map(|s| s.to_owned());
+/// let tuner = dev.ppm_tuner(soapysdr::Direction::Rx, 0, ppm_for_serial(serial)).unwrap();
+/// tuner.set_frequency(100e6, "").unwrap();
+/// ```
+#[derive(Clone)]
+pub struct PpmTuner {
+    device: Device,
+    direction: Direction,
+    channel: usize,
+    ppm: f64,
+    software: bool,
+}
+
+impl Device {
+    /// Apply a reference clock correction of `ppm` to a channel, in the driver if supported or
+    /// in software otherwise.
+    pub fn ppm_tuner(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<PpmTuner, Error> {
+        let software = !self.has_frequency_correction(direction, channel)?;
+        if !software {
+            self.set_frequency_correction(direction, channel, ppm)?;
+        }
+        Ok(PpmTuner {
+            device: self.clone(),
+            direction,
+            channel,
+            ppm,
+            software,
+        })
+    }
+}
+
+impl PpmTuner {
+    /// Get the correction in parts per million.
+    pub fn ppm(&self) -> f64 {
+        self.ppm
+    }
+
+    /// Returns true if the correction is applied in software because the driver doesn't
+    /// support frequency correction.
+    pub fn is_software(&self) -> bool {
+        self.software
+    }
+
+    /// Change the correction.
+    ///
+    /// With a software correction, the channel is retuned to keep the same corrected frequency.
+    pub fn set_ppm(&mut self, ppm: f64) -> Result<(), Error> {
+        if self.software {
+            let frequency = self.frequency()?;
+            self.ppm = ppm;
+            self.set_frequency(frequency, Args::new())
+        } else {
+            self.device
+                .set_frequency_correction(self.direction, self.channel, ppm)?;
+            self.ppm = ppm;
+            Ok(())
+        }
+    }
+
+    /// Set the corrected center frequency of the channel in Hz.
+    ///
+    /// See [`Device::set_frequency`] for the meaning of `args`.
+    pub fn set_frequency<A: Into<Args>>(&self, frequency: f64, args: A) -> Result<(), Error> {
+        let frequency = if self.software {
+            to_device(frequency, self.ppm)
+        } else {
+            frequency
+        };
+        self.device
+            .set_frequency(self.direction, self.channel, frequency, args)
+    }
+
+    /// Get the corrected center frequency of the channel in Hz.
+    pub fn frequency(&self) -> Result<f64, Error> {
+        let frequency = self.device.frequency(self.direction, self.channel)?;
+        Ok(if self.software {
+            from_device(frequency, self.ppm)
+        } else {
+            frequency
+        })
+    }
+}
+
+/// The frequency to request from an uncorrected device to tune to `frequency`.
+fn to_device(frequency: f64, ppm: f64) -> f64 {
+    frequency / (1.0 + ppm * 1e-6)
+}
+
+/// The actual frequency when an uncorrected device reports `frequency`.
+fn from_device(frequency: f64, ppm: f64) -> f64 {
+    frequency * (1.0 + ppm * 1e-6)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn software_correction() {
+        // A fast clock tunes high, so a lower frequency must be requested.
+        assert!((to_device(100e6, 50.0) - 99_995_000.25).abs() < 1e-3);
+        assert_eq!(to_device(100e6, 0.0), 100e6);
+
+        for &(frequency, ppm) in &[(100e6, 50.0), (1.09e9, -12.5), (24e6, 0.3)] {
+            let roundtrip = from_device(to_device(frequency, ppm), ppm);
+            assert!(
+                (roundtrip - frequency).abs() < 1e-6,
+                "{} {}",
+                frequency,
+                ppm
+            );
+        }
+    }
+}
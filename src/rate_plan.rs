@@ -0,0 +1,230 @@
+use crate::{Device, Direction, Error, ErrorCode, Range};
+
+/// Largest interpolation or decimation factor considered by the resampling ratio.
+const MAX_FACTOR: u64 = 1_000_000;
+
+/// A plan for producing an application sample rate from a device, returned by
+/// [`Device::plan_sample_rate`].
+///
+/// Samples at `device_rate` are resampled to the application rate by interpolating by
+/// `interpolation` and then decimating by `decimation`. For transmit, the ratio applies in
+/// reverse.
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub struct RatePlan {
+    /// The sample rate the application asked for.
+    pub requested_rate: f64,
+
+    /// The achievable device sample rate closest to `requested_rate`.
+    pub device_rate: f64,
+
+    /// The narrowest supported analog bandwidth that passes the requested signal, or `None` if
+    /// the driver doesn't report bandwidths.
+    pub bandwidth: Option<f64>,
+
+    /// Interpolation factor of the resampler.
+    pub interpolation: u64,
+
+    /// Decimation factor of the resampler.
+    pub decimation: u64,
+}
+
+impl RatePlan {
+    /// Returns true if the device rate is the requested rate, so no resampling is needed.
+    pub fn is_exact(&self) -> bool {
+        self.interpolation == self.decimation
+    }
+
+    /// The application sample rate produced by resampling the device rate.
+    ///
+    /// This differs from `requested_rate` only if the ratio could not be represented exactly
+    /// within the factor limit.
+    pub fn output_rate(&self) -> f64 {
+        self.device_rate * self.interpolation as f64 / self.decimation as f64
+    }
+}
+
+impl Device {
+    /// Plan how to produce `rate` samples per second on a channel.
+    ///
+    /// The device rate is the closest rate within
+    /// [`get_sample_rate_range`](Device::get_sample_rate_range), and the bandwidth is chosen
+    /// from [`bandwidth_range`](Device::bandwidth_range). Neither is applied to the device.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use soapysdr::Direction::Rx;
+    /// # let dev = soapysdr::Device::new("driver=rtlsdr").unwrap();
+    /// let plan = dev.plan_sample_rate(Rx, 0, 48_000.0).unwrap();
+    /// dev.set_sample_rate(Rx, 0, plan.device_rate).unwrap();
+    /// if let Some(bw) = plan.bandwidth {
+    ///     dev.set_bandwidth(Rx, 0, bw).unwrap();
+    /// }
+    /// println!("resample by {}/{}", plan.interpolation, plan.decimation);
+    /// ```
+    pub fn plan_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<RatePlan, Error> {
+        if rate.is_nan() || rate <= 0.0 {
+            return Err(Error {
+                code: ErrorCode::Other,
+                message: format!("Invalid sample rate {}", rate),
+            });
+        }
+
+        let rate_ranges = self.get_sample_rate_range(direction, channel)?;
+        let device_rate = nearest(&rate_ranges, rate).ok_or_else(|| Error {
+            code: ErrorCode::NotSupported,
+            message: "Device reported no sample rates".into(),
+        })?;
+
+        let bandwidth_ranges = self.bandwidth_range(direction, channel)?;
+        let bandwidth = narrowest_at_least(&bandwidth_ranges, rate.min(device_rate));
+
+        let (interpolation, decimation) = ratio(rate / device_rate);
+        Ok(RatePlan {
+            requested_rate: rate,
+            device_rate,
+            bandwidth,
+            interpolation,
+            decimation,
+        })
+    }
+}
+
+/// The number of steps from `range.minimum` to `range.maximum`, if the range is stepped.
+fn steps(range: &Range) -> Option<f64> {
+    if range.step > 0.0 && range.maximum > range.minimum {
+        Some(((range.maximum - range.minimum) / range.step + 1e-9).floor())
+    } else {
+        None
+    }
+}
+
+/// The value within `ranges` closest to `x`, preferring the higher value on a tie.
+fn nearest(ranges: &[Range], x: f64) -> Option<f64> {
+    ranges
+        .iter()
+        .map(|range| match steps(range) {
+            Some(n) => {
+                let k = ((x - range.minimum) / range.step).round().clamp(0.0, n);
+                range.minimum + k * range.step
+            }
+            None => x.clamp(range.minimum, range.minimum.max(range.maximum)),
+        })
+        .min_by(|a, b| (a - x).abs().total_cmp(&(b - x).abs()).then(b.total_cmp(a)))
+}
+
+/// The smallest value within `ranges` that is at least `x`, or the largest value if none is.
+fn narrowest_at_least(ranges: &[Range], x: f64) -> Option<f64> {
+    let above = ranges
+        .iter()
+        .filter_map(|range| {
+            let value = match steps(range) {
+                Some(_) if x > range.minimum => {
+                    range.minimum + ((x - range.minimum) / range.step - 1e-9).ceil() * range.step
+                }
+                _ => x.max(range.minimum),
+            };
+            (value <= range.maximum.max(range.minimum) * (1.0 + 1e-12)).then_some(value)
+        })
+        .min_by(|a, b| a.total_cmp(b));
+
+    above.or_else(|| {
+        ranges
+            .iter()
+            .map(|range| range.maximum.max(range.minimum))
+            .max_by(|a, b| a.total_cmp(b))
+    })
+}
+
+/// The best rational approximation `p / q` of `x`, with both terms at most [`MAX_FACTOR`].
+fn ratio(x: f64) -> (u64, u64) {
+    // Convergents of the continued fraction expansion of `x`.
+    let (mut p0, mut q0, mut p1, mut q1) = (0u64, 1u64, 1u64, 0u64);
+    let mut rest = x;
+    loop {
+        let a = rest.floor();
+        if a > MAX_FACTOR as f64 {
+            break;
+        }
+        let a = a as u64;
+        let (p2, q2) = (a * p1 + p0, a * q1 + q0);
+        if p2 > MAX_FACTOR || q2 > MAX_FACTOR {
+            break;
+        }
+        (p0, q0, p1, q1) = (p1, q1, p2, q2);
+
+        let frac = rest - a as f64;
+        if frac.abs() < 1e-9 || ((p1 as f64 / q1 as f64) / x - 1.0).abs() < 1e-12 {
+            break;
+        }
+        rest = 1.0 / frac;
+    }
+
+    if q1 == 0 {
+        // `x` is too large to represent; use the largest interpolation.
+        (MAX_FACTOR, 1)
+    } else if p1 == 0 {
+        (1, MAX_FACTOR)
+    } else {
+        (p1, q1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rate() {
+        let rtlsdr = [
+            Range::new(225_001.0, 300e3, 0.0),
+            Range::new(900_001.0, 3.2e6, 0.0),
+        ];
+        assert_eq!(nearest(&rtlsdr, 48e3), Some(225_001.0));
+        assert_eq!(nearest(&rtlsdr, 2.4e6), Some(2.4e6));
+        assert_eq!(nearest(&rtlsdr, 500e3), Some(300e3));
+        assert_eq!(nearest(&rtlsdr, 10e6), Some(3.2e6));
+
+        let stepped = [Range::new(1e6, 10e6, 1e6)];
+        assert_eq!(nearest(&stepped, 2.4e6), Some(2e6));
+        assert_eq!(nearest(&stepped, 2.5e6), Some(3e6));
+        assert_eq!(nearest(&stepped, 20e6), Some(10e6));
+
+        let discrete = [Range::new(2e6, 2e6, 0.0), Range::new(8e6, 8e6, 0.0)];
+        assert_eq!(nearest(&discrete, 5e6), Some(8e6));
+        assert_eq!(nearest(&[], 5e6), None);
+    }
+
+    #[test]
+    fn bandwidth() {
+        let lms = [Range::new(1.4e6, 130e6, 0.0)];
+        assert_eq!(narrowest_at_least(&lms, 2.4e6), Some(2.4e6));
+        assert_eq!(narrowest_at_least(&lms, 48e3), Some(1.4e6));
+
+        let filters = [
+            Range::new(1.5e6, 1.5e6, 0.0),
+            Range::new(5e6, 5e6, 0.0),
+            Range::new(8e6, 28e6, 2e6),
+        ];
+        assert_eq!(narrowest_at_least(&filters, 1e6), Some(1.5e6));
+        assert_eq!(narrowest_at_least(&filters, 2e6), Some(5e6));
+        assert_eq!(narrowest_at_least(&filters, 9e6), Some(10e6));
+        assert_eq!(narrowest_at_least(&filters, 40e6), Some(28e6));
+        assert_eq!(narrowest_at_least(&[], 1e6), None);
+    }
+
+    #[test]
+    fn resampling_ratio() {
+        assert_eq!(ratio(1.0), (1, 1));
+        assert_eq!(ratio(48e3 / 2.4e6), (1, 50));
+        assert_eq!(ratio(44.1e3 / 2.048e6), (441, 20480));
+        assert_eq!(ratio(3.0), (3, 1));
+        let (p, q) = ratio(std::f64::consts::PI);
+        assert!(p <= MAX_FACTOR && q <= MAX_FACTOR);
+        assert!((p as f64 / q as f64 - std::f64::consts::PI).abs() < 1e-10);
+    }
+}
@@ -0,0 +1,418 @@
+use std::sync::atomic::{AtomicBool, AtomicI64, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::ring::{Consumer, Producer, ring};
+use crate::{Error, ErrorCode, HardwareTime, RxMetadata, RxStream, StreamFlags, StreamSample};
+
+/// Timeout for each blocking read on the reader thread, which bounds how long it takes to
+/// notice that the [`RxReader`] was dropped.
+const READ_TIMEOUT_US: i64 = 100_000;
+
+/// Chunk length used if the driver doesn't report an MTU.
+const DEFAULT_CHUNK_LEN: usize = 4096;
+
+/// A slot of the ring buffer, holding `chunk_len` samples for each channel.
+struct Chunk<E> {
+    samples: Vec<E>,
+    meta: RxMetadata,
+}
+
+/// State shared between the reader thread and the [`RxReader`], other than the ring itself.
+struct Shared {
+    stop: AtomicBool,
+    dropped: AtomicU64,
+    overflows: AtomicU64,
+    last_overflow_ns: AtomicI64,
+    error: Mutex<Option<Error>>,
+}
+
+/// Sentinel for `last_overflow_ns` when no timestamp is known.
+const NO_TIME: i64 = i64::MIN;
+
+/// An [`RxStream`] read continuously on a dedicated thread into a lock-free ring buffer.
+///
+/// To obtain an RxReader, call [`RxStream::spawn_reader`]. The thread reads chunks of
+/// [`mtu`](RxStream::mtu) samples and publishes them to the ring without blocking. If the
+/// consumer falls behind and the ring is full, the thread keeps reading to avoid an overflow on
+/// the device, and discards the chunk; these are counted by
+/// [`dropped_chunks`](RxReader::dropped_chunks).
+///
+/// Dropping the RxReader stops the thread and closes the underlying stream. This blocks for up
+/// to 100ms while the thread finishes its current read.
+pub struct RxReader<E> {
+    shared: Arc<Shared>,
+    ring: Consumer<Chunk<E>>,
+    nchannels: usize,
+    chunk_len: usize,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<E: StreamSample + Copy + Default + Send + 'static> RxStream<E> {
+    /// Move the stream to a dedicated thread that reads into a ring buffer of `capacity` chunks.
+    ///
+    /// The stream should be [activated](RxStream::activate) first.
+    ///
+    /// # Panics
+    ///  * If `capacity` is 0.
+    pub fn spawn_reader(self, capacity: usize) -> Result<RxReader<E>, Error> {
+        assert!(capacity > 0, "Capacity must be nonzero");
+
+        let chunk_len = match self.mtu()? {
+            0 => DEFAULT_CHUNK_LEN,
+            mtu => mtu,
+        };
+        let nchannels = self.num_channels();
+
+        let mut stream = self;
+        Ok(spawn(
+            move |buffers, timeout_us| stream.read_with_meta(buffers, timeout_us),
+            nchannels,
+            chunk_len,
+            capacity,
+        ))
+    }
+}
+
+/// Start a reader thread that fills a ring of `capacity` chunks by calling `read`, which is
+/// dropped on the thread when it exits.
+fn spawn<E, F>(read: F, nchannels: usize, chunk_len: usize, capacity: usize) -> RxReader<E>
+where
+    E: Copy + Default + Send + 'static,
+    F: FnMut(&mut [&mut [E]], i64) -> Result<RxMetadata, Error> + Send + 'static,
+{
+    let slots = (0..capacity)
+        .map(|_| Chunk {
+            samples: vec![E::default(); chunk_len * nchannels],
+            meta: RxMetadata {
+                len: 0,
+                flags: StreamFlags::empty(),
+                raw_time: HardwareTime::ZERO,
+            },
+        })
+        .collect();
+    let (producer, consumer) = ring(slots);
+
+    let shared = Arc::new(Shared {
+        stop: AtomicBool::new(false),
+        dropped: AtomicU64::new(0),
+        overflows: AtomicU64::new(0),
+        last_overflow_ns: AtomicI64::new(NO_TIME),
+        error: Mutex::new(None),
+    });
+
+    let thread_shared = shared.clone();
+    let thread =
+        thread::spawn(move || reader_thread(read, producer, nchannels, chunk_len, &thread_shared));
+
+    RxReader {
+        shared,
+        ring: consumer,
+        nchannels,
+        chunk_len,
+        thread: Some(thread),
+    }
+}
+
+/// Read into the ring until stopped or an error occurs. Returning drops the producer, which
+/// closes the ring.
+fn reader_thread<E: Copy + Default>(
+    mut read: impl FnMut(&mut [&mut [E]], i64) -> Result<RxMetadata, Error>,
+    mut ring: Producer<Chunk<E>>,
+    nchannels: usize,
+    chunk_len: usize,
+    shared: &Shared,
+) {
+    let mut scratch = vec![E::default(); chunk_len * nchannels];
+    let mut overflowed = false;
+
+    while !shared.stop.load(Ordering::Relaxed) {
+        let slot = ring.slot();
+        let in_ring = slot.is_some();
+        let samples = match slot {
+            Some(chunk) => &mut chunk.samples,
+            None => &mut scratch,
+        };
+        let mut buffers: Vec<&mut [E]> = samples.chunks_mut(chunk_len).collect();
+
+        match read(&mut buffers, READ_TIMEOUT_US) {
+            Ok(meta) => {
+                if overflowed {
+                    // The first timestamp after an overflow marks the end of the gap.
+                    overflowed = false;
+                    if let Some(t) = meta.time() {
+                        shared.last_overflow_ns.store(t.as_ns(), Ordering::Relaxed);
+                    }
+                }
+
+                if in_ring {
+                    // Only this thread fills slots, so this is the slot that was just read into.
+                    ring.slot().unwrap().meta = meta;
+                    ring.push();
+                } else {
+                    shared.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+            Err(e) if e.code == ErrorCode::Timeout => {}
+            Err(e) if e.code == ErrorCode::Overflow => {
+                overflowed = true;
+                shared.last_overflow_ns.store(NO_TIME, Ordering::Relaxed);
+                shared.overflows.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                *shared.error.lock().unwrap() = Some(e);
+                break;
+            }
+        }
+    }
+}
+
+impl<E> RxReader<E> {
+    /// Get the number of samples per channel in each chunk.
+    pub fn chunk_len(&self) -> usize {
+        self.chunk_len
+    }
+
+    /// Get the number of chunks that were discarded because the ring buffer was full.
+    pub fn dropped_chunks(&self) -> u64 {
+        self.shared.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Get the number of overflows reported by the device.
+    pub fn overflows(&self) -> u64 {
+        self.shared.overflows.load(Ordering::Relaxed)
+    }
+
+    /// Get the device timestamp at which samples resumed after the most recent overflow.
+    ///
+    /// Returns `None` if no overflow has occurred, or if the driver does not provide
+    /// timestamps.
+    pub fn last_overflow_time(&self) -> Option<HardwareTime> {
+        match self.shared.last_overflow_ns.load(Ordering::Relaxed) {
+            NO_TIME => None,
+            t => Some(HardwareTime::from_ns(t)),
+        }
+    }
+
+    /// Get the number of chunks waiting to be received.
+    pub fn pending(&self) -> usize {
+        self.ring.len()
+    }
+
+    /// Receive the next chunk if one is available, without blocking.
+    ///
+    /// The chunk's slot is returned to the ring buffer when the [`RxChunk`] is dropped.
+    pub fn try_recv(&mut self) -> Option<RxChunk<'_, E>> {
+        let meta = self.ring.peek()?.meta;
+        Some(RxChunk { reader: self, meta })
+    }
+
+    /// Receive the next chunk, waiting up to `timeout` for one to become available. A timeout
+    /// too long to represent, such as [`Duration::MAX`], waits without a deadline.
+    ///
+    /// Returns an error with [ErrorCode::Timeout] if no chunk arrives in time, or the error that
+    /// stopped the reader thread once all chunks read before it have been received.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<RxChunk<'_, E>, Error> {
+        if self.ring.wait(Instant::now().checked_add(timeout)) {
+            return Ok(self.try_recv().unwrap());
+        }
+
+        if self.ring.is_closed() {
+            Err(self.shared.error.lock().unwrap().clone().unwrap_or(Error {
+                code: ErrorCode::StreamError,
+                message: "Reader thread has exited".into(),
+            }))
+        } else {
+            Err(Error {
+                code: ErrorCode::Timeout,
+                message: "No chunk received before timeout".into(),
+            })
+        }
+    }
+}
+
+impl<E> Drop for RxReader<E> {
+    fn drop(&mut self) {
+        self.shared.stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            thread.join().ok();
+        }
+    }
+}
+
+/// A chunk of samples received from an [`RxReader`].
+///
+/// The chunk's slot is returned to the ring buffer when dropped.
+pub struct RxChunk<'a, E> {
+    reader: &'a mut RxReader<E>,
+    meta: RxMetadata,
+}
+
+impl<E> RxChunk<'_, E> {
+    /// Get the number of samples in each channel.
+    pub fn len(&self) -> usize {
+        self.meta.len
+    }
+
+    /// Returns true if the chunk contains no samples.
+    pub fn is_empty(&self) -> bool {
+        self.meta.len == 0
+    }
+
+    /// Get the number of channels in the chunk.
+    pub fn num_channels(&self) -> usize {
+        self.reader.nchannels
+    }
+
+    /// Get the samples of a channel, by index into the `channels` array passed to
+    /// `Device::rx_stream`.
+    ///
+    /// # Panics
+    ///  * If `channel` is out of range.
+    pub fn channel(&self, channel: usize) -> &[E] {
+        let reader = &*self.reader;
+        assert!(channel < reader.nchannels, "Channel index out of range");
+        let samples = &reader.ring.peek().unwrap().samples;
+        let start = channel * reader.chunk_len;
+        &samples[start..start + self.meta.len]
+    }
+
+    /// Get the flags and timestamp reported by the driver for this chunk.
+    pub fn meta(&self) -> RxMetadata {
+        self.meta
+    }
+}
+
+impl<E> Drop for RxChunk<'_, E> {
+    fn drop(&mut self) {
+        self.reader.ring.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    /// Spawn a reader of one channel of `chunk_len` 2 over the scripted `reads`, which ends
+    /// with a fatal error, and wait for its thread to exit.
+    fn scripted(capacity: usize, reads: Vec<Result<RxMetadata, Error>>) -> RxReader<u32> {
+        let mut reads = VecDeque::from(reads);
+        let mut n = 0;
+        let mut reader = spawn(
+            move |buffers: &mut [&mut [u32]], _timeout_us| {
+                let result = reads.pop_front().unwrap_or(Err(Error {
+                    code: ErrorCode::NotSupported,
+                    message: "end of script".into(),
+                }));
+                if result.is_ok() {
+                    n += 1;
+                    buffers[0].fill(n);
+                }
+                result
+            },
+            1,
+            2,
+            capacity,
+        );
+        reader.thread.take().unwrap().join().unwrap();
+        reader
+    }
+
+    fn meta(time_ns: Option<i64>) -> Result<RxMetadata, Error> {
+        Ok(RxMetadata {
+            len: 2,
+            flags: match time_ns {
+                Some(_) => StreamFlags::HAS_TIME,
+                None => StreamFlags::empty(),
+            },
+            raw_time: HardwareTime::from_ns(time_ns.unwrap_or(0)),
+        })
+    }
+
+    fn error(code: ErrorCode) -> Result<RxMetadata, Error> {
+        Err(Error {
+            code,
+            message: String::new(),
+        })
+    }
+
+    #[test]
+    fn drops_chunks_when_full() {
+        let mut reader = scripted(2, (0..5).map(|_| meta(None)).collect());
+        assert_eq!(reader.dropped_chunks(), 3);
+        assert_eq!(reader.pending(), 2);
+
+        for n in 1..=2 {
+            let chunk = reader.recv_timeout(Duration::ZERO).unwrap();
+            assert_eq!(chunk.len(), 2);
+            assert_eq!(chunk.channel(0), &[n, n]);
+        }
+
+        let err = reader.recv_timeout(Duration::ZERO).err().unwrap();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+    }
+
+    #[test]
+    fn recv_without_deadline() {
+        let mut reader = scripted(4, vec![meta(None)]);
+        assert_eq!(
+            reader.recv_timeout(Duration::MAX).unwrap().channel(0),
+            &[1, 1]
+        );
+
+        let err = reader.recv_timeout(Duration::MAX).err().unwrap();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+    }
+
+    #[test]
+    fn timeouts_are_retried() {
+        let mut reader = scripted(4, vec![error(ErrorCode::Timeout), meta(None)]);
+        assert_eq!(reader.dropped_chunks(), 0);
+        assert_eq!(
+            reader.recv_timeout(Duration::ZERO).unwrap().channel(0),
+            &[1, 1]
+        );
+        assert!(reader.try_recv().is_none());
+    }
+
+    #[test]
+    fn overflow_time_is_first_timestamp_after() {
+        let reader = scripted(
+            4,
+            vec![
+                meta(Some(100)),
+                error(ErrorCode::Overflow),
+                meta(Some(500)),
+                meta(Some(600)),
+            ],
+        );
+        assert_eq!(reader.overflows(), 1);
+        assert_eq!(
+            reader.last_overflow_time(),
+            Some(HardwareTime::from_ns(500))
+        );
+
+        let reader = scripted(
+            4,
+            vec![
+                meta(Some(100)),
+                error(ErrorCode::Overflow),
+                meta(Some(500)),
+                error(ErrorCode::Overflow),
+                meta(None),
+                meta(Some(900)),
+            ],
+        );
+        assert_eq!(reader.overflows(), 2);
+        assert_eq!(reader.last_overflow_time(), None);
+    }
+
+    #[test]
+    fn no_overflow_time_before_overflow() {
+        let reader = scripted(4, vec![meta(Some(100))]);
+        assert_eq!(reader.overflows(), 0);
+        assert_eq!(reader.last_overflow_time(), None);
+    }
+}
@@ -0,0 +1,2804 @@
+//! Recording and deterministic replay of device sessions.
+//!
+//! [`RecordingDevice`] wraps any [`SdrDevice`] and logs every control call, its result, and every
+//! receive stream read with its metadata and samples to a session file. [`ReplayDevice`] plays a
+//! session back: it returns the recorded results and samples to the same sequence of calls, and
+//! fails as soon as a call differs from the recording. A session captured on the hardware where a
+//! bug occurred becomes a regression test that runs anywhere.
+//!
+//! Calls must be replayed in the order they were recorded, so an application that uses the
+//! device from several threads only replays if those threads interleave the same way. Transmit
+//! streams record the arguments and results of each write, but not the samples.
+//!
+//! # Example
+//! ```no_run
+//! use soapysdr::record::{RecordingDevice, ReplayDevice};
+//! use soapysdr::{Device, Direction, Error, SdrDevice};
+//!
+//! fn tune<D: SdrDevice>(dev: &D) -> Result<f64, Error> {
+//!     dev.set_frequency(Direction::Rx, 0, 915e6, ())?;
+//!     dev.frequency(Direction::Rx, 0)
+//! }
+//!
+//! let dev = Device::new("driver=rtlsdr").unwrap();
+//! let recording = RecordingDevice::create(dev, "session.txt").unwrap();
+//! let tuned = tune(&recording).unwrap();
+//! recording.flush().unwrap();
+//!
+//! let replay = ReplayDevice::load("session.txt").unwrap();
+//! assert_eq!(tune(&replay).unwrap(), tuned);
+//! replay.finish().unwrap();
+//! ```
+//!
+//! # Format
+//!
+//! Sessions are text files with one call per line, written as `<target> <call> = <result>`. The
+//! target is `dev` for the device, or `rx<n>` or `tx<n>` for the `n`th stream opened. The call is
+//! the method name and its arguments. The result is `ok` followed by the returned value, or `err`
+//! followed by the error code and message. Receive samples are stored in hex, so a session grows
+//! by twice the size of the samples read.
+
+use std::collections::VecDeque;
+use std::fmt::{Debug, Write as _};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::marker::PhantomData;
+use std::mem;
+use std::path::Path;
+use std::str::SplitAsciiWhitespace;
+use std::sync::{Arc, Mutex};
+
+use crate::format::{sample_bytes, sample_bytes_mut};
+use crate::{
+    ArgInfo, ArgType, Args, ChannelSet, Direction, Error, ErrorCode, Format, HardwareTime, Range,
+    RxMetadata, SdrDevice, SdrRxStream, SdrTxStream, StatusEvent, StreamFlags, StreamSample,
+};
+
+const HEADER: &str = "# SoapySDR session";
+
+/// Format a call as it appears in a session.
+fn call(method: &str, args: &[&dyn Debug]) -> String {
+    let args: Vec<String> = args.iter().map(|a| format!("{a:?}")).collect();
+    format!("{method}({})", args.join(", "))
+}
+
+type Tokens<'a> = SplitAsciiWhitespace<'a>;
+
+/// A value stored in a session as whitespace-separated tokens.
+trait Value: Sized {
+    /// Append the value's tokens, each preceded by a space.
+    fn encode(&self, out: &mut String);
+
+    fn decode(tokens: &mut Tokens) -> Option<Self>;
+}
+
+fn push(out: &mut String, token: &str) {
+    out.push(' ');
+    out.push_str(token);
+}
+
+macro_rules! parsed_value {
+    ($($t:ty),*) => {$(
+        impl Value for $t {
+            fn encode(&self, out: &mut String) {
+                push(out, &format!("{self:?}"));
+            }
+
+            fn decode(tokens: &mut Tokens) -> Option<Self> {
+                tokens.next()?.parse().ok()
+            }
+        }
+    )*};
+}
+
+parsed_value!(bool, u8, u32, i32, i64, usize, f64);
+
+impl Value for () {
+    fn encode(&self, _out: &mut String) {}
+
+    fn decode(_tokens: &mut Tokens) -> Option<Self> {
+        Some(())
+    }
+}
+
+/// Strings are percent-encoded so they form a single token, with `-` for the empty string.
+impl Value for String {
+    fn encode(&self, out: &mut String) {
+        out.push(' ');
+        if self.is_empty() {
+            out.push('-');
+        }
+        for b in self.bytes() {
+            if b.is_ascii_alphanumeric() || b"_.:/+,".contains(&b) {
+                out.push(b as char);
+            } else {
+                write!(out, "%{b:02X}").unwrap();
+            }
+        }
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        let token = tokens.next()?;
+        if token == "-" {
+            return Some(String::new());
+        }
+        let mut bytes = Vec::with_capacity(token.len());
+        let mut rest = token.as_bytes();
+        while let Some((&b, tail)) = rest.split_first() {
+            if b == b'%' {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            } else {
+                bytes.push(b);
+                rest = tail;
+            }
+        }
+        String::from_utf8(bytes).ok()
+    }
+}
+
+impl<T: Value> Value for Vec<T> {
+    fn encode(&self, out: &mut String) {
+        self.len().encode(out);
+        for item in self {
+            item.encode(out);
+        }
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        let len = usize::decode(tokens)?;
+        (0..len).map(|_| T::decode(tokens)).collect()
+    }
+}
+
+impl<T: Value> Value for Option<T> {
+    fn encode(&self, out: &mut String) {
+        match self {
+            Some(value) => {
+                push(out, "some");
+                value.encode(out);
+            }
+            None => push(out, "none"),
+        }
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        match tokens.next()? {
+            "some" => Some(Some(T::decode(tokens)?)),
+            "none" => Some(None),
+            _ => None,
+        }
+    }
+}
+
+impl<A: Value, B: Value> Value for (A, B) {
+    fn encode(&self, out: &mut String) {
+        self.0.encode(out);
+        self.1.encode(out);
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        Some((A::decode(tokens)?, B::decode(tokens)?))
+    }
+}
+
+impl Value for Format {
+    fn encode(&self, out: &mut String) {
+        push(out, self.as_str());
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        tokens.next()?.parse().ok()
+    }
+}
+
+impl Value for HardwareTime {
+    fn encode(&self, out: &mut String) {
+        self.as_ns().encode(out);
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        i64::decode(tokens).map(HardwareTime::from_ns)
+    }
+}
+
+impl Value for Range {
+    fn encode(&self, out: &mut String) {
+        self.minimum.encode(out);
+        self.maximum.encode(out);
+        self.step.encode(out);
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        Some(Range::new(
+            f64::decode(tokens)?,
+            f64::decode(tokens)?,
+            f64::decode(tokens)?,
+        ))
+    }
+}
+
+impl Value for Args {
+    fn encode(&self, out: &mut String) {
+        let pairs: Vec<(String, String)> = self.iter().map(|(k, v)| (k.into(), v.into())).collect();
+        pairs.encode(out);
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        let pairs = Vec::<(String, String)>::decode(tokens)?;
+        Some(pairs.into_iter().collect())
+    }
+}
+
+impl Value for ArgType {
+    fn encode(&self, out: &mut String) {
+        push(
+            out,
+            match self {
+                ArgType::Bool => "bool",
+                ArgType::Float => "float",
+                ArgType::Int => "int",
+                ArgType::String => "string",
+            },
+        );
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        match tokens.next()? {
+            "bool" => Some(ArgType::Bool),
+            "float" => Some(ArgType::Float),
+            "int" => Some(ArgType::Int),
+            "string" => Some(ArgType::String),
+            _ => None,
+        }
+    }
+}
+
+impl Value for ArgInfo {
+    fn encode(&self, out: &mut String) {
+        self.key.encode(out);
+        self.value.encode(out);
+        self.name.encode(out);
+        self.description.encode(out);
+        self.units.encode(out);
+        self.data_type.encode(out);
+        self.options.encode(out);
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        Some(ArgInfo {
+            key: Value::decode(tokens)?,
+            value: Value::decode(tokens)?,
+            name: Value::decode(tokens)?,
+            description: Value::decode(tokens)?,
+            units: Value::decode(tokens)?,
+            data_type: Value::decode(tokens)?,
+            options: Value::decode(tokens)?,
+        })
+    }
+}
+
+impl Value for StatusEvent {
+    fn encode(&self, out: &mut String) {
+        let (name, flags) = match *self {
+            StatusEvent::Timeout => return push(out, "Timeout"),
+            StatusEvent::BurstAck { .. } => ("BurstAck", None),
+            StatusEvent::Underflow { .. } => ("Underflow", None),
+            StatusEvent::Overflow { .. } => ("Overflow", None),
+            StatusEvent::TimeError { .. } => ("TimeError", None),
+            StatusEvent::Corruption { .. } => ("Corruption", None),
+            StatusEvent::StreamError { .. } => ("StreamError", None),
+            StatusEvent::Other { flags, .. } => ("Other", Some(flags.bits())),
+        };
+        push(out, name);
+        self.channels().mask().encode(out);
+        self.time().encode(out);
+        if let Some(flags) = flags {
+            flags.encode(out);
+        }
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        let name = tokens.next()?;
+        if name == "Timeout" {
+            return Some(StatusEvent::Timeout);
+        }
+        let channels = ChannelSet::from_mask(usize::decode(tokens)?);
+        let time = Option::<HardwareTime>::decode(tokens)?;
+        Some(match name {
+            "BurstAck" => StatusEvent::BurstAck { channels, time },
+            "Underflow" => StatusEvent::Underflow { channels, time },
+            "Overflow" => StatusEvent::Overflow { channels, time },
+            "TimeError" => StatusEvent::TimeError { channels, time },
+            "Corruption" => StatusEvent::Corruption { channels, time },
+            "StreamError" => StatusEvent::StreamError { channels, time },
+            "Other" => StatusEvent::Other {
+                channels,
+                time,
+                flags: StreamFlags::from_bits(i32::decode(tokens)?),
+            },
+            _ => return None,
+        })
+    }
+}
+
+/// The result of a receive stream read, with the bytes read into each channel's buffer.
+struct RxRead {
+    meta: RxMetadata,
+    buffers: Vec<Vec<u8>>,
+}
+
+impl Value for RxRead {
+    fn encode(&self, out: &mut String) {
+        self.meta.len.encode(out);
+        self.meta.flags.bits().encode(out);
+        self.meta.raw_time.encode(out);
+        self.buffers.len().encode(out);
+        for buffer in &self.buffers {
+            out.push(' ');
+            if buffer.is_empty() {
+                out.push('-');
+            }
+            for b in buffer {
+                write!(out, "{b:02x}").unwrap();
+            }
+        }
+    }
+
+    fn decode(tokens: &mut Tokens) -> Option<Self> {
+        let meta = RxMetadata {
+            len: usize::decode(tokens)?,
+            flags: StreamFlags::from_bits(i32::decode(tokens)?),
+            raw_time: HardwareTime::decode(tokens)?,
+        };
+        let buffers = (0..usize::decode(tokens)?)
+            .map(|_| {
+                let hex = tokens.next()?;
+                if hex == "-" {
+                    return Some(Vec::new());
+                }
+                if hex.len() % 2 != 0 {
+                    return None;
+                }
+                (0..hex.len())
+                    .step_by(2)
+                    .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+                    .collect()
+            })
+            .collect::<Option<_>>()?;
+        Some(RxRead { meta, buffers })
+    }
+}
+
+fn encode_result<T: Value>(result: &Result<T, Error>) -> String {
+    let mut out = String::new();
+    match result {
+        Ok(value) => {
+            out.push_str("ok");
+            value.encode(&mut out);
+        }
+        Err(e) => {
+            out.push_str("err");
+            (e.code as i32).encode(&mut out);
+            e.message.encode(&mut out);
+        }
+    }
+    out
+}
+
+fn decode_result<T: Value>(s: &str) -> Option<Result<T, Error>> {
+    let mut tokens = s.split_ascii_whitespace();
+    let result = match tokens.next()? {
+        "ok" => Ok(T::decode(&mut tokens)?),
+        "err" => Err(Error {
+            code: ErrorCode::from_c(i32::decode(&mut tokens)?),
+            message: String::decode(&mut tokens)?,
+        }),
+        _ => return None,
+    };
+    tokens.next().is_none().then_some(result)
+}
+
+struct Log {
+    out: Box<dyn Write + Send>,
+    streams: usize,
+    error: Option<io::Error>,
+}
+
+impl Log {
+    fn write<T: Value>(&mut self, target: &str, call: &str, result: &Result<T, Error>) {
+        // Keep the first error for `flush` to report, and stop writing after it.
+        if self.error.is_none() {
+            let line = encode_result(result);
+            self.error = writeln!(self.out, "{target} {call} = {line}").err();
+        }
+    }
+}
+
+fn record<T: Value>(
+    log: &Mutex<Log>,
+    target: &str,
+    call: String,
+    result: Result<T, Error>,
+) -> Result<T, Error> {
+    log.lock().unwrap().write(target, &call, &result);
+    result
+}
+
+/// A device wrapper that records a session of calls to the device it wraps.
+///
+/// Write errors don't interrupt the session; the first is returned by
+/// [`flush`](RecordingDevice::flush).
+#[derive(Clone)]
+pub struct RecordingDevice<D> {
+    inner: D,
+    log: Arc<Mutex<Log>>,
+}
+
+impl<D: SdrDevice> RecordingDevice<D> {
+    /// Record calls to `inner`, writing the session to `out`.
+    pub fn new<W: Write + Send + 'static>(inner: D, mut out: W) -> io::Result<RecordingDevice<D>> {
+        writeln!(out, "{HEADER}")?;
+        Ok(RecordingDevice {
+            inner,
+            log: Arc::new(Mutex::new(Log {
+                out: Box::new(out),
+                streams: 0,
+                error: None,
+            })),
+        })
+    }
+
+    /// Record calls to `inner`, writing the session to a file.
+    pub fn create<P: AsRef<Path>>(inner: D, path: P) -> io::Result<RecordingDevice<D>> {
+        RecordingDevice::new(inner, BufWriter::new(File::create(path)?))
+    }
+
+    /// Get the device being recorded.
+    pub fn inner(&self) -> &D {
+        &self.inner
+    }
+
+    /// Flush the session, returning the first error that occurred while writing it.
+    pub fn flush(&self) -> io::Result<()> {
+        let mut log = self.log.lock().unwrap();
+        match log.error.take() {
+            Some(e) => Err(e),
+            None => log.out.flush(),
+        }
+    }
+
+    fn record<T: Value>(&self, call: String, result: Result<T, Error>) -> Result<T, Error> {
+        record(&self.log, "dev", call, result)
+    }
+
+    /// Record the opening of a stream, returning its target name.
+    fn record_stream<S>(&self, direction: &str, call: String, result: &Result<S, Error>) -> String {
+        let mut log = self.log.lock().unwrap();
+        log.streams += 1;
+        let id = log.streams;
+        log.write(
+            "dev",
+            &call,
+            &result.as_ref().map(|_| id).map_err(Error::clone),
+        );
+        format!("{direction}{id}")
+    }
+}
+
+impl<D: SdrDevice> SdrDevice for RecordingDevice<D> {
+    type RxStream<E: StreamSample> = RecordingRxStream<D::RxStream<E>>;
+    type TxStream<E: StreamSample> = RecordingTxStream<D::TxStream<E>>;
+
+    fn rx_stream<E: StreamSample>(
+        &self,
+        channels: &[usize],
+    ) -> Result<RecordingRxStream<D::RxStream<E>>, Error> {
+        self.rx_stream_args(channels, ())
+    }
+
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<RecordingRxStream<D::RxStream<E>>, Error> {
+        let args = args.into();
+        let call = call(
+            "rx_stream",
+            &[&E::STREAM_FORMAT, &channels, &args.to_string()],
+        );
+        let result = self.inner.rx_stream_args::<E, _>(channels, args);
+        let target = self.record_stream("rx", call, &result);
+        result.map(|inner| RecordingRxStream {
+            inner,
+            log: self.log.clone(),
+            target,
+        })
+    }
+
+    fn tx_stream<E: StreamSample>(
+        &self,
+        channels: &[usize],
+    ) -> Result<RecordingTxStream<D::TxStream<E>>, Error> {
+        self.tx_stream_args(channels, ())
+    }
+
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<RecordingTxStream<D::TxStream<E>>, Error> {
+        let args = args.into();
+        let call = call(
+            "tx_stream",
+            &[&E::STREAM_FORMAT, &channels, &args.to_string()],
+        );
+        let result = self.inner.tx_stream_args::<E, _>(channels, args);
+        let target = self.record_stream("tx", call, &result);
+        result.map(|inner| RecordingTxStream {
+            inner,
+            log: self.log.clone(),
+            target,
+        })
+    }
+
+    fn driver_key(&self) -> Result<String, Error> {
+        let call = call("driver_key", &[]);
+        self.record(call, self.inner.driver_key())
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        let call = call("hardware_key", &[]);
+        self.record(call, self.inner.hardware_key())
+    }
+
+    fn hardware_info(&self) -> Result<Args, Error> {
+        let call = call("hardware_info", &[]);
+        self.record(call, self.inner.hardware_info())
+    }
+
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error> {
+        let call = call("frontend_mapping", &[&direction]);
+        self.record(call, self.inner.frontend_mapping(direction))
+    }
+
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error> {
+        let mapping = mapping.into();
+        let call = call(
+            "set_frontend_mapping",
+            &[&direction, &String::from_utf8_lossy(&mapping)],
+        );
+        self.record(call, self.inner.set_frontend_mapping(direction, mapping))
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        let call = call("num_channels", &[&direction]);
+        self.record(call, self.inner.num_channels(direction))
+    }
+
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error> {
+        let call = call("channel_info", &[&direction, &channel]);
+        self.record(call, self.inner.channel_info(direction, channel))
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("full_duplex", &[&direction, &channel]);
+        self.record(call, self.inner.full_duplex(direction, channel))
+    }
+
+    fn list_sensors(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_sensors", &[]);
+        self.record(call, self.inner.list_sensors())
+    }
+
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error> {
+        let call = call("get_sensor_info", &[&key]);
+        self.record(call, self.inner.get_sensor_info(key))
+    }
+
+    fn read_sensor(&self, key: &str) -> Result<String, Error> {
+        let call = call("read_sensor", &[&key]);
+        self.record(call, self.inner.read_sensor(key))
+    }
+
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        let call = call("list_channel_sensors", &[&dir, &channel]);
+        self.record(call, self.inner.list_channel_sensors(dir, channel))
+    }
+
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error> {
+        let call = call("get_channel_sensor_info", &[&dir, &channel, &key]);
+        self.record(call, self.inner.get_channel_sensor_info(dir, channel, key))
+    }
+
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error> {
+        let call = call("read_channel_sensor", &[&dir, &channel, &key]);
+        self.record(call, self.inner.read_channel_sensor(dir, channel, key))
+    }
+
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        let call = call("stream_formats", &[&direction, &channel]);
+        self.record(call, self.inner.stream_formats(direction, channel))
+    }
+
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        let call = call("native_stream_format", &[&direction, &channel]);
+        self.record(call, self.inner.native_stream_format(direction, channel))
+    }
+
+    fn stream_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        let call = call("stream_args_info", &[&direction, &channel]);
+        self.record(call, self.inner.stream_args_info(direction, channel))
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        let call = call("antennas", &[&direction, &channel]);
+        self.record(call, self.inner.antennas(direction, channel))
+    }
+
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let call = call(
+            "set_antenna",
+            &[&direction, &channel, &String::from_utf8_lossy(&name)],
+        );
+        self.record(call, self.inner.set_antenna(direction, channel, name))
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        let call = call("antenna", &[&direction, &channel]);
+        self.record(call, self.inner.antenna(direction, channel))
+    }
+
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("has_dc_offset_mode", &[&direction, &channel]);
+        self.record(call, self.inner.has_dc_offset_mode(direction, channel))
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        let call = call("set_dc_offset_mode", &[&direction, &channel, &automatic]);
+        self.record(
+            call,
+            self.inner.set_dc_offset_mode(direction, channel, automatic),
+        )
+    }
+
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("dc_offset_mode", &[&direction, &channel]);
+        self.record(call, self.inner.dc_offset_mode(direction, channel))
+    }
+
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("has_dc_offset", &[&direction, &channel]);
+        self.record(call, self.inner.has_dc_offset(direction, channel))
+    }
+
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error> {
+        let call = call(
+            "set_dc_offset",
+            &[&direction, &channel, &offset_i, &offset_q],
+        );
+        self.record(
+            call,
+            self.inner
+                .set_dc_offset(direction, channel, offset_i, offset_q),
+        )
+    }
+
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        let call = call("dc_offset", &[&direction, &channel]);
+        self.record(call, self.inner.dc_offset(direction, channel))
+    }
+
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("has_iq_balance", &[&direction, &channel]);
+        self.record(call, self.inner.has_iq_balance(direction, channel))
+    }
+
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error> {
+        let call = call(
+            "set_iq_balance",
+            &[&direction, &channel, &balance_i, &balance_q],
+        );
+        self.record(
+            call,
+            self.inner
+                .set_iq_balance(direction, channel, balance_i, balance_q),
+        )
+    }
+
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        let call = call("iq_balance", &[&direction, &channel]);
+        self.record(call, self.inner.iq_balance(direction, channel))
+    }
+
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("has_iq_balance_mode", &[&direction, &channel]);
+        self.record(call, self.inner.has_iq_balance_mode(direction, channel))
+    }
+
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        let call = call("set_iq_balance_mode", &[&direction, &channel, &automatic]);
+        self.record(
+            call,
+            self.inner
+                .set_iq_balance_mode(direction, channel, automatic),
+        )
+    }
+
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("iq_balance_mode", &[&direction, &channel]);
+        self.record(call, self.inner.iq_balance_mode(direction, channel))
+    }
+
+    fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        let call = call("has_frequency_correction", &[&direction, &channel]);
+        self.record(
+            call,
+            self.inner.has_frequency_correction(direction, channel),
+        )
+    }
+
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        let call = call("set_frequency_correction", &[&direction, &channel, &ppm]);
+        self.record(
+            call,
+            self.inner.set_frequency_correction(direction, channel, ppm),
+        )
+    }
+
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let call = call("frequency_correction", &[&direction, &channel]);
+        self.record(call, self.inner.frequency_correction(direction, channel))
+    }
+
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        let call = call("list_gains", &[&direction, &channel]);
+        self.record(call, self.inner.list_gains(direction, channel))
+    }
+
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("has_gain_mode", &[&direction, &channel]);
+        self.record(call, self.inner.has_gain_mode(direction, channel))
+    }
+
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        let call = call("set_gain_mode", &[&direction, &channel, &automatic]);
+        self.record(
+            call,
+            self.inner.set_gain_mode(direction, channel, automatic),
+        )
+    }
+
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        let call = call("gain_mode", &[&direction, &channel]);
+        self.record(call, self.inner.gain_mode(direction, channel))
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        let call = call("set_gain", &[&direction, &channel, &gain]);
+        self.record(call, self.inner.set_gain(direction, channel, gain))
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let call = call("gain", &[&direction, &channel]);
+        self.record(call, self.inner.gain(direction, channel))
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        let call = call("gain_range", &[&direction, &channel]);
+        self.record(call, self.inner.gain_range(direction, channel))
+    }
+
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let call = call(
+            "set_gain_element",
+            &[&direction, &channel, &String::from_utf8_lossy(&name), &gain],
+        );
+        self.record(
+            call,
+            self.inner.set_gain_element(direction, channel, name, gain),
+        )
+    }
+
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = name.into();
+        let call = call(
+            "gain_element",
+            &[&direction, &channel, &String::from_utf8_lossy(&name)],
+        );
+        self.record(call, self.inner.gain_element(direction, channel, name))
+    }
+
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error> {
+        let name = name.into();
+        let call = call(
+            "gain_element_range",
+            &[&direction, &channel, &String::from_utf8_lossy(&name)],
+        );
+        self.record(
+            call,
+            self.inner.gain_element_range(direction, channel, name),
+        )
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        let call = call("frequency_range", &[&direction, &channel]);
+        self.record(call, self.inner.frequency_range(direction, channel))
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let call = call("frequency", &[&direction, &channel]);
+        self.record(call, self.inner.frequency(direction, channel))
+    }
+
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let args = args.into();
+        let call = call(
+            "set_frequency",
+            &[&direction, &channel, &frequency, &args.to_string()],
+        );
+        self.record(
+            call,
+            self.inner
+                .set_frequency(direction, channel, frequency, args),
+        )
+    }
+
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        let call = call("list_frequencies", &[&direction, &channel]);
+        self.record(call, self.inner.list_frequencies(direction, channel))
+    }
+
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error> {
+        let name = name.into();
+        let call = call(
+            "component_frequency_range",
+            &[&direction, &channel, &String::from_utf8_lossy(&name)],
+        );
+        self.record(
+            call,
+            self.inner
+                .component_frequency_range(direction, channel, name),
+        )
+    }
+
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = name.into();
+        let call = call(
+            "component_frequency",
+            &[&direction, &channel, &String::from_utf8_lossy(&name)],
+        );
+        self.record(
+            call,
+            self.inner.component_frequency(direction, channel, name),
+        )
+    }
+
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let args = args.into();
+        let call = call(
+            "set_component_frequency",
+            &[
+                &direction,
+                &channel,
+                &String::from_utf8_lossy(&name),
+                &frequency,
+                &args.to_string(),
+            ],
+        );
+        self.record(
+            call,
+            self.inner
+                .set_component_frequency(direction, channel, name, frequency, args),
+        )
+    }
+
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        let call = call("frequency_args_info", &[&direction, &channel]);
+        self.record(call, self.inner.frequency_args_info(direction, channel))
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let call = call("sample_rate", &[&direction, &channel]);
+        self.record(call, self.inner.sample_rate(direction, channel))
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        let call = call("set_sample_rate", &[&direction, &channel, &rate]);
+        self.record(call, self.inner.set_sample_rate(direction, channel, rate))
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        let call = call("get_sample_rate_range", &[&direction, &channel]);
+        self.record(call, self.inner.get_sample_rate_range(direction, channel))
+    }
+
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        let call = call("list_sample_rates", &[&direction, &channel]);
+        self.record(call, self.inner.list_sample_rates(direction, channel))
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        let call = call("bandwidth", &[&direction, &channel]);
+        self.record(call, self.inner.bandwidth(direction, channel))
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error> {
+        let call = call("set_bandwidth", &[&direction, &channel, &bandwidth]);
+        self.record(
+            call,
+            self.inner.set_bandwidth(direction, channel, bandwidth),
+        )
+    }
+
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        let call = call("bandwidth_range", &[&direction, &channel]);
+        self.record(call, self.inner.bandwidth_range(direction, channel))
+    }
+
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        let call = call("list_bandwidths", &[&direction, &channel]);
+        self.record(call, self.inner.list_bandwidths(direction, channel))
+    }
+
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_clock_sources", &[]);
+        self.record(call, self.inner.list_clock_sources())
+    }
+
+    fn get_clock_source(&self) -> Result<String, Error> {
+        let call = call("get_clock_source", &[]);
+        self.record(call, self.inner.get_clock_source())
+    }
+
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error> {
+        let clock_source = clock_source.into();
+        let call = call(
+            "set_clock_source",
+            &[&String::from_utf8_lossy(&clock_source)],
+        );
+        self.record(call, self.inner.set_clock_source(clock_source))
+    }
+
+    fn get_master_clock_rate(&self) -> Result<f64, Error> {
+        let call = call("get_master_clock_rate", &[]);
+        self.record(call, self.inner.get_master_clock_rate())
+    }
+
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        let call = call("set_master_clock_rate", &[&rate]);
+        self.record(call, self.inner.set_master_clock_rate(rate))
+    }
+
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        let call = call("get_master_clock_rates", &[]);
+        self.record(call, self.inner.get_master_clock_rates())
+    }
+
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        let call = call("set_reference_clock_rate", &[&rate]);
+        self.record(call, self.inner.set_reference_clock_rate(rate))
+    }
+
+    fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        let call = call("get_reference_clock_rate", &[]);
+        self.record(call, self.inner.get_reference_clock_rate())
+    }
+
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        let call = call("get_reference_clock_rates", &[]);
+        self.record(call, self.inner.get_reference_clock_rates())
+    }
+
+    fn list_time_sources(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_time_sources", &[]);
+        self.record(call, self.inner.list_time_sources())
+    }
+
+    fn get_time_source(&self) -> Result<String, Error> {
+        let call = call("get_time_source", &[]);
+        self.record(call, self.inner.get_time_source())
+    }
+
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error> {
+        let time_source = time_source.into();
+        let call = call("set_time_source", &[&String::from_utf8_lossy(&time_source)]);
+        self.record(call, self.inner.set_time_source(time_source))
+    }
+
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error> {
+        let call = call("has_hardware_time", &[&hw_time_source]);
+        self.record(call, self.inner.has_hardware_time(hw_time_source))
+    }
+
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
+        let call = call("get_hardware_time", &[&hw_time_source]);
+        self.record(call, self.inner.get_hardware_time(hw_time_source))
+    }
+
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error> {
+        let call = call("set_hardware_time", &[&hw_time_source, &new_time]);
+        self.record(call, self.inner.set_hardware_time(hw_time_source, new_time))
+    }
+
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        let call = call("set_command_time", &[&time, &what]);
+        self.record(call, self.inner.set_command_time(time, what))
+    }
+
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        let call = call("clear_command_time", &[&what]);
+        self.record(call, self.inner.clear_command_time(what))
+    }
+
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_register_interfaces", &[]);
+        self.record(call, self.inner.list_register_interfaces())
+    }
+
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let call = call(
+            "write_register",
+            &[&String::from_utf8_lossy(&name), &address, &value],
+        );
+        self.record(call, self.inner.write_register(name, address, value))
+    }
+
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error> {
+        let name = name.into();
+        let call = call(
+            "read_register",
+            &[&String::from_utf8_lossy(&name), &address],
+        );
+        self.record(call, self.inner.read_register(name, address))
+    }
+
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let call = call(
+            "write_registers",
+            &[&String::from_utf8_lossy(&name), &address, &value],
+        );
+        self.record(call, self.inner.write_registers(name, address, value))
+    }
+
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        let name = name.into();
+        let call = call(
+            "read_registers",
+            &[&String::from_utf8_lossy(&name), &address, &length],
+        );
+        self.record(call, self.inner.read_registers(name, address, length))
+    }
+
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error> {
+        let key = key.into();
+        let value = value.into();
+        let call = call(
+            "write_setting",
+            &[
+                &String::from_utf8_lossy(&key),
+                &String::from_utf8_lossy(&value),
+            ],
+        );
+        self.record(call, self.inner.write_setting(key, value))
+    }
+
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error> {
+        let key = key.into();
+        let call = call("read_setting", &[&String::from_utf8_lossy(&key)]);
+        self.record(call, self.inner.read_setting(key))
+    }
+
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        let call = call("setting_info", &[]);
+        self.record(call, self.inner.setting_info())
+    }
+
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        let call = call("setting_info_with_key", &[&key]);
+        self.record(call, self.inner.setting_info_with_key(key))
+    }
+
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        let call = call("channel_setting_info", &[&direction, &channel]);
+        self.record(call, self.inner.channel_setting_info(direction, channel))
+    }
+
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        let call = call(
+            "channel_setting_info_with_key",
+            &[&direction, &channel, &key],
+        );
+        self.record(
+            call,
+            self.inner
+                .channel_setting_info_with_key(direction, channel, key),
+        )
+    }
+
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        let key = key.into();
+        let value = value.into();
+        let call = call(
+            "write_channel_setting",
+            &[
+                &direction,
+                &channel,
+                &String::from_utf8_lossy(&key),
+                &String::from_utf8_lossy(&value),
+            ],
+        );
+        self.record(
+            call,
+            self.inner
+                .write_channel_setting(direction, channel, key, value),
+        )
+    }
+
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        let key = key.into();
+        let call = call(
+            "read_channel_setting",
+            &[&direction, &channel, &String::from_utf8_lossy(&key)],
+        );
+        self.record(
+            call,
+            self.inner.read_channel_setting(direction, channel, key),
+        )
+    }
+
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_gpio_banks", &[]);
+        self.record(call, self.inner.list_gpio_banks())
+    }
+
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        let bank = bank.into();
+        let call = call("write_gpio", &[&String::from_utf8_lossy(&bank), &value]);
+        self.record(call, self.inner.write_gpio(bank, value))
+    }
+
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = bank.into();
+        let call = call(
+            "write_gpio_masked",
+            &[&String::from_utf8_lossy(&bank), &value, &mask],
+        );
+        self.record(call, self.inner.write_gpio_masked(bank, value, mask))
+    }
+
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = bank.into();
+        let call = call("read_gpio", &[&String::from_utf8_lossy(&bank)]);
+        self.record(call, self.inner.read_gpio(bank))
+    }
+
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        let bank = bank.into();
+        let call = call("write_gpio_dir", &[&String::from_utf8_lossy(&bank), &dir]);
+        self.record(call, self.inner.write_gpio_dir(bank, dir))
+    }
+
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = bank.into();
+        let call = call(
+            "write_gpio_dir_masked",
+            &[&String::from_utf8_lossy(&bank), &dir, &mask],
+        );
+        self.record(call, self.inner.write_gpio_dir_masked(bank, dir, mask))
+    }
+
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = bank.into();
+        let call = call("read_gpio_dir", &[&String::from_utf8_lossy(&bank)]);
+        self.record(call, self.inner.read_gpio_dir(bank))
+    }
+
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        let call = call("write_i2c", &[&addr, &data]);
+        self.record(call, self.inner.write_i2c(addr, data))
+    }
+
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        let call = call("read_i2c", &[&addr, &num_bytes]);
+        self.record(call, self.inner.read_i2c(addr, num_bytes))
+    }
+
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        let call = call("transact_spi", &[&addr, &data, &num_bits]);
+        self.record(call, self.inner.transact_spi(addr, data, num_bits))
+    }
+
+    fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        let call = call("list_uarts", &[]);
+        self.record(call, self.inner.list_uarts())
+    }
+
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        let which = which.into();
+        let call = call("write_uart", &[&String::from_utf8_lossy(&which), &data]);
+        self.record(call, self.inner.write_uart(which, data))
+    }
+
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        let which = which.into();
+        let call = call(
+            "read_uart",
+            &[&String::from_utf8_lossy(&which), &timeout_us],
+        );
+        self.record(call, self.inner.read_uart(which, timeout_us))
+    }
+}
+
+/// A receive stream of a [`RecordingDevice`].
+pub struct RecordingRxStream<S> {
+    inner: S,
+    log: Arc<Mutex<Log>>,
+    target: String,
+}
+
+impl<S> RecordingRxStream<S> {
+    fn record<T: Value>(&self, call: String, result: Result<T, Error>) -> Result<T, Error> {
+        record(&self.log, &self.target, call, result)
+    }
+}
+
+impl<E: StreamSample, S: SdrRxStream<E>> SdrRxStream<E> for RecordingRxStream<S> {
+    fn num_channels(&self) -> usize {
+        self.inner.num_channels()
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        self.record(call("mtu", &[]), self.inner.mtu())
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        let result = self.inner.activate(time);
+        self.record(call("activate", &[&time]), result)
+    }
+
+    fn active(&self) -> bool {
+        self.inner.active()
+    }
+
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        let result = self.inner.activate_burst(time, num_samples);
+        self.record(call("activate_burst", &[&time, &num_samples]), result)
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        let result = self.inner.deactivate(time);
+        self.record(call("deactivate", &[&time]), result)
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        let result = self.inner.read_with_meta(buffers, timeout_us);
+        let read = match result {
+            Ok(meta) => Ok(RxRead {
+                meta,
+                buffers: buffers
+                    .iter()
+                    .map(|b| sample_bytes(&b[..meta.len]).to_vec())
+                    .collect(),
+            }),
+            Err(ref e) => Err(e.clone()),
+        };
+        let call = call("read", &[&num_samples, &timeout_us]);
+        self.log.lock().unwrap().write(&self.target, &call, &read);
+        result
+    }
+
+    fn time(&self) -> HardwareTime {
+        self.inner.time()
+    }
+
+    fn burst_ended(&self) -> bool {
+        self.inner.burst_ended()
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        let result = self.inner.read_status_event(timeout_us);
+        self.record(call("read_status_event", &[&timeout_us]), result)
+    }
+}
+
+/// A transmit stream of a [`RecordingDevice`].
+pub struct RecordingTxStream<S> {
+    inner: S,
+    log: Arc<Mutex<Log>>,
+    target: String,
+}
+
+impl<S> RecordingTxStream<S> {
+    fn record<T: Value>(&self, call: String, result: Result<T, Error>) -> Result<T, Error> {
+        record(&self.log, &self.target, call, result)
+    }
+}
+
+impl<E: StreamSample, S: SdrTxStream<E>> SdrTxStream<E> for RecordingTxStream<S> {
+    fn num_channels(&self) -> usize {
+        self.inner.num_channels()
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        self.record(call("mtu", &[]), self.inner.mtu())
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        let result = self.inner.activate(time);
+        self.record(call("activate", &[&time]), result)
+    }
+
+    fn active(&self) -> bool {
+        self.inner.active()
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        let result = self.inner.deactivate(time);
+        self.record(call("deactivate", &[&time]), result)
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        let num_elems = buffers.first().map_or(0, |b| b.len());
+        let result = self.inner.write(buffers, at, end_burst, timeout_us);
+        self.record(
+            call("write", &[&num_elems, &at, &end_burst, &timeout_us]),
+            result,
+        )
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        let result = self.inner.read_status_event(timeout_us);
+        self.record(call("read_status_event", &[&timeout_us]), result)
+    }
+}
+
+struct Entry {
+    line: usize,
+    target: String,
+    call: String,
+    result: String,
+}
+
+struct Session {
+    entries: VecDeque<Entry>,
+    failure: Option<Error>,
+}
+
+/// A device that replays a session recorded by [`RecordingDevice`].
+///
+/// Each call must match the next call in the session, including its arguments, and returns the
+/// recorded result. Once a call diverges from the session, it and every later call fail with an
+/// [`ErrorCode::Other`] error describing the first divergence.
+#[derive(Clone)]
+pub struct ReplayDevice {
+    session: Arc<Mutex<Session>>,
+}
+
+impl ReplayDevice {
+    /// Read a session in its text format.
+    pub fn read_from<R: BufRead>(r: R) -> io::Result<ReplayDevice> {
+        let mut entries = VecDeque::new();
+        for (i, line) in r.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let entry = line.split_once(' ').and_then(|(target, rest)| {
+                let (call, result) = rest.rsplit_once(" = ")?;
+                Some(Entry {
+                    line: i + 1,
+                    target: target.into(),
+                    call: call.into(),
+                    result: result.into(),
+                })
+            });
+            entries.push_back(entry.ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid session line {}", i + 1),
+                )
+            })?);
+        }
+        Ok(ReplayDevice {
+            session: Arc::new(Mutex::new(Session {
+                entries,
+                failure: None,
+            })),
+        })
+    }
+
+    /// Load a session from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<ReplayDevice> {
+        ReplayDevice::read_from(BufReader::new(File::open(path)?))
+    }
+
+    /// Check that the session was replayed completely without diverging.
+    pub fn finish(&self) -> Result<(), Error> {
+        let session = self.session.lock().unwrap();
+        if let Some(failure) = &session.failure {
+            return Err(failure.clone());
+        }
+        match session.entries.front() {
+            Some(next) => Err(Error {
+                code: ErrorCode::Other,
+                message: format!(
+                    "Replay ended before line {}: `{} {}`",
+                    next.line, next.target, next.call
+                ),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn replay<T: Value>(&self, target: &str, call: String) -> Result<T, Error> {
+        let mut session = self.session.lock().unwrap();
+        if let Some(failure) = &session.failure {
+            return Err(failure.clone());
+        }
+        let result = match session.entries.pop_front() {
+            Some(next) if next.target == target && next.call == call => decode_result(&next.result)
+                .ok_or_else(|| format!("invalid result on line {}", next.line)),
+            Some(next) => Err(format!(
+                "expected `{} {}` from line {}, got `{target} {call}`",
+                next.target, next.call, next.line
+            )),
+            None => Err(format!("unexpected `{target} {call}` after the end")),
+        };
+        result.unwrap_or_else(|message| Err(session.diverge(message)))
+    }
+
+    fn diverge(&self, message: String) -> Error {
+        self.session.lock().unwrap().diverge(message)
+    }
+}
+
+impl Session {
+    fn diverge(&mut self, message: String) -> Error {
+        let error = Error {
+            code: ErrorCode::Other,
+            message: format!("Replay diverged from the session: {message}"),
+        };
+        self.failure = Some(error.clone());
+        error
+    }
+}
+
+impl SdrDevice for ReplayDevice {
+    type RxStream<E: StreamSample> = ReplayRxStream<E>;
+    type TxStream<E: StreamSample> = ReplayTxStream<E>;
+
+    fn rx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<ReplayRxStream<E>, Error> {
+        self.rx_stream_args(channels, ())
+    }
+
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<ReplayRxStream<E>, Error> {
+        let args = args.into().to_string();
+        let id: usize = self.replay(
+            "dev",
+            call("rx_stream", &[&E::STREAM_FORMAT, &channels, &args]),
+        )?;
+        Ok(ReplayRxStream {
+            device: self.clone(),
+            target: format!("rx{id}"),
+            num_channels: channels.len(),
+            active: false,
+            last: RxMetadata {
+                len: 0,
+                flags: StreamFlags::empty(),
+                raw_time: HardwareTime::ZERO,
+            },
+            phantom: PhantomData,
+        })
+    }
+
+    fn tx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<ReplayTxStream<E>, Error> {
+        self.tx_stream_args(channels, ())
+    }
+
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<ReplayTxStream<E>, Error> {
+        let args = args.into().to_string();
+        let id: usize = self.replay(
+            "dev",
+            call("tx_stream", &[&E::STREAM_FORMAT, &channels, &args]),
+        )?;
+        Ok(ReplayTxStream {
+            device: self.clone(),
+            target: format!("tx{id}"),
+            num_channels: channels.len(),
+            active: false,
+            phantom: PhantomData,
+        })
+    }
+
+    fn driver_key(&self) -> Result<String, Error> {
+        self.replay("dev", call("driver_key", &[]))
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        self.replay("dev", call("hardware_key", &[]))
+    }
+
+    fn hardware_info(&self) -> Result<Args, Error> {
+        self.replay("dev", call("hardware_info", &[]))
+    }
+
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error> {
+        self.replay("dev", call("frontend_mapping", &[&direction]))
+    }
+
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error> {
+        let mapping = mapping.into();
+        self.replay(
+            "dev",
+            call(
+                "set_frontend_mapping",
+                &[&direction, &String::from_utf8_lossy(&mapping)],
+            ),
+        )
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        self.replay("dev", call("num_channels", &[&direction]))
+    }
+
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error> {
+        self.replay("dev", call("channel_info", &[&direction, &channel]))
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("full_duplex", &[&direction, &channel]))
+    }
+
+    fn list_sensors(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_sensors", &[]))
+    }
+
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error> {
+        self.replay("dev", call("get_sensor_info", &[&key]))
+    }
+
+    fn read_sensor(&self, key: &str) -> Result<String, Error> {
+        self.replay("dev", call("read_sensor", &[&key]))
+    }
+
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_channel_sensors", &[&dir, &channel]))
+    }
+
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error> {
+        self.replay(
+            "dev",
+            call("get_channel_sensor_info", &[&dir, &channel, &key]),
+        )
+    }
+
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error> {
+        self.replay("dev", call("read_channel_sensor", &[&dir, &channel, &key]))
+    }
+
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        self.replay("dev", call("stream_formats", &[&direction, &channel]))
+    }
+
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        self.replay("dev", call("native_stream_format", &[&direction, &channel]))
+    }
+
+    fn stream_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.replay("dev", call("stream_args_info", &[&direction, &channel]))
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("antennas", &[&direction, &channel]))
+    }
+
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "set_antenna",
+                &[&direction, &channel, &String::from_utf8_lossy(&name)],
+            ),
+        )
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        self.replay("dev", call("antenna", &[&direction, &channel]))
+    }
+
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("has_dc_offset_mode", &[&direction, &channel]))
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_dc_offset_mode", &[&direction, &channel, &automatic]),
+        )
+    }
+
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("dc_offset_mode", &[&direction, &channel]))
+    }
+
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("has_dc_offset", &[&direction, &channel]))
+    }
+
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call(
+                "set_dc_offset",
+                &[&direction, &channel, &offset_i, &offset_q],
+            ),
+        )
+    }
+
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.replay("dev", call("dc_offset", &[&direction, &channel]))
+    }
+
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("has_iq_balance", &[&direction, &channel]))
+    }
+
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call(
+                "set_iq_balance",
+                &[&direction, &channel, &balance_i, &balance_q],
+            ),
+        )
+    }
+
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.replay("dev", call("iq_balance", &[&direction, &channel]))
+    }
+
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("has_iq_balance_mode", &[&direction, &channel]))
+    }
+
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_iq_balance_mode", &[&direction, &channel, &automatic]),
+        )
+    }
+
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("iq_balance_mode", &[&direction, &channel]))
+    }
+
+    fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        self.replay(
+            "dev",
+            call("has_frequency_correction", &[&direction, &channel]),
+        )
+    }
+
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_frequency_correction", &[&direction, &channel, &ppm]),
+        )
+    }
+
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.replay("dev", call("frequency_correction", &[&direction, &channel]))
+    }
+
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_gains", &[&direction, &channel]))
+    }
+
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("has_gain_mode", &[&direction, &channel]))
+    }
+
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_gain_mode", &[&direction, &channel, &automatic]),
+        )
+    }
+
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.replay("dev", call("gain_mode", &[&direction, &channel]))
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        self.replay("dev", call("set_gain", &[&direction, &channel, &gain]))
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.replay("dev", call("gain", &[&direction, &channel]))
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        self.replay("dev", call("gain_range", &[&direction, &channel]))
+    }
+
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "set_gain_element",
+                &[&direction, &channel, &String::from_utf8_lossy(&name), &gain],
+            ),
+        )
+    }
+
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "gain_element",
+                &[&direction, &channel, &String::from_utf8_lossy(&name)],
+            ),
+        )
+    }
+
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "gain_element_range",
+                &[&direction, &channel, &String::from_utf8_lossy(&name)],
+            ),
+        )
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.replay("dev", call("frequency_range", &[&direction, &channel]))
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.replay("dev", call("frequency", &[&direction, &channel]))
+    }
+
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let args = args.into();
+        self.replay(
+            "dev",
+            call(
+                "set_frequency",
+                &[&direction, &channel, &frequency, &args.to_string()],
+            ),
+        )
+    }
+
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_frequencies", &[&direction, &channel]))
+    }
+
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "component_frequency_range",
+                &[&direction, &channel, &String::from_utf8_lossy(&name)],
+            ),
+        )
+    }
+
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "component_frequency",
+                &[&direction, &channel, &String::from_utf8_lossy(&name)],
+            ),
+        )
+    }
+
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        let args = args.into();
+        self.replay(
+            "dev",
+            call(
+                "set_component_frequency",
+                &[
+                    &direction,
+                    &channel,
+                    &String::from_utf8_lossy(&name),
+                    &frequency,
+                    &args.to_string(),
+                ],
+            ),
+        )
+    }
+
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.replay("dev", call("frequency_args_info", &[&direction, &channel]))
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.replay("dev", call("sample_rate", &[&direction, &channel]))
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_sample_rate", &[&direction, &channel, &rate]),
+        )
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        self.replay(
+            "dev",
+            call("get_sample_rate_range", &[&direction, &channel]),
+        )
+    }
+
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.replay("dev", call("list_sample_rates", &[&direction, &channel]))
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.replay("dev", call("bandwidth", &[&direction, &channel]))
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_bandwidth", &[&direction, &channel, &bandwidth]),
+        )
+    }
+
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.replay("dev", call("bandwidth_range", &[&direction, &channel]))
+    }
+
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.replay("dev", call("list_bandwidths", &[&direction, &channel]))
+    }
+
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_clock_sources", &[]))
+    }
+
+    fn get_clock_source(&self) -> Result<String, Error> {
+        self.replay("dev", call("get_clock_source", &[]))
+    }
+
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error> {
+        let clock_source = clock_source.into();
+        self.replay(
+            "dev",
+            call(
+                "set_clock_source",
+                &[&String::from_utf8_lossy(&clock_source)],
+            ),
+        )
+    }
+
+    fn get_master_clock_rate(&self) -> Result<f64, Error> {
+        self.replay("dev", call("get_master_clock_rate", &[]))
+    }
+
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.replay("dev", call("set_master_clock_rate", &[&rate]))
+    }
+
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        self.replay("dev", call("get_master_clock_rates", &[]))
+    }
+
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.replay("dev", call("set_reference_clock_rate", &[&rate]))
+    }
+
+    fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        self.replay("dev", call("get_reference_clock_rate", &[]))
+    }
+
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        self.replay("dev", call("get_reference_clock_rates", &[]))
+    }
+
+    fn list_time_sources(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_time_sources", &[]))
+    }
+
+    fn get_time_source(&self) -> Result<String, Error> {
+        self.replay("dev", call("get_time_source", &[]))
+    }
+
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error> {
+        let time_source = time_source.into();
+        self.replay(
+            "dev",
+            call("set_time_source", &[&String::from_utf8_lossy(&time_source)]),
+        )
+    }
+
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error> {
+        self.replay("dev", call("has_hardware_time", &[&hw_time_source]))
+    }
+
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
+        self.replay("dev", call("get_hardware_time", &[&hw_time_source]))
+    }
+
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error> {
+        self.replay(
+            "dev",
+            call("set_hardware_time", &[&hw_time_source, &new_time]),
+        )
+    }
+
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        self.replay("dev", call("set_command_time", &[&time, &what]))
+    }
+
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        self.replay("dev", call("clear_command_time", &[&what]))
+    }
+
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_register_interfaces", &[]))
+    }
+
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "write_register",
+                &[&String::from_utf8_lossy(&name), &address, &value],
+            ),
+        )
+    }
+
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "read_register",
+                &[&String::from_utf8_lossy(&name), &address],
+            ),
+        )
+    }
+
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "write_registers",
+                &[&String::from_utf8_lossy(&name), &address, &value],
+            ),
+        )
+    }
+
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        let name = name.into();
+        self.replay(
+            "dev",
+            call(
+                "read_registers",
+                &[&String::from_utf8_lossy(&name), &address, &length],
+            ),
+        )
+    }
+
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error> {
+        let key = key.into();
+        let value = value.into();
+        self.replay(
+            "dev",
+            call(
+                "write_setting",
+                &[
+                    &String::from_utf8_lossy(&key),
+                    &String::from_utf8_lossy(&value),
+                ],
+            ),
+        )
+    }
+
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error> {
+        let key = key.into();
+        self.replay(
+            "dev",
+            call("read_setting", &[&String::from_utf8_lossy(&key)]),
+        )
+    }
+
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        self.replay("dev", call("setting_info", &[]))
+    }
+
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        self.replay("dev", call("setting_info_with_key", &[&key]))
+    }
+
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.replay("dev", call("channel_setting_info", &[&direction, &channel]))
+    }
+
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        self.replay(
+            "dev",
+            call(
+                "channel_setting_info_with_key",
+                &[&direction, &channel, &key],
+            ),
+        )
+    }
+
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        let key = key.into();
+        let value = value.into();
+        self.replay(
+            "dev",
+            call(
+                "write_channel_setting",
+                &[
+                    &direction,
+                    &channel,
+                    &String::from_utf8_lossy(&key),
+                    &String::from_utf8_lossy(&value),
+                ],
+            ),
+        )
+    }
+
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        let key = key.into();
+        self.replay(
+            "dev",
+            call(
+                "read_channel_setting",
+                &[&direction, &channel, &String::from_utf8_lossy(&key)],
+            ),
+        )
+    }
+
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_gpio_banks", &[]))
+    }
+
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        let bank = bank.into();
+        self.replay(
+            "dev",
+            call("write_gpio", &[&String::from_utf8_lossy(&bank), &value]),
+        )
+    }
+
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = bank.into();
+        self.replay(
+            "dev",
+            call(
+                "write_gpio_masked",
+                &[&String::from_utf8_lossy(&bank), &value, &mask],
+            ),
+        )
+    }
+
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = bank.into();
+        self.replay("dev", call("read_gpio", &[&String::from_utf8_lossy(&bank)]))
+    }
+
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        let bank = bank.into();
+        self.replay(
+            "dev",
+            call("write_gpio_dir", &[&String::from_utf8_lossy(&bank), &dir]),
+        )
+    }
+
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        let bank = bank.into();
+        self.replay(
+            "dev",
+            call(
+                "write_gpio_dir_masked",
+                &[&String::from_utf8_lossy(&bank), &dir, &mask],
+            ),
+        )
+    }
+
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        let bank = bank.into();
+        self.replay(
+            "dev",
+            call("read_gpio_dir", &[&String::from_utf8_lossy(&bank)]),
+        )
+    }
+
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        self.replay("dev", call("write_i2c", &[&addr, &data]))
+    }
+
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        self.replay("dev", call("read_i2c", &[&addr, &num_bytes]))
+    }
+
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        self.replay("dev", call("transact_spi", &[&addr, &data, &num_bits]))
+    }
+
+    fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        self.replay("dev", call("list_uarts", &[]))
+    }
+
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        let which = which.into();
+        self.replay(
+            "dev",
+            call("write_uart", &[&String::from_utf8_lossy(&which), &data]),
+        )
+    }
+
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        let which = which.into();
+        self.replay(
+            "dev",
+            call(
+                "read_uart",
+                &[&String::from_utf8_lossy(&which), &timeout_us],
+            ),
+        )
+    }
+}
+
+/// A receive stream of a [`ReplayDevice`], returning the recorded samples.
+pub struct ReplayRxStream<E: StreamSample> {
+    device: ReplayDevice,
+    target: String,
+    num_channels: usize,
+    active: bool,
+    last: RxMetadata,
+    phantom: PhantomData<fn(&mut [E])>,
+}
+
+impl<E: StreamSample> SdrRxStream<E> for ReplayRxStream<E> {
+    fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        self.device.replay(&self.target, call("mtu", &[]))
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.device
+            .replay::<()>(&self.target, call("activate", &[&time]))?;
+        self.active = true;
+        Ok(())
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        self.device
+            .replay::<()>(&self.target, call("activate_burst", &[&time, &num_samples]))?;
+        self.active = true;
+        self.last.flags = StreamFlags::empty();
+        Ok(())
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.device
+            .replay::<()>(&self.target, call("deactivate", &[&time]))?;
+        self.active = false;
+        Ok(())
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        let read: RxRead = self
+            .device
+            .replay(&self.target, call("read", &[&num_samples, &timeout_us]))?;
+
+        let size = read.meta.len * mem::size_of::<E>();
+        if read.meta.len > num_samples
+            || read.buffers.len() != buffers.len()
+            || read.buffers.iter().any(|b| b.len() != size)
+        {
+            return Err(self.device.diverge(format!(
+                "recorded read on {} doesn't fit the buffers",
+                self.target
+            )));
+        }
+        for (dst, src) in buffers.iter_mut().zip(&read.buffers) {
+            sample_bytes_mut(&mut dst[..read.meta.len]).copy_from_slice(src);
+        }
+
+        self.last = read.meta;
+        Ok(read.meta)
+    }
+
+    fn time(&self) -> HardwareTime {
+        self.last.raw_time
+    }
+
+    fn burst_ended(&self) -> bool {
+        self.last.flags.contains(StreamFlags::END_BURST)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.device
+            .replay(&self.target, call("read_status_event", &[&timeout_us]))
+    }
+}
+
+/// A transmit stream of a [`ReplayDevice`].
+pub struct ReplayTxStream<E: StreamSample> {
+    device: ReplayDevice,
+    target: String,
+    num_channels: usize,
+    active: bool,
+    phantom: PhantomData<fn(&[E])>,
+}
+
+impl<E: StreamSample> SdrTxStream<E> for ReplayTxStream<E> {
+    fn num_channels(&self) -> usize {
+        self.num_channels
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        self.device.replay(&self.target, call("mtu", &[]))
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.device
+            .replay::<()>(&self.target, call("activate", &[&time]))?;
+        self.active = true;
+        Ok(())
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.device
+            .replay::<()>(&self.target, call("deactivate", &[&time]))?;
+        self.active = false;
+        Ok(())
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        let num_elems = buffers.first().map_or(0, |b| b.len());
+        self.device.replay(
+            &self.target,
+            call("write", &[&num_elems, &at, &end_burst, &timeout_us]),
+        )
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.device
+            .replay(&self.target, call("read_status_event", &[&timeout_us]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockDevice;
+
+    #[derive(Clone, Default)]
+    struct Buffer(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Buffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// Run a session against a device, collecting everything it returned.
+    fn session<D: SdrDevice>(dev: &D) -> Vec<String> {
+        let mut out = Vec::new();
+        out.push(format!(
+            "{:?}",
+            dev.set_frequency(Direction::Rx, 0, 915e6, ())
+        ));
+        out.push(format!("{:?}", dev.frequency(Direction::Rx, 0)));
+        out.push(format!("{:?}", dev.antennas(Direction::Rx, 0)));
+        out.push(format!("{:?}", dev.set_gain_mode(Direction::Rx, 0, true)));
+        out.push(format!("{:?}", dev.gain_range(Direction::Rx, 0)));
+        out.push(format!("{:?}", dev.read_setting("key with spaces = %")));
+
+        let mut rx = dev.rx_stream::<i16>(&[0]).unwrap();
+        rx.activate(None).unwrap();
+        let mut buf = [0i16; 3];
+        for _ in 0..4 {
+            let meta = rx.read_with_meta(&mut [&mut buf], 1000);
+            out.push(format!("{meta:?} {buf:?}"));
+        }
+        out.push(format!("{:?}", rx.read_status_event(0)));
+
+        let mut tx = dev.tx_stream::<u8>(&[0]).unwrap();
+        tx.activate(None).unwrap();
+        out.push(format!("{:?}", tx.write_all(&[&[1; 5000]], None, true, 0)));
+        out
+    }
+
+    fn mock() -> MockDevice {
+        let dev = MockDevice::new().with_antennas(Direction::Rx, &["TX/RX", "RX2"]);
+        dev.write_setting("key with spaces = %", "a\nb").unwrap();
+        dev.queue_rx_with_meta(
+            &[&[1i16, -2, 3, 4][..]],
+            StreamFlags::END_BURST,
+            Some(HardwareTime::from_ns(-7)),
+        );
+        dev.queue_rx_error(ErrorCode::Overflow);
+        dev.queue_status_event(
+            Direction::Rx,
+            StatusEvent::Other {
+                channels: ChannelSet::from_mask(1),
+                time: None,
+                flags: StreamFlags::USER_FLAG0,
+            },
+        );
+        dev
+    }
+
+    #[test]
+    fn record_replay() {
+        let buffer = Buffer::default();
+        let recording = RecordingDevice::new(mock(), buffer.clone()).unwrap();
+        let recorded = session(&recording);
+        recording.flush().unwrap();
+
+        let text = buffer.0.lock().unwrap().clone();
+        let replay = ReplayDevice::read_from(&text[..]).unwrap();
+        assert_eq!(session(&replay), recorded);
+        replay.finish().unwrap();
+
+        assert!(recorded[3].contains("NotSupported"));
+        assert!(recorded[8].contains("Overflow"));
+    }
+
+    #[test]
+    fn divergence() {
+        let buffer = Buffer::default();
+        let recording = RecordingDevice::new(mock(), buffer.clone()).unwrap();
+        session(&recording);
+
+        let text = buffer.0.lock().unwrap().clone();
+        let replay = ReplayDevice::read_from(&text[..]).unwrap();
+        replay.set_frequency(Direction::Rx, 0, 915e6, ()).unwrap();
+        assert!(replay.frequency(Direction::Tx, 0).is_err());
+        assert!(replay.frequency(Direction::Rx, 0).is_err());
+        assert!(replay.finish().is_err());
+
+        let replay = ReplayDevice::read_from(&text[..]).unwrap();
+        replay.set_frequency(Direction::Rx, 0, 915e6, ()).unwrap();
+        let err = replay.finish().unwrap_err();
+        assert!(err.message.contains("line 3"), "{}", err.message);
+    }
+
+    #[test]
+    fn values() {
+        fn roundtrip<T: Value>(value: T) -> T {
+            let mut out = String::new();
+            value.encode(&mut out);
+            let mut tokens = out.split_ascii_whitespace();
+            let decoded = T::decode(&mut tokens).unwrap();
+            assert!(tokens.next().is_none());
+            decoded
+        }
+
+        for s in ["", "-", "%", "a b", "x = y", "ünï\n"] {
+            assert_eq!(roundtrip(s.to_owned()), s);
+        }
+        for x in [0.1, -1e300, f64::INFINITY, 5e-324] {
+            assert_eq!(roundtrip(x), x);
+        }
+        assert_eq!(
+            roundtrip(vec![Some((1u32, "a".to_owned())), None]),
+            [Some((1, "a".into())), None]
+        );
+        let event = StatusEvent::Underflow {
+            channels: ChannelSet::from_mask(0b11),
+            time: Some(HardwareTime::from_ns(1)),
+        };
+        assert_eq!(roundtrip(event), event);
+    }
+}
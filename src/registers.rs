@@ -0,0 +1,434 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+
+use crate::{Device, Error, ErrorCode};
+
+/// A named bitfield within a [`Register`].
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Bitfield {
+    /// The name of the field.
+    pub name: String,
+
+    /// The bit position of the least significant bit of the field.
+    pub offset: u32,
+
+    /// The number of bits in the field.
+    pub width: u32,
+}
+
+impl Bitfield {
+    /// Get the mask of the field's bits within the register.
+    pub fn mask(&self) -> u32 {
+        let bits = if self.width >= 32 {
+            u32::MAX
+        } else {
+            (1 << self.width) - 1
+        };
+        bits << self.offset
+    }
+
+    /// Extract the field's value from a register value.
+    pub fn extract(&self, register: u32) -> u32 {
+        (register & self.mask()) >> self.offset
+    }
+
+    /// Replace the field's bits in a register value with `value`.
+    ///
+    /// Returns `None` if `value` doesn't fit in the field.
+    pub fn insert(&self, register: u32, value: u32) -> Option<u32> {
+        let shifted = value.checked_shl(self.offset)?;
+        if shifted >> self.offset != value || shifted & !self.mask() != 0 {
+            return None;
+        }
+        Some((register & !self.mask()) | shifted)
+    }
+}
+
+/// A named register and its bitfields.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Register {
+    /// The name of the register.
+    pub name: String,
+
+    /// The register address on its interface.
+    pub address: u32,
+
+    /// The bitfields of the register.
+    pub fields: Vec<Bitfield>,
+}
+
+/// A declarative description of the registers on one register interface of a device.
+///
+/// Build a map with [`register`](RegisterMap::register) and [`field`](RegisterMap::field),
+/// then [`bind`](RegisterMap::bind) it to a device to access the registers by name.
+///
+/// # Example
+/// ```no_run
+/// use soapysdr::RegisterMap;
+/// # let dev = soapysdr::Device::new("driver=lime").unwrap();
+/// let regs = RegisterMap::new("BBIC")
+///     .register("CTRL", 0x0020)
+///     .field("ENABLE", 0, 1)
+///     .field("MODE", 4, 3)
+///     .register("STATUS", 0x0021)
+///     .bind(&dev)
+///     .unwrap();
+/// regs.write_field("CTRL", "MODE", 5).unwrap();
+/// println!("status: {:#x}", regs.read("STATUS").unwrap());
+/// ```
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RegisterMap {
+    interface: String,
+    registers: Vec<Register>,
+}
+
+impl RegisterMap {
+    /// Create an empty map of the register interface `interface`, as listed by
+    /// [`Device::list_register_interfaces`].
+    pub fn new<S: Into<String>>(interface: S) -> RegisterMap {
+        RegisterMap {
+            interface: interface.into(),
+            registers: Vec::new(),
+        }
+    }
+
+    /// Add a register.
+    pub fn register<S: Into<String>>(mut self, name: S, address: u32) -> RegisterMap {
+        self.registers.push(Register {
+            name: name.into(),
+            address,
+            fields: Vec::new(),
+        });
+        self
+    }
+
+    /// Add a bitfield of `width` bits starting at bit `offset` to the most recently added
+    /// register.
+    ///
+    /// # Panics
+    ///  * If no register has been added.
+    ///  * If the field doesn't fit in 32 bits.
+    pub fn field<S: Into<String>>(mut self, name: S, offset: u32, width: u32) -> RegisterMap {
+        assert!(
+            width > 0 && offset.checked_add(width).is_some_and(|end| end <= 32),
+            "Bitfield must fit in a 32-bit register"
+        );
+        let register = self
+            .registers
+            .last_mut()
+            .expect("A register must be added before its fields");
+        register.fields.push(Bitfield {
+            name: name.into(),
+            offset,
+            width,
+        });
+        self
+    }
+
+    /// Get the name of the register interface.
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// Get the registers in the map.
+    pub fn registers(&self) -> &[Register] {
+        &self.registers
+    }
+
+    /// Look up a register by name.
+    pub fn get(&self, name: &str) -> Option<&Register> {
+        self.registers.iter().find(|r| r.name == name)
+    }
+
+    /// Bind the map to a device, checking that the device has the register interface.
+    pub fn bind(self, device: &Device) -> Result<RegisterBank, Error> {
+        if !device.list_register_interfaces()?.contains(&self.interface) {
+            return Err(Error {
+                code: ErrorCode::NotSupported,
+                message: format!("Device has no register interface {:?}", self.interface),
+            });
+        }
+        Ok(RegisterBank {
+            device: device.clone(),
+            map: self,
+        })
+    }
+
+    fn lookup(&self, name: &str) -> Result<&Register, Error> {
+        self.get(name).ok_or_else(|| Error {
+            code: ErrorCode::Other,
+            message: format!("Unknown register {:?} on {:?}", name, self.interface),
+        })
+    }
+
+    fn lookup_field(&self, register: &str, field: &str) -> Result<(&Register, &Bitfield), Error> {
+        let reg = self.lookup(register)?;
+        let bitfield = reg
+            .fields
+            .iter()
+            .find(|f| f.name == field)
+            .ok_or_else(|| Error {
+                code: ErrorCode::Other,
+                message: format!("Unknown field {:?} of register {:?}", field, register),
+            })?;
+        Ok((reg, bitfield))
+    }
+}
+
+/// A [`RegisterMap`] bound to a device, for reading and writing registers by name.
+///
+/// To obtain a RegisterBank, call [`RegisterMap::bind`].
+#[derive(Clone)]
+pub struct RegisterBank {
+    device: Device,
+    map: RegisterMap,
+}
+
+impl RegisterBank {
+    /// Get the register map.
+    pub fn map(&self) -> &RegisterMap {
+        &self.map
+    }
+
+    /// Read a register by name.
+    pub fn read(&self, register: &str) -> Result<u32, Error> {
+        let reg = self.map.lookup(register)?;
+        self.device
+            .read_register(self.map.interface.as_str(), reg.address)
+    }
+
+    /// Write a register by name.
+    pub fn write(&self, register: &str, value: u32) -> Result<(), Error> {
+        let reg = self.map.lookup(register)?;
+        self.device
+            .write_register(self.map.interface.as_str(), reg.address, value)
+    }
+
+    /// Read a bitfield of a register.
+    pub fn read_field(&self, register: &str, field: &str) -> Result<u32, Error> {
+        let (reg, bitfield) = self.map.lookup_field(register, field)?;
+        let value = self
+            .device
+            .read_register(self.map.interface.as_str(), reg.address)?;
+        Ok(bitfield.extract(value))
+    }
+
+    /// Write a bitfield of a register, leaving its other bits unchanged.
+    ///
+    /// This is a read-modify-write of the whole register. Returns an error without writing if
+    /// `value` doesn't fit in the field.
+    pub fn write_field(&self, register: &str, field: &str, value: u32) -> Result<(), Error> {
+        let (reg, bitfield) = self.map.lookup_field(register, field)?;
+        let interface = self.map.interface.as_str();
+        let current = self.device.read_register(interface, reg.address)?;
+        let new = bitfield.insert(current, value).ok_or_else(|| Error {
+            code: ErrorCode::Other,
+            message: format!(
+                "Value {:#x} does not fit in {}-bit field {:?} of register {:?}",
+                value, bitfield.width, field, register
+            ),
+        })?;
+        self.device.write_register(interface, reg.address, new)
+    }
+
+    /// Read a block of `length` words starting at `address` on the bank's interface.
+    pub fn dump(&self, address: u32, length: usize) -> Result<RegisterDump, Error> {
+        self.device
+            .dump_registers(self.map.interface.as_str(), address, length)
+    }
+}
+
+/// A block of register words read from a device, which can be saved to a file and restored.
+///
+/// Returned by [`Device::dump_registers`] and [`RegisterBank::dump`].
+///
+/// The file format is text: an `interface` line, an `address` line, and one hexadecimal word
+/// per line. Lines starting with `#` are comments.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RegisterDump {
+    /// The register interface the block was read from.
+    pub interface: String,
+
+    /// The start address of the block.
+    pub address: u32,
+
+    /// The words of the block.
+    pub words: Vec<u32>,
+}
+
+impl RegisterDump {
+    /// Write the dump in its text format.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        writeln!(w, "# SoapySDR register dump")?;
+        writeln!(w, "interface {}", self.interface)?;
+        writeln!(w, "address {:#010x}", self.address)?;
+        for word in &self.words {
+            writeln!(w, "{:#010x}", word)?;
+        }
+        w.flush()
+    }
+
+    /// Read a dump in its text format.
+    pub fn read_from<R: BufRead>(r: R) -> io::Result<RegisterDump> {
+        fn invalid(msg: String) -> io::Error {
+            io::Error::new(io::ErrorKind::InvalidData, msg)
+        }
+
+        fn parse_hex(s: &str) -> io::Result<u32> {
+            let digits = s
+                .strip_prefix("0x")
+                .or_else(|| s.strip_prefix("0X"))
+                .unwrap_or(s);
+            u32::from_str_radix(digits, 16)
+                .map_err(|_| invalid(format!("Invalid hex value {:?}", s)))
+        }
+
+        let mut interface = None;
+        let mut address = None;
+        let mut words = Vec::new();
+        for line in r.lines() {
+            let line = line?;
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix("interface ") {
+                interface = Some(name.trim().to_owned());
+            } else if let Some(addr) = line.strip_prefix("address ") {
+                address = Some(parse_hex(addr.trim())?);
+            } else if address.is_none() {
+                return Err(invalid("Register words before address line".into()));
+            } else {
+                words.push(parse_hex(line)?);
+            }
+        }
+
+        Ok(RegisterDump {
+            interface: interface.ok_or_else(|| invalid("Missing interface line".into()))?,
+            address: address.ok_or_else(|| invalid("Missing address line".into()))?,
+            words,
+        })
+    }
+
+    /// Save the dump to a file.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        self.write_to(BufWriter::new(File::create(path)?))
+    }
+
+    /// Load a dump from a file.
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<RegisterDump> {
+        RegisterDump::read_from(BufReader::new(File::open(path)?))
+    }
+}
+
+impl Device {
+    /// Read a block of `length` words starting at `address` on register interface `name`.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=lime").unwrap();
+    /// let dump = dev.dump_registers("BBIC", 0x0000, 256).unwrap();
+    /// dump.save("bbic.regs").unwrap();
+    /// // ...
+    /// dev.restore_registers(&soapysdr::RegisterDump::load("bbic.regs").unwrap()).unwrap();
+    /// ```
+    pub fn dump_registers(
+        &self,
+        name: &str,
+        address: u32,
+        length: usize,
+    ) -> Result<RegisterDump, Error> {
+        Ok(RegisterDump {
+            interface: name.to_owned(),
+            address,
+            words: self.read_registers(name, address, length)?,
+        })
+    }
+
+    /// Write a block of words previously read with [`Device::dump_registers`] back to the
+    /// device.
+    pub fn restore_registers(&self, dump: &RegisterDump) -> Result<(), Error> {
+        self.write_registers(dump.interface.as_str(), dump.address, &dump.words)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bitfields() {
+        let mode = Bitfield {
+            name: "MODE".into(),
+            offset: 4,
+            width: 3,
+        };
+        assert_eq!(mode.mask(), 0x70);
+        assert_eq!(mode.extract(0xffff_ffd5), 0x5);
+        assert_eq!(mode.insert(0xffff_ffff, 0x2), Some(0xffff_ffaf));
+        assert_eq!(mode.insert(0, 0x8), None);
+
+        let all = Bitfield {
+            name: "ALL".into(),
+            offset: 0,
+            width: 32,
+        };
+        assert_eq!(all.mask(), u32::MAX);
+        assert_eq!(all.insert(0, 0xdead_beef), Some(0xdead_beef));
+
+        let top = Bitfield {
+            name: "TOP".into(),
+            offset: 31,
+            width: 1,
+        };
+        assert_eq!(top.insert(0, 1), Some(0x8000_0000));
+        assert_eq!(top.insert(0, 2), None);
+    }
+
+    #[test]
+    fn map_lookup() {
+        let map = RegisterMap::new("BBIC")
+            .register("CTRL", 0x20)
+            .field("ENABLE", 0, 1)
+            .register("STATUS", 0x21);
+        assert_eq!(map.get("STATUS").unwrap().address, 0x21);
+        assert!(map.get("MISSING").is_none());
+        let (reg, field) = map.lookup_field("CTRL", "ENABLE").unwrap();
+        assert_eq!((reg.address, field.mask()), (0x20, 1));
+        assert!(map.lookup_field("CTRL", "MODE").is_err());
+    }
+
+    #[test]
+    #[should_panic(expected = "fit in a 32-bit register")]
+    fn field_too_wide() {
+        RegisterMap::new("BBIC")
+            .register("CTRL", 0)
+            .field("WIDE", 30, 4);
+    }
+
+    #[test]
+    fn dump_roundtrip() {
+        let dump = RegisterDump {
+            interface: "LMS7002M".into(),
+            address: 0x0020,
+            words: vec![0, 0xdead_beef, 0x1234],
+        };
+        let mut text = Vec::new();
+        dump.write_to(&mut text).unwrap();
+        assert_eq!(
+            String::from_utf8_lossy(&text),
+            "# SoapySDR register dump\ninterface LMS7002M\naddress 0x00000020\n\
+             0x00000000\n0xdeadbeef\n0x00001234\n"
+        );
+        assert_eq!(RegisterDump::read_from(&text[..]).unwrap(), dump);
+    }
+
+    #[test]
+    fn dump_parse_errors() {
+        let err = RegisterDump::read_from(&b"interface X\n0x1\n"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(RegisterDump::read_from(&b"address 0x0\n"[..]).is_err());
+        assert!(RegisterDump::read_from(&b"interface X\naddress 0x0\nzz\n"[..]).is_err());
+    }
+}
@@ -0,0 +1,305 @@
+use std::cell::UnsafeCell;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering, fence};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, Thread};
+use std::time::Instant;
+
+/// Create a single-producer, single-consumer ring buffer of preallocated slots.
+///
+/// Slots are written in place by the [`Producer`] and read in place by the [`Consumer`], so their
+/// contents (e.g. sample buffers) are reused rather than moved between threads.
+///
+/// # Panics
+///  * If `slots` is empty.
+pub(crate) fn ring<T: Send>(slots: Vec<T>) -> (Producer<T>, Consumer<T>) {
+    assert!(!slots.is_empty(), "Capacity must be nonzero");
+    let ring = Arc::new(Ring {
+        slots: slots.into_iter().map(UnsafeCell::new).collect(),
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+        closed: AtomicBool::new(false),
+        waiting: AtomicBool::new(false),
+        waiter: Mutex::new(None),
+    });
+    (Producer { ring: ring.clone() }, Consumer { ring })
+}
+
+/// `head` and `tail` count slots pushed and popped. The slot at index `head % capacity` is owned
+/// by the producer when `head - tail < capacity`, and the slot at index `tail % capacity` is
+/// owned by the consumer when `tail < head`. Only the producer stores `head` and only the
+/// consumer stores `tail`.
+///
+/// A consumer about to block stores its thread in `waiter` and then sets `waiting`, so the
+/// producer knows to unpark it.
+struct Ring<T> {
+    slots: Box<[UnsafeCell<T>]>,
+    head: AtomicUsize,
+    tail: AtomicUsize,
+    closed: AtomicBool,
+    waiting: AtomicBool,
+    waiter: Mutex<Option<Thread>>,
+}
+
+/// Each slot is only accessed by the side that owns it, as described on `Ring`, and there is
+/// exactly one `Producer` and one `Consumer`.
+unsafe impl<T: Send> Sync for Ring<T> {}
+
+impl<T> Ring<T> {
+    fn slot(&self, index: usize) -> *mut T {
+        self.slots[index % self.slots.len()].get()
+    }
+
+    /// Wake the consumer if it is waiting, without blocking.
+    fn notify(&self) {
+        // Pairs with the fence in `Consumer::wait`: either the consumer sees the update that was
+        // just published, or this sees that it is waiting.
+        fence(Ordering::SeqCst);
+        if self.waiting.swap(false, Ordering::Relaxed) {
+            // The consumer only holds the lock while registering, before it sets `waiting`. If
+            // it holds it now, it is registering for another wait and checks for updates after.
+            if let Ok(waiter) = self.waiter.try_lock() {
+                if let Some(thread) = &*waiter {
+                    thread.unpark();
+                }
+            }
+        }
+    }
+}
+
+/// The writing side of a [`ring`].
+///
+/// Dropping the producer closes the ring.
+pub(crate) struct Producer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Producer<T> {
+    /// Get the next free slot, or `None` if the ring is full.
+    ///
+    /// The same slot is returned until it is published with [`push`](Producer::push).
+    pub fn slot(&mut self) -> Option<&mut T> {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        if head - tail < self.ring.slots.len() {
+            Some(unsafe { &mut *self.ring.slot(head) })
+        } else {
+            None
+        }
+    }
+
+    /// Publish the slot returned by [`slot`](Producer::slot) to the consumer.
+    ///
+    /// # Panics
+    ///  * If the ring is full.
+    pub fn push(&mut self) {
+        let head = self.ring.head.load(Ordering::Relaxed);
+        let tail = self.ring.tail.load(Ordering::Acquire);
+        assert!(head - tail < self.ring.slots.len(), "Ring is full");
+        self.ring.head.store(head + 1, Ordering::Release);
+        self.ring.notify();
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.ring.closed.store(true, Ordering::Release);
+        self.ring.notify();
+    }
+}
+
+/// The reading side of a [`ring`].
+pub(crate) struct Consumer<T> {
+    ring: Arc<Ring<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Get the number of slots waiting to be popped.
+    pub fn len(&self) -> usize {
+        let head = self.ring.head.load(Ordering::Acquire);
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        head - tail
+    }
+
+    /// Returns true if the [`Producer`] was dropped.
+    ///
+    /// Slots pushed before it was dropped can still be popped.
+    pub fn is_closed(&self) -> bool {
+        self.ring.closed.load(Ordering::Acquire)
+    }
+
+    /// Get the oldest published slot, or `None` if the ring is empty.
+    pub fn peek(&self) -> Option<&T> {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        if self.ring.head.load(Ordering::Acquire) == tail {
+            return None;
+        }
+        Some(unsafe { &*self.ring.slot(tail) })
+    }
+
+    /// Return the oldest published slot to the producer.
+    ///
+    /// # Panics
+    ///  * If the ring is empty.
+    pub fn pop(&mut self) {
+        let tail = self.ring.tail.load(Ordering::Relaxed);
+        assert!(
+            self.ring.head.load(Ordering::Acquire) != tail,
+            "Ring is empty"
+        );
+        self.ring.tail.store(tail + 1, Ordering::Release);
+    }
+
+    /// Block until a slot is available, the ring is closed, or `deadline` passes. With no
+    /// deadline, wait until a slot is available or the ring is closed.
+    ///
+    /// Returns true if a slot is available.
+    pub fn wait(&self, deadline: Option<Instant>) -> bool {
+        loop {
+            if self.len() > 0 {
+                return true;
+            }
+            if self.is_closed() {
+                // The producer may have pushed a final slot before closing.
+                return self.len() > 0;
+            }
+            let remaining = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) if !remaining.is_zero() => Some(remaining),
+                    _ => return false,
+                },
+                None => None,
+            };
+
+            *self.ring.waiter.lock().unwrap() = Some(thread::current());
+            self.ring.waiting.store(true, Ordering::Relaxed);
+            fence(Ordering::SeqCst);
+            if self.len() == 0 && !self.is_closed() {
+                match remaining {
+                    Some(remaining) => thread::park_timeout(remaining),
+                    None => thread::park(),
+                }
+            }
+            self.ring.waiting.store(false, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn fifo_order_and_capacity() {
+        let (mut tx, mut rx) = ring(vec![0u32; 3]);
+        assert_eq!(rx.len(), 0);
+        assert!(rx.peek().is_none());
+
+        for round in 0..5 {
+            for i in 0..3 {
+                *tx.slot().unwrap() = round * 10 + i;
+                tx.push();
+            }
+            assert!(tx.slot().is_none());
+            assert_eq!(rx.len(), 3);
+
+            for i in 0..3 {
+                assert_eq!(*rx.peek().unwrap(), round * 10 + i);
+                rx.pop();
+            }
+            assert!(rx.peek().is_none());
+        }
+    }
+
+    #[test]
+    fn slot_is_reused_until_pushed() {
+        let (mut tx, rx) = ring(vec![Vec::new(), Vec::new()]);
+        tx.slot().unwrap().push(1);
+        tx.slot().unwrap().push(2);
+        assert!(rx.peek().is_none());
+        tx.push();
+        assert_eq!(rx.peek().unwrap(), &[1, 2]);
+    }
+
+    #[test]
+    #[should_panic(expected = "Ring is full")]
+    fn push_full() {
+        let (mut tx, _rx) = ring(vec![0u8]);
+        tx.push();
+        tx.push();
+    }
+
+    #[test]
+    #[should_panic(expected = "Ring is empty")]
+    fn pop_empty() {
+        let (_tx, mut rx) = ring(vec![0u8]);
+        rx.pop();
+    }
+
+    #[test]
+    fn wait_timeout_and_close() {
+        let (mut tx, rx) = ring(vec![0u8; 2]);
+        assert!(!rx.wait(Some(Instant::now() + Duration::from_millis(10))));
+        assert!(!rx.is_closed());
+
+        tx.push();
+        drop(tx);
+        assert!(rx.is_closed());
+        assert!(rx.wait(Some(Instant::now())));
+        assert!(rx.wait(None));
+    }
+
+    #[test]
+    fn wait_wakes_on_push_and_close() {
+        let (mut tx, mut rx) = ring(vec![0u8; 2]);
+        let deadline = Instant::now() + Duration::from_secs(10);
+
+        let producer = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            *tx.slot().unwrap() = 7;
+            tx.push();
+            thread::sleep(Duration::from_millis(10));
+        });
+
+        assert!(rx.wait(None));
+        assert_eq!(*rx.peek().unwrap(), 7);
+        rx.pop();
+
+        assert!(!rx.wait(Some(deadline)));
+        assert!(rx.is_closed());
+        assert!(Instant::now() < deadline);
+        producer.join().unwrap();
+    }
+
+    #[test]
+    fn two_threads() {
+        const COUNT: u64 = if cfg!(miri) { 200 } else { 100_000 };
+        const LEN: usize = 16;
+
+        let (mut tx, mut rx) = ring(vec![[0u64; LEN]; 4]);
+
+        let producer = thread::spawn(move || {
+            let mut i = 0;
+            while i < COUNT {
+                match tx.slot() {
+                    Some(slot) => {
+                        slot.fill(i);
+                        tx.push();
+                        i += 1;
+                    }
+                    None => thread::yield_now(),
+                }
+            }
+        });
+
+        let deadline = Some(Instant::now() + Duration::from_secs(60));
+        for i in 0..COUNT {
+            assert!(rx.wait(deadline));
+            assert_eq!(rx.peek().unwrap(), &[i; LEN]);
+            rx.pop();
+        }
+        assert!(!rx.wait(deadline));
+        producer.join().unwrap();
+    }
+}
@@ -0,0 +1,1329 @@
+//! Traits abstracting over [`Device`] and its streams.
+//!
+//! Application code written against [`SdrDevice`] instead of [`Device`] can be run against other
+//! implementations, such as the in-memory [`MockDevice`](crate::mock::MockDevice) in unit tests.
+
+use crate::device::write_all;
+use crate::{
+    ArgInfo, Args, Device, Direction, Error, Format, HardwareTime, Range, RxMetadata, RxStream,
+    StatusEvent, StreamSample, TxStream,
+};
+
+/// The control interface of an SDR device, as implemented by [`Device`].
+///
+/// The methods mirror those of [`Device`] and have the same semantics. Helpers layered on top of
+/// `Device`, such as [`Device::at_time`] or [`Device::corrections`], are not part of the trait.
+pub trait SdrDevice {
+    /// The stream type returned by [`rx_stream`](SdrDevice::rx_stream).
+    type RxStream<E: StreamSample>: SdrRxStream<E>;
+
+    /// The stream type returned by [`tx_stream`](SdrDevice::tx_stream).
+    type TxStream<E: StreamSample>: SdrTxStream<E>;
+
+    /// A key that uniquely identifies the device driver.
+    fn driver_key(&self) -> Result<String, Error>;
+
+    /// A key that uniquely identifies the hardware.
+    fn hardware_key(&self) -> Result<String, Error>;
+
+    /// Query a dictionary of available device information.
+    fn hardware_info(&self) -> Result<Args, Error>;
+
+    /// Get the mapping configuration string.
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error>;
+
+    /// Set the frontend mapping of available DSP units to RF frontends.
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error>;
+
+    /// Get a number of channels given the streaming direction
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error>;
+
+    /// Get channel info given the streaming direction
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error>;
+
+    /// Find out if the specified channel is full or half duplex.
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// List the device's sensors.
+    fn list_sensors(&self) -> Result<Vec<String>, Error>;
+
+    /// Get sensor info.
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error>;
+
+    /// Read sensor value.
+    fn read_sensor(&self, key: &str) -> Result<String, Error>;
+
+    /// List the channel's sensors.
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error>;
+
+    /// Get channel sensor info.
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error>;
+
+    /// Read channel sensor value.
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error>;
+
+    /// Query a list of the available stream formats.
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error>;
+
+    /// Get the hardware's native stream format and full-scale value for this channel.
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error>;
+
+    /// Query the argument info description for stream args.
+    fn stream_args_info(&self, direction: Direction, channel: usize)
+    -> Result<Vec<ArgInfo>, Error>;
+
+    /// Initialize an RX stream given a list of channels
+    fn rx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<Self::RxStream<E>, Error>;
+
+    /// Initialize an RX stream given a list of channels and stream arguments.
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<Self::RxStream<E>, Error>;
+
+    /// Initialize a TX stream given a list of channels and stream arguments.
+    fn tx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<Self::TxStream<E>, Error>;
+
+    /// Initialize a TX stream given a list of channels and stream arguments.
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<Self::TxStream<E>, Error>;
+
+    /// Get a list of available antennas to select on a given chain.
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error>;
+
+    /// Set the selected antenna on a chain.
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error>;
+
+    /// Get the selected antenna on a chain.
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error>;
+
+    /// Does the device support automatic DC offset corrections?
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Enable or disable automatic DC offset corrections mode.
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error>;
+
+    /// Returns true if automatic DC offset mode is enabled
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Does the device support frontend DC offset corrections?
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Set the frontend DC offset correction.
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error>;
+
+    /// Get the frontend DC offset correction for (I, Q), 1.0 max
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error>;
+
+    /// Does the device support frontend IQ balance correction?
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Set the frontend IQ balance correction
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error>;
+
+    /// Get the frontend IQ balance correction for (I, Q), 1.0 max
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error>;
+
+    /// Does the device support automatic frontend IQ balance correction?
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Enable or disable automatic frontend IQ balance correction.
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error>;
+
+    /// Returns true if automatic IQ balance correction is enabled
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Does the device support frontend frequency correction?
+    fn has_frequency_correction(&self, direction: Direction, channel: usize)
+    -> Result<bool, Error>;
+
+    /// Set the frontend frequency correction value in parts per million (PPM).
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error>;
+
+    /// Get the frontend frequency correction value in parts per million (PPM).
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error>;
+
+    /// List available amplification elements.
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error>;
+
+    /// Does the device support automatic gain control?
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Enable or disable automatic gain control.
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error>;
+
+    /// Returns true if automatic gain control is enabled
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error>;
+
+    /// Set the overall amplification in a chain.
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error>;
+
+    /// Get the overall value of the gain elements in a chain in dB.
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error>;
+
+    /// Get the overall range of possible gain values.
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error>;
+
+    /// Set the value of a amplification element in a chain.
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error>;
+
+    /// Get the value of an individual amplification element in a chain in dB.
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error>;
+
+    /// Get the range of possible gain values for a specific element.
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error>;
+
+    /// Get the ranges of overall frequency values.
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error>;
+
+    /// Get the overall center frequency of the chain.
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error>;
+
+    /// Set the center frequency of the chain.
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error>;
+
+    /// List available tunable elements in the chain.
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error>;
+
+    /// Get the range of tunable values for the specified element.
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error>;
+
+    /// Get the frequency of a tunable element in the chain.
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error>;
+
+    /// Tune the center frequency of the specified element.
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error>;
+
+    /// Query the argument info description for tune args.
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error>;
+
+    /// Get the baseband sample rate of the chain in samples per second.
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error>;
+
+    /// Set the baseband sample rate of the chain in samples per second.
+    fn set_sample_rate(&self, direction: Direction, channel: usize, rate: f64)
+    -> Result<(), Error>;
+
+    /// Get the range of possible baseband sample rates.
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error>;
+
+    /// Get the list of discrete baseband sample rates supported by the chain.
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error>;
+
+    /// Get the baseband filter width of the chain in Hz
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error>;
+
+    /// Set the baseband filter width of the chain in Hz
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error>;
+
+    /// Get the ranges of possible baseband filter widths.
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error>;
+
+    /// Get the list of discrete baseband filter widths supported by the chain.
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error>;
+
+    /// List clock sources
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error>;
+
+    /// Get the current clock source
+    fn get_clock_source(&self) -> Result<String, Error>;
+
+    /// Set the current clock source
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error>;
+
+    /// Get the current master clock rate
+    fn get_master_clock_rate(&self) -> Result<f64, Error>;
+
+    /// Set the master clock rate of the device in Hz.
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error>;
+
+    /// Get the ranges of available master clock rates in Hz.
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error>;
+
+    /// Set the reference clock rate of the device in Hz.
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error>;
+
+    /// Get the current reference clock rate in Hz
+    fn get_reference_clock_rate(&self) -> Result<f64, Error>;
+
+    /// Get the ranges of available reference clock rates in Hz.
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error>;
+
+    /// List time sources
+    fn list_time_sources(&self) -> Result<Vec<String>, Error>;
+
+    /// Get the current time source
+    fn get_time_source(&self) -> Result<String, Error>;
+
+    /// Set the current time source
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error>;
+
+    /// Check whether there is a given hardware time source.
+    ///
+    /// Hardware time sources are not the same as time sources, at least for UHD devices, which
+    /// support the hardware time sources `"PPS"` and `""` (`None`).
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error>;
+
+    /// Get the current hardware time
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error>;
+
+    /// Set the current hardware timestamp for the given source.
+    ///
+    /// UHD supports the hardware time sources `"CMD"`, `"PPS"` and `"UNKNOWN_PPS"`.
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error>;
+
+    /// Set the time at which subsequent control commands, such as tuning or gain changes, take
+    /// effect, until it is [cleared](Self::clear_command_time).
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error>;
+
+    /// Clear the command time, so that control commands take effect immediately.
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error>;
+
+    /// Get a list of available register interfaces by name
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error>;
+
+    /// Write a register on device given interface name
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error>;
+
+    /// Read a register on device given interface name
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error>;
+
+    /// Write a memory block on the device given interface name
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error>;
+
+    /// Read a memory block of up to `length` words on the device given interface name
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error>;
+
+    /// Write a setting
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error>;
+
+    /// Read a setting
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error>;
+
+    /// Describe the available device settings.
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error>;
+
+    /// Describe a device setting.
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error>;
+
+    /// Describe the available settings of a channel.
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error>;
+
+    /// Describe a setting of a channel.
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error>;
+
+    /// Write a setting of a channel
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error>;
+
+    /// Read a setting of a channel
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error>;
+
+    /// Get a list of available GPIO banks by name.
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error>;
+
+    /// Write the value of a GPIO bank.
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error>;
+
+    /// Write the value of a GPIO bank, modifying only the bits set in `mask`.
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error>;
+
+    /// Read back the value of a GPIO bank.
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error>;
+
+    /// Write the data direction of a GPIO bank.
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error>;
+
+    /// Write the data direction of a GPIO bank, modifying only the bits set in `mask`.
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error>;
+
+    /// Read the data direction of a GPIO bank.
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error>;
+
+    /// Write bytes to an I2C peripheral.
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error>;
+
+    /// Read up to `num_bytes` bytes from an I2C peripheral.
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error>;
+
+    /// Perform a SPI transaction, returning the bits read back.
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error>;
+
+    /// Get a list of available UART devices by name.
+    fn list_uarts(&self) -> Result<Vec<String>, Error>;
+
+    /// Write bytes to a UART device.
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error>;
+
+    /// Read the bytes available from a UART device, waiting up to `timeout_us` microseconds for
+    /// data to arrive.
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error>;
+}
+
+/// A stream open for receiving, as implemented by [`RxStream`].
+pub trait SdrRxStream<E: StreamSample> {
+    /// Get the number of channels in the stream.
+    fn num_channels(&self) -> usize;
+
+    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    fn mtu(&self) -> Result<usize, Error>;
+
+    /// Activate a stream.
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error>;
+
+    /// Fetch the active state of the stream.
+    fn active(&self) -> bool;
+
+    /// Activate a stream to receive a finite burst of `num_samples` samples per channel.
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error>;
+
+    /// Deactivate a stream.
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error>;
+
+    /// Read samples from the stream into the provided buffers, returning the number of samples
+    /// read.
+    fn read(&mut self, buffers: &mut [&mut [E]], timeout_us: i64) -> Result<usize, Error> {
+        self.read_with_meta(buffers, timeout_us)
+            .map(|meta| meta.len)
+    }
+
+    /// Read samples from the stream into the provided buffers, returning the number of samples
+    /// read along with the flags and timestamp reported by the driver.
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error>;
+
+    /// Return timestamp of the last successful `read()` operation.
+    fn time(&self) -> HardwareTime;
+
+    /// Return whether the last successful `read()` operation received the final samples of a
+    /// burst.
+    fn burst_ended(&self) -> bool;
+
+    /// Wait for a status event on the stream, such as an overflow.
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error>;
+}
+
+/// A stream open for transmitting, as implemented by [`TxStream`].
+pub trait SdrTxStream<E: StreamSample> {
+    /// Get the number of channels in the stream.
+    fn num_channels(&self) -> usize;
+
+    /// Get the stream's maximum transmission unit (MTU) in number of elements.
+    fn mtu(&self) -> Result<usize, Error>;
+
+    /// Activate a stream.
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error>;
+
+    /// Fetch the active state of the stream.
+    fn active(&self) -> bool;
+
+    /// Deactivate a stream.
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error>;
+
+    /// Attempt to write samples to the device from the provided buffers, returning the number of
+    /// samples written.
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error>;
+
+    /// Write all samples to the device, calling [`write`](SdrTxStream::write) until the entire
+    /// buffer has been written.
+    fn write_all(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<(), Error> {
+        write_all(buffers, at, 1, |buffers, at| {
+            self.write(buffers, at, end_burst, timeout_us)
+        })
+    }
+
+    /// Wait for a status event on the stream, such as an underflow or burst completion.
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error>;
+}
+
+impl SdrDevice for Device {
+    type RxStream<E: StreamSample> = RxStream<E>;
+    type TxStream<E: StreamSample> = TxStream<E>;
+
+    fn driver_key(&self) -> Result<String, Error> {
+        Device::driver_key(self)
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        Device::hardware_key(self)
+    }
+
+    fn hardware_info(&self) -> Result<Args, Error> {
+        Device::hardware_info(self)
+    }
+
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error> {
+        Device::frontend_mapping(self, direction)
+    }
+
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error> {
+        Device::set_frontend_mapping(self, direction, mapping)
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        Device::num_channels(self, direction)
+    }
+
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error> {
+        Device::channel_info(self, direction, channel)
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::full_duplex(self, direction, channel)
+    }
+
+    fn list_sensors(&self) -> Result<Vec<String>, Error> {
+        Device::list_sensors(self)
+    }
+
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error> {
+        Device::get_sensor_info(self, key)
+    }
+
+    fn read_sensor(&self, key: &str) -> Result<String, Error> {
+        Device::read_sensor(self, key)
+    }
+
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        Device::list_channel_sensors(self, dir, channel)
+    }
+
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error> {
+        Device::get_channel_sensor_info(self, dir, channel, key)
+    }
+
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error> {
+        Device::read_channel_sensor(self, dir, channel, key)
+    }
+
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        Device::stream_formats(self, direction, channel)
+    }
+
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        Device::native_stream_format(self, direction, channel)
+    }
+
+    fn stream_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        Device::stream_args_info(self, direction, channel)
+    }
+
+    fn rx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<Self::RxStream<E>, Error> {
+        Device::rx_stream(self, channels)
+    }
+
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<Self::RxStream<E>, Error> {
+        Device::rx_stream_args(self, channels, args)
+    }
+
+    fn tx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<Self::TxStream<E>, Error> {
+        Device::tx_stream(self, channels)
+    }
+
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<Self::TxStream<E>, Error> {
+        Device::tx_stream_args(self, channels, args)
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        Device::antennas(self, direction, channel)
+    }
+
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error> {
+        Device::set_antenna(self, direction, channel, name)
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        Device::antenna(self, direction, channel)
+    }
+
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::has_dc_offset_mode(self, direction, channel)
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        Device::set_dc_offset_mode(self, direction, channel, automatic)
+    }
+
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::dc_offset_mode(self, direction, channel)
+    }
+
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::has_dc_offset(self, direction, channel)
+    }
+
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error> {
+        Device::set_dc_offset(self, direction, channel, offset_i, offset_q)
+    }
+
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        Device::dc_offset(self, direction, channel)
+    }
+
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::has_iq_balance(self, direction, channel)
+    }
+
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error> {
+        Device::set_iq_balance(self, direction, channel, balance_i, balance_q)
+    }
+
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        Device::iq_balance(self, direction, channel)
+    }
+
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::has_iq_balance_mode(self, direction, channel)
+    }
+
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        Device::set_iq_balance_mode(self, direction, channel, automatic)
+    }
+
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::iq_balance_mode(self, direction, channel)
+    }
+
+    fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        Device::has_frequency_correction(self, direction, channel)
+    }
+
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        Device::set_frequency_correction(self, direction, channel, ppm)
+    }
+
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        Device::frequency_correction(self, direction, channel)
+    }
+
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        Device::list_gains(self, direction, channel)
+    }
+
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::has_gain_mode(self, direction, channel)
+    }
+
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        Device::set_gain_mode(self, direction, channel, automatic)
+    }
+
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        Device::gain_mode(self, direction, channel)
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        Device::set_gain(self, direction, channel, gain)
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        Device::gain(self, direction, channel)
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        Device::gain_range(self, direction, channel)
+    }
+
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error> {
+        Device::set_gain_element(self, direction, channel, name, gain)
+    }
+
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        Device::gain_element(self, direction, channel, name)
+    }
+
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error> {
+        Device::gain_element_range(self, direction, channel, name)
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        Device::frequency_range(self, direction, channel)
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        Device::frequency(self, direction, channel)
+    }
+
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        Device::set_frequency(self, direction, channel, frequency, args)
+    }
+
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        Device::list_frequencies(self, direction, channel)
+    }
+
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error> {
+        Device::component_frequency_range(self, direction, channel, name)
+    }
+
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        Device::component_frequency(self, direction, channel, name)
+    }
+
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        Device::set_component_frequency(self, direction, channel, name, frequency, args)
+    }
+
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        Device::frequency_args_info(self, direction, channel)
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        Device::sample_rate(self, direction, channel)
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        Device::set_sample_rate(self, direction, channel, rate)
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        Device::get_sample_rate_range(self, direction, channel)
+    }
+
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        Device::list_sample_rates(self, direction, channel)
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        Device::bandwidth(self, direction, channel)
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error> {
+        Device::set_bandwidth(self, direction, channel, bandwidth)
+    }
+
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        Device::bandwidth_range(self, direction, channel)
+    }
+
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        Device::list_bandwidths(self, direction, channel)
+    }
+
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
+        Device::list_clock_sources(self)
+    }
+
+    fn get_clock_source(&self) -> Result<String, Error> {
+        Device::get_clock_source(self)
+    }
+
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error> {
+        Device::set_clock_source(self, clock_source)
+    }
+
+    fn get_master_clock_rate(&self) -> Result<f64, Error> {
+        Device::get_master_clock_rate(self)
+    }
+
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        Device::set_master_clock_rate(self, rate)
+    }
+
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        Device::get_master_clock_rates(self)
+    }
+
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        Device::set_reference_clock_rate(self, rate)
+    }
+
+    fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        Device::get_reference_clock_rate(self)
+    }
+
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        Device::get_reference_clock_rates(self)
+    }
+
+    fn list_time_sources(&self) -> Result<Vec<String>, Error> {
+        Device::list_time_sources(self)
+    }
+
+    fn get_time_source(&self) -> Result<String, Error> {
+        Device::get_time_source(self)
+    }
+
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error> {
+        Device::set_time_source(self, time_source)
+    }
+
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error> {
+        Device::has_hardware_time(self, hw_time_source)
+    }
+
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
+        Device::get_hardware_time(self, hw_time_source)
+    }
+
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error> {
+        Device::set_hardware_time(self, hw_time_source, new_time)
+    }
+
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        Device::set_command_time(self, time, what)
+    }
+
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        Device::clear_command_time(self, what)
+    }
+
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
+        Device::list_register_interfaces(self)
+    }
+
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error> {
+        Device::write_register(self, name, address, value)
+    }
+
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error> {
+        Device::read_register(self, name, address)
+    }
+
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error> {
+        Device::write_registers(self, name, address, value)
+    }
+
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        Device::read_registers(self, name, address, length)
+    }
+
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error> {
+        Device::write_setting(self, key, value)
+    }
+
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error> {
+        Device::read_setting(self, key)
+    }
+
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        Device::setting_info(self)
+    }
+
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        Device::setting_info_with_key(self, key)
+    }
+
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        Device::channel_setting_info(self, direction, channel)
+    }
+
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        Device::channel_setting_info_with_key(self, direction, channel, key)
+    }
+
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        Device::write_channel_setting(self, direction, channel, key, value)
+    }
+
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        Device::read_channel_setting(self, direction, channel, key)
+    }
+
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        Device::list_gpio_banks(self)
+    }
+
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        Device::write_gpio(self, bank, value)
+    }
+
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        Device::write_gpio_masked(self, bank, value, mask)
+    }
+
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        Device::read_gpio(self, bank)
+    }
+
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        Device::write_gpio_dir(self, bank, dir)
+    }
+
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        Device::write_gpio_dir_masked(self, bank, dir, mask)
+    }
+
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        Device::read_gpio_dir(self, bank)
+    }
+
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        Device::write_i2c(self, addr, data)
+    }
+
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        Device::read_i2c(self, addr, num_bytes)
+    }
+
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        Device::transact_spi(self, addr, data, num_bits)
+    }
+
+    fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        Device::list_uarts(self)
+    }
+
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        Device::write_uart(self, which, data)
+    }
+
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        Device::read_uart(self, which, timeout_us)
+    }
+}
+
+impl<E: StreamSample> SdrRxStream<E> for RxStream<E> {
+    fn num_channels(&self) -> usize {
+        RxStream::num_channels(self)
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        RxStream::mtu(self)
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        RxStream::activate(self, time)
+    }
+
+    fn active(&self) -> bool {
+        RxStream::active(self)
+    }
+
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        RxStream::activate_burst(self, time, num_samples)
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        RxStream::deactivate(self, time)
+    }
+
+    fn read(&mut self, buffers: &mut [&mut [E]], timeout_us: i64) -> Result<usize, Error> {
+        RxStream::read(self, buffers, timeout_us)
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        RxStream::read_with_meta(self, buffers, timeout_us)
+    }
+
+    fn time(&self) -> HardwareTime {
+        RxStream::time(self)
+    }
+
+    fn burst_ended(&self) -> bool {
+        RxStream::burst_ended(self)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        RxStream::read_status_event(self, timeout_us)
+    }
+}
+
+impl<E: StreamSample> SdrTxStream<E> for TxStream<E> {
+    fn num_channels(&self) -> usize {
+        TxStream::num_channels(self)
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        TxStream::mtu(self)
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        TxStream::activate(self, time)
+    }
+
+    fn active(&self) -> bool {
+        TxStream::active(self)
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        TxStream::deactivate(self, time)
+    }
+
+    fn write(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<usize, Error> {
+        TxStream::write(self, buffers, at, end_burst, timeout_us)
+    }
+
+    fn write_all(
+        &mut self,
+        buffers: &[&[E]],
+        at: Option<HardwareTime>,
+        end_burst: bool,
+        timeout_us: i64,
+    ) -> Result<(), Error> {
+        TxStream::write_all(self, buffers, at, end_burst, timeout_us)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        TxStream::read_status_event(self, timeout_us)
+    }
+}
@@ -0,0 +1,351 @@
+use std::fmt;
+
+use crate::arginfo::ArgType;
+use crate::{ArgInfo, Device, Direction, Error, ErrorCode};
+
+/// The value of a device or channel setting, typed according to its [`ArgType`].
+#[derive(Clone, PartialEq, Debug)]
+pub enum SettingValue {
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+}
+
+impl SettingValue {
+    /// Parse a setting string as returned by the driver into a value of type `data_type`.
+    ///
+    /// Booleans are parsed more strictly than by SoapySDR: `"true"` is true, `"false"` and the
+    /// empty string are false, and a number is true if it is nonzero. Any other string is an
+    /// error, where SoapySDR would treat it as true, so that a misspelled value is caught.
+    ///
+    /// ```
+    /// use soapysdr::{ArgType, SettingValue};
+    /// assert_eq!(SettingValue::parse(ArgType::Bool, "true").unwrap(), SettingValue::Bool(true));
+    /// assert_eq!(SettingValue::parse(ArgType::Int, "42").unwrap(), SettingValue::Int(42));
+    /// assert!(SettingValue::parse(ArgType::Int, "4.2").is_err());
+    /// ```
+    pub fn parse(data_type: ArgType, s: &str) -> Result<SettingValue, Error> {
+        let invalid = || Error {
+            code: ErrorCode::Other,
+            message: format!("Invalid {:?} setting value {:?}", data_type, s),
+        };
+        let trimmed = s.trim();
+        Ok(match data_type {
+            ArgType::Bool => SettingValue::Bool(match trimmed {
+                "" | "false" => false,
+                "true" => true,
+                _ => trimmed.parse::<f64>().map_err(|_| invalid())? != 0.0,
+            }),
+            ArgType::Int => SettingValue::Int(trimmed.parse().map_err(|_| invalid())?),
+            ArgType::Float => SettingValue::Float(trimmed.parse().map_err(|_| invalid())?),
+            ArgType::String => SettingValue::String(s.to_owned()),
+        })
+    }
+
+    /// Get the type of the value.
+    pub fn data_type(&self) -> ArgType {
+        match self {
+            SettingValue::Bool(_) => ArgType::Bool,
+            SettingValue::Int(_) => ArgType::Int,
+            SettingValue::Float(_) => ArgType::Float,
+            SettingValue::String(_) => ArgType::String,
+        }
+    }
+
+    /// Get the value if it is a `Bool`.
+    pub fn as_bool(&self) -> Option<bool> {
+        match *self {
+            SettingValue::Bool(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Get the value if it is an `Int`.
+    pub fn as_int(&self) -> Option<i64> {
+        match *self {
+            SettingValue::Int(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Get the value if it is a `Float` or an `Int`.
+    pub fn as_float(&self) -> Option<f64> {
+        match *self {
+            SettingValue::Float(v) => Some(v),
+            SettingValue::Int(v) => Some(v as f64),
+            _ => None,
+        }
+    }
+
+    /// Get the value if it is a `String`.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            SettingValue::String(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Formats the value as the string passed to the driver.
+impl fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SettingValue::Bool(v) => write!(f, "{}", v),
+            SettingValue::Int(v) => write!(f, "{}", v),
+            SettingValue::Float(v) => write!(f, "{}", v),
+            SettingValue::String(v) => f.write_str(v),
+        }
+    }
+}
+
+impl From<bool> for SettingValue {
+    fn from(v: bool) -> SettingValue {
+        SettingValue::Bool(v)
+    }
+}
+
+impl From<i64> for SettingValue {
+    fn from(v: i64) -> SettingValue {
+        SettingValue::Int(v)
+    }
+}
+
+impl From<i32> for SettingValue {
+    fn from(v: i32) -> SettingValue {
+        SettingValue::Int(v.into())
+    }
+}
+
+impl From<f64> for SettingValue {
+    fn from(v: f64) -> SettingValue {
+        SettingValue::Float(v)
+    }
+}
+
+impl From<&str> for SettingValue {
+    fn from(v: &str) -> SettingValue {
+        SettingValue::String(v.to_owned())
+    }
+}
+
+impl From<String> for SettingValue {
+    fn from(v: String) -> SettingValue {
+        SettingValue::String(v)
+    }
+}
+
+impl ArgInfo {
+    /// Parse a value of this argument according to its [`data_type`](ArgInfo::data_type).
+    pub fn parse_value(&self, s: &str) -> Result<SettingValue, Error> {
+        SettingValue::parse(self.data_type, s)
+    }
+
+    /// Convert `value` to this argument's type and check it against the
+    /// [`options`](ArgInfo::options), if any.
+    ///
+    /// Returns the converted value, or an error if it can't be converted or is not one of the
+    /// options.
+    pub fn check_value(&self, value: &SettingValue) -> Result<SettingValue, Error> {
+        let value = self.parse_value(&value.to_string()).map_err(|_| Error {
+            code: ErrorCode::Other,
+            message: format!(
+                "Setting {:?} expects a {:?} value, not {}",
+                self.key, self.data_type, value
+            ),
+        })?;
+
+        if !self.options.is_empty()
+            && !self.options.iter().any(|(option, _)| {
+                self.parse_value(option)
+                    .map_or_else(|_| *option == value.to_string(), |o| o == value)
+            })
+        {
+            let options: Vec<&str> = self.options.iter().map(|(o, _)| &o[..]).collect();
+            return Err(Error {
+                code: ErrorCode::NotSupported,
+                message: format!(
+                    "{} is not a valid value for setting {:?}; options are {}",
+                    value,
+                    self.key,
+                    options.join(", ")
+                ),
+            });
+        }
+
+        Ok(value)
+    }
+}
+
+impl Device {
+    /// Read a device setting, typed according to its
+    /// [`setting_info_with_key`](Device::setting_info_with_key).
+    ///
+    /// Settings the driver does not describe are returned as [`SettingValue::String`].
+    pub fn setting(&self, key: &str) -> Result<SettingValue, Error> {
+        let info = self.setting_info_with_key(key)?;
+        typed(info.as_ref(), self.read_setting(key)?)
+    }
+
+    /// Write a device setting, after checking the value against its
+    /// [`setting_info_with_key`](Device::setting_info_with_key).
+    ///
+    /// Values of settings the driver does not describe are written unchecked.
+    pub fn set_setting<V: Into<SettingValue>>(&self, key: &str, value: V) -> Result<(), Error> {
+        let info = self.setting_info_with_key(key)?;
+        let value = checked(info.as_ref(), value.into())?;
+        self.write_setting(key, &value.to_string()[..])
+    }
+
+    /// Read a setting of a channel, typed according to its
+    /// [`channel_setting_info_with_key`](Device::channel_setting_info_with_key).
+    ///
+    /// Settings the driver does not describe are returned as [`SettingValue::String`].
+    pub fn channel_setting(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<SettingValue, Error> {
+        let info = self.channel_setting_info_with_key(direction, channel, key)?;
+        typed(
+            info.as_ref(),
+            self.read_channel_setting(direction, channel, key)?,
+        )
+    }
+
+    /// Write a setting of a channel, after checking the value against its
+    /// [`channel_setting_info_with_key`](Device::channel_setting_info_with_key).
+    ///
+    /// Values of settings the driver does not describe are written unchecked.
+    ///
+    /// # Example
+    /// ```no_run
+    /// # let dev = soapysdr::Device::new("driver=lime").unwrap();
+    /// dev.set_channel_setting(soapysdr::Direction::Rx, 1, "biastee", true).unwrap();
+    /// ```
+    pub fn set_channel_setting<V: Into<SettingValue>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+        value: V,
+    ) -> Result<(), Error> {
+        let info = self.channel_setting_info_with_key(direction, channel, key)?;
+        let value = checked(info.as_ref(), value.into())?;
+        self.write_channel_setting(direction, channel, key, &value.to_string()[..])
+    }
+}
+
+fn typed(info: Option<&ArgInfo>, s: String) -> Result<SettingValue, Error> {
+    match info {
+        Some(info) => info.parse_value(&s),
+        None => Ok(SettingValue::String(s)),
+    }
+}
+
+fn checked(info: Option<&ArgInfo>, value: SettingValue) -> Result<SettingValue, Error> {
+    match info {
+        Some(info) => info.check_value(&value),
+        None => Ok(value),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn info(data_type: ArgType, options: &[&str]) -> ArgInfo {
+        ArgInfo {
+            key: "key".into(),
+            value: String::new(),
+            name: None,
+            description: None,
+            units: None,
+            data_type,
+            options: options.iter().map(|&o| (o.to_owned(), None)).collect(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        use SettingValue::*;
+        assert_eq!(SettingValue::parse(ArgType::Bool, "").unwrap(), Bool(false));
+        assert_eq!(
+            SettingValue::parse(ArgType::Bool, "false").unwrap(),
+            Bool(false)
+        );
+        assert_eq!(SettingValue::parse(ArgType::Bool, "1").unwrap(), Bool(true));
+        assert_eq!(
+            SettingValue::parse(ArgType::Bool, "0").unwrap(),
+            Bool(false)
+        );
+        assert!(SettingValue::parse(ArgType::Bool, "yes").is_err());
+        assert_eq!(SettingValue::parse(ArgType::Int, " -7 ").unwrap(), Int(-7));
+        assert_eq!(
+            SettingValue::parse(ArgType::Float, "2.5e3").unwrap(),
+            Float(2500.0)
+        );
+        assert_eq!(
+            SettingValue::parse(ArgType::String, " a b ").unwrap(),
+            String(" a b ".into())
+        );
+    }
+
+    #[test]
+    fn display_roundtrip() {
+        for value in [
+            SettingValue::Bool(true),
+            SettingValue::Int(-12),
+            SettingValue::Float(0.125),
+            SettingValue::String("LNAH".into()),
+        ] {
+            let parsed = SettingValue::parse(value.data_type(), &value.to_string()).unwrap();
+            assert_eq!(parsed, value);
+        }
+    }
+
+    #[test]
+    fn check_type() {
+        let float = info(ArgType::Float, &[]);
+        assert_eq!(
+            float.check_value(&SettingValue::Int(3)).unwrap(),
+            SettingValue::Float(3.0)
+        );
+        assert_eq!(
+            float.check_value(&"1.5".into()).unwrap(),
+            SettingValue::Float(1.5)
+        );
+        assert!(float.check_value(&true.into()).is_err());
+
+        let int = info(ArgType::Int, &[]);
+        assert!(int.check_value(&SettingValue::Float(2.5)).is_err());
+
+        let string = info(ArgType::String, &[]);
+        assert_eq!(
+            string.check_value(&SettingValue::Int(3)).unwrap(),
+            SettingValue::String("3".into())
+        );
+    }
+
+    #[test]
+    fn check_options() {
+        let mode = info(ArgType::String, &["AUTO", "MANUAL"]);
+        assert!(mode.check_value(&"MANUAL".into()).is_ok());
+        let err = mode.check_value(&"manual".into()).unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+        assert!(err.message.contains("AUTO, MANUAL"), "{}", err);
+
+        let decimation = info(ArgType::Int, &["1", "2", "4", "8"]);
+        assert_eq!(
+            decimation.check_value(&4.into()).unwrap(),
+            SettingValue::Int(4)
+        );
+        assert!(decimation.check_value(&3.into()).is_err());
+
+        let gain = info(ArgType::Float, &["0", "10.5"]);
+        assert!(gain.check_value(&10.5.into()).is_ok());
+        assert!(gain.check_value(&SettingValue::Int(0)).is_ok());
+        assert!(gain.check_value(&1.0.into()).is_err());
+    }
+}
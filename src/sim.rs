@@ -0,0 +1,1215 @@
+//! A simulated device that produces synthetic signals, for developing without hardware.
+//!
+//! [`SimDevice`] reports a typical set of antennas, gain elements and tuning ranges, and its
+//! receive streams produce the [`Signal`]s added to it as they would appear through a receiver
+//! tuned with [`set_frequency`](SdrDevice::set_frequency), [`set_gain`](SdrDevice::set_gain),
+//! [`set_sample_rate`](SdrDevice::set_sample_rate) and
+//! [`set_bandwidth`](SdrDevice::set_bandwidth). Stream timestamps, and the device's hardware
+//! time, advance with the number of samples read.
+//!
+//! Reads return as soon as the samples are generated rather than pacing them in real time.
+//!
+//! # Example
+//! ```
+//! use num_complex::Complex;
+//! use soapysdr::sim::{Signal, SimDevice};
+//! use soapysdr::{Direction, SdrDevice, SdrRxStream};
+//!
+//! let dev = SimDevice::new()
+//!     .with_signal(Signal::tone(100.25e6, -20.0))
+//!     .with_signal(Signal::noise(-70.0));
+//! dev.set_frequency(Direction::Rx, 0, 100e6, ()).unwrap();
+//! dev.set_sample_rate(Direction::Rx, 0, 1e6).unwrap();
+//!
+//! let mut stream = dev.rx_stream::<Complex<f32>>(&[0]).unwrap();
+//! stream.activate(None).unwrap();
+//! let mut buf = vec![Complex::new(0.0, 0.0); 1024];
+//! let meta = stream.read_with_meta(&mut [&mut buf], 1_000_000).unwrap();
+//! assert_eq!(meta.len, 1024);
+//! ```
+
+use std::f64::consts::TAU;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::{fs, io};
+
+use num_complex::Complex;
+
+use crate::convert::convert;
+use crate::format::{sample_bytes, sample_bytes_mut};
+use crate::mock::{MockDevice, MockTxStream, Tuning};
+use crate::{
+    ArgInfo, Args, Direction, Error, ErrorCode, Format, HardwareTime, Range, RxMetadata, SdrDevice,
+    SdrRxStream, StatusEvent, StreamFlags, StreamSample,
+};
+
+fn db_to_amplitude(db: f64) -> f64 {
+    10f64.powf(db / 20.0)
+}
+
+#[derive(Clone)]
+enum Source {
+    Tone {
+        frequency: f64,
+        power: f64,
+    },
+    Noise {
+        power: f64,
+    },
+    Recording {
+        samples: Arc<[Complex<f64>]>,
+        sample_rate: f64,
+        frequency: f64,
+    },
+}
+
+/// A signal received by a [`SimDevice`].
+///
+/// Signal powers are in dB relative to full scale, as they appear with a total gain of 0 dB.
+#[derive(Clone)]
+pub struct Signal(Source);
+
+impl Signal {
+    /// A continuous tone at the absolute RF `frequency` in Hz.
+    ///
+    /// Tones outside the receiver's filter bandwidth are not received.
+    pub fn tone(frequency: f64, power: f64) -> Signal {
+        Signal(Source::Tone { frequency, power })
+    }
+
+    /// White Gaussian noise across the whole sample rate.
+    pub fn noise(power: f64) -> Signal {
+        Signal(Source::Noise { power })
+    }
+
+    /// A looped recording of baseband samples taken at `sample_rate`, centered on the RF
+    /// `frequency` in Hz.
+    ///
+    /// The recording is resampled to the stream's sample rate and shifted by the difference
+    /// between its center frequency and the tuned frequency.
+    pub fn recording(samples: Vec<Complex<f64>>, sample_rate: f64, frequency: f64) -> Signal {
+        Signal(Source::Recording {
+            samples: samples.into(),
+            sample_rate,
+            frequency,
+        })
+    }
+
+    /// Load a [recording](Signal::recording) from a file of raw interleaved samples in `format`,
+    /// such as those written by `rtl_sdr` (`CU8`) or `SoapySDRUtil --rate` captures (`CF32`).
+    pub fn file<P: AsRef<Path>>(
+        path: P,
+        format: Format,
+        sample_rate: f64,
+        frequency: f64,
+    ) -> io::Result<Signal> {
+        let data = fs::read(path)?;
+        let mut samples = vec![Complex::new(0.0, 0.0); data.len() / format.size()];
+        let len = convert(format, &data, Format::CF64, sample_bytes_mut(&mut samples))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        samples.truncate(len);
+        Ok(Signal::recording(samples, sample_rate, frequency))
+    }
+}
+
+/// The progress of a stream through a signal.
+#[derive(Clone, Copy, Default)]
+struct Progress {
+    /// Phase of the signal's frequency offset, in cycles.
+    phase: f64,
+
+    /// Position in a recording, in samples.
+    position: f64,
+}
+
+impl Progress {
+    fn rotate(&mut self, step: f64) -> Complex<f64> {
+        let r = Complex::from_polar(1.0, TAU * self.phase);
+        self.phase = (self.phase + step).fract();
+        r
+    }
+}
+
+/// A xorshift64* generator, so that simulated noise is reproducible from a seed.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        // Scramble the seed with SplitMix64, as xorshift needs a non-zero state.
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Rng((z ^ (z >> 31)) | 1)
+    }
+
+    fn next_f64(&mut self) -> f64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        let x = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A pair of independent standard normal values, by the Box-Muller transform.
+    fn gaussian(&mut self) -> Complex<f64> {
+        let r = (-2.0 * (1.0 - self.next_f64()).ln()).sqrt();
+        Complex::from_polar(r, TAU * self.next_f64())
+    }
+}
+
+/// Add the signals as received with `tuning` to `out`.
+fn receive(
+    signals: &[Signal],
+    tuning: &Tuning,
+    progress: &mut [Progress],
+    rng: &mut Rng,
+    out: &mut [Complex<f64>],
+) {
+    let gain = db_to_amplitude(tuning.gain);
+    let passband = tuning.bandwidth.min(tuning.sample_rate) / 2.0;
+
+    for (signal, progress) in signals.iter().zip(progress) {
+        match signal.0 {
+            Source::Tone { frequency, power } => {
+                let offset = frequency - tuning.frequency;
+                if offset.abs() > passband {
+                    continue;
+                }
+                let amplitude = db_to_amplitude(power) * gain;
+                let step = offset / tuning.sample_rate;
+                for s in out.iter_mut() {
+                    *s += progress.rotate(step) * amplitude;
+                }
+            }
+            Source::Noise { power } => {
+                let sigma = db_to_amplitude(power) * gain / 2f64.sqrt();
+                for s in out.iter_mut() {
+                    *s += rng.gaussian() * sigma;
+                }
+            }
+            Source::Recording {
+                ref samples,
+                sample_rate,
+                frequency,
+            } => {
+                if samples.is_empty() {
+                    continue;
+                }
+                let step = (frequency - tuning.frequency) / tuning.sample_rate;
+                let advance = sample_rate / tuning.sample_rate;
+                for s in out.iter_mut() {
+                    *s += samples[progress.position as usize] * progress.rotate(step) * gain;
+                    progress.position = (progress.position + advance) % samples.len() as f64;
+                }
+            }
+        }
+    }
+}
+
+struct Sim {
+    signals: Vec<Signal>,
+    seed: u64,
+    streams: u64,
+}
+
+/// A simulated device producing synthetic receive signals.
+///
+/// Control calls are handled by an inner [`MockDevice`], available from
+/// [`control`](SimDevice::control) to inject errors or inspect the call log and transmitted
+/// samples. Receive streams convert to any complex sample type.
+#[derive(Clone)]
+pub struct SimDevice {
+    control: MockDevice,
+    sim: Arc<Mutex<Sim>>,
+}
+
+impl Default for SimDevice {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimDevice {
+    /// Create a simulated device with one channel in each direction and typical ranges for a
+    /// wideband transceiver.
+    pub fn new() -> SimDevice {
+        let mut control = MockDevice::new()
+            .with_driver_key("sim")
+            .with_hardware_key("sim")
+            .with_hardware_info("origin", "soapysdr::sim")
+            .with_antennas(Direction::Rx, &["TX/RX", "RX2"])
+            .with_antennas(Direction::Tx, &["TX/RX"])
+            .with_gain_elements(
+                Direction::Rx,
+                &[
+                    ("LNA", Range::new(0.0, 30.0, 1.0)),
+                    ("VGA", Range::new(0.0, 40.0, 1.0)),
+                ],
+            )
+            .with_gain_elements(Direction::Tx, &[("PAD", Range::new(0.0, 60.0, 1.0))])
+            .with_gain_mode(Direction::Rx)
+            .with_corrections(Direction::Rx)
+            .with_corrections(Direction::Tx)
+            .with_clock_sources(&["internal", "external"])
+            .with_time_sources(&["internal", "external"]);
+        for direction in [Direction::Rx, Direction::Tx] {
+            control = control
+                .with_frequency_range(direction, vec![Range::new(70e6, 6e9, 0.0)])
+                .with_sample_rate_range(direction, vec![Range::new(200e3, 56e6, 0.0)])
+                .with_bandwidth_range(direction, vec![Range::new(200e3, 56e6, 0.0)]);
+        }
+        SimDevice::from_mock(control)
+    }
+
+    /// Create a simulated device whose configuration and control calls are handled by `control`.
+    pub fn from_mock(control: MockDevice) -> SimDevice {
+        SimDevice {
+            control,
+            sim: Arc::new(Mutex::new(Sim {
+                signals: Vec::new(),
+                seed: 0,
+                streams: 0,
+            })),
+        }
+    }
+
+    /// Add a signal to the received spectrum.
+    pub fn with_signal(self, signal: Signal) -> SimDevice {
+        self.add_signal(signal);
+        self
+    }
+
+    /// Set the seed of the noise generators of streams opened afterwards.
+    pub fn with_seed(self, seed: u64) -> SimDevice {
+        self.sim.lock().unwrap().seed = seed;
+        self
+    }
+
+    /// Add a signal to the received spectrum, including for streams that are already open.
+    pub fn add_signal(&self, signal: Signal) {
+        self.sim.lock().unwrap().signals.push(signal);
+    }
+
+    /// Remove all signals from the received spectrum.
+    pub fn clear_signals(&self) {
+        self.sim.lock().unwrap().signals.clear();
+    }
+
+    /// Get the mock device handling control calls.
+    pub fn control(&self) -> &MockDevice {
+        &self.control
+    }
+}
+
+impl SdrDevice for SimDevice {
+    type RxStream<E: StreamSample> = SimRxStream<E>;
+    type TxStream<E: StreamSample> = MockTxStream<E>;
+
+    fn rx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<SimRxStream<E>, Error> {
+        self.rx_stream_args(channels, ())
+    }
+
+    fn rx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<SimRxStream<E>, Error> {
+        let args = args.into().to_string();
+        self.control
+            .log_call("rx_stream", &[&E::STREAM_FORMAT, &channels, &args])?;
+        let channels = self.control.stream_channels(Direction::Rx, channels)?;
+
+        // Check that samples can be converted to the stream format.
+        convert(Format::CF64, &[], E::STREAM_FORMAT, &mut [])?;
+
+        let seed = {
+            let mut sim = self.sim.lock().unwrap();
+            sim.streams += 1;
+            sim.seed.wrapping_add(sim.streams)
+        };
+        Ok(SimRxStream {
+            device: self.clone(),
+            progress: vec![Vec::new(); channels.len()],
+            channels,
+            active: false,
+            burst: None,
+            base_time: HardwareTime::ZERO,
+            base_rate: 0.0,
+            samples: 0,
+            last: RxMetadata {
+                len: 0,
+                flags: StreamFlags::empty(),
+                raw_time: HardwareTime::ZERO,
+            },
+            rng: Rng::new(seed),
+            scratch: Vec::new(),
+            phantom: PhantomData,
+        })
+    }
+
+    fn tx_stream<E: StreamSample>(&self, channels: &[usize]) -> Result<MockTxStream<E>, Error> {
+        self.control.tx_stream(channels)
+    }
+
+    fn tx_stream_args<E: StreamSample, A: Into<Args>>(
+        &self,
+        channels: &[usize],
+        args: A,
+    ) -> Result<MockTxStream<E>, Error> {
+        self.control.tx_stream_args(channels, args)
+    }
+
+    fn driver_key(&self) -> Result<String, Error> {
+        self.control.driver_key()
+    }
+
+    fn hardware_key(&self) -> Result<String, Error> {
+        self.control.hardware_key()
+    }
+
+    fn hardware_info(&self) -> Result<Args, Error> {
+        self.control.hardware_info()
+    }
+
+    fn frontend_mapping(&self, direction: Direction) -> Result<String, Error> {
+        self.control.frontend_mapping(direction)
+    }
+
+    fn set_frontend_mapping<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        mapping: S,
+    ) -> Result<(), Error> {
+        self.control.set_frontend_mapping(direction, mapping)
+    }
+
+    fn num_channels(&self, direction: Direction) -> Result<usize, Error> {
+        self.control.num_channels(direction)
+    }
+
+    fn channel_info(&self, direction: Direction, channel: usize) -> Result<Args, Error> {
+        self.control.channel_info(direction, channel)
+    }
+
+    fn full_duplex(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.full_duplex(direction, channel)
+    }
+
+    fn list_sensors(&self) -> Result<Vec<String>, Error> {
+        self.control.list_sensors()
+    }
+
+    fn get_sensor_info(&self, key: &str) -> Result<ArgInfo, Error> {
+        self.control.get_sensor_info(key)
+    }
+
+    fn read_sensor(&self, key: &str) -> Result<String, Error> {
+        self.control.read_sensor(key)
+    }
+
+    fn list_channel_sensors(&self, dir: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.control.list_channel_sensors(dir, channel)
+    }
+
+    fn get_channel_sensor_info(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<ArgInfo, Error> {
+        self.control.get_channel_sensor_info(dir, channel, key)
+    }
+
+    fn read_channel_sensor(
+        &self,
+        dir: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<String, Error> {
+        self.control.read_channel_sensor(dir, channel, key)
+    }
+
+    fn stream_formats(&self, direction: Direction, channel: usize) -> Result<Vec<Format>, Error> {
+        self.control.stream_formats(direction, channel)
+    }
+
+    fn native_stream_format(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<(Format, f64), Error> {
+        self.control.native_stream_format(direction, channel)
+    }
+
+    fn stream_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.control.stream_args_info(direction, channel)
+    }
+
+    fn antennas(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.control.antennas(direction, channel)
+    }
+
+    fn set_antenna<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<(), Error> {
+        self.control.set_antenna(direction, channel, name)
+    }
+
+    fn antenna(&self, direction: Direction, channel: usize) -> Result<String, Error> {
+        self.control.antenna(direction, channel)
+    }
+
+    fn has_dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.has_dc_offset_mode(direction, channel)
+    }
+
+    fn set_dc_offset_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.control
+            .set_dc_offset_mode(direction, channel, automatic)
+    }
+
+    fn dc_offset_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.dc_offset_mode(direction, channel)
+    }
+
+    fn has_dc_offset(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.has_dc_offset(direction, channel)
+    }
+
+    fn set_dc_offset(
+        &self,
+        direction: Direction,
+        channel: usize,
+        offset_i: f64,
+        offset_q: f64,
+    ) -> Result<(), Error> {
+        self.control
+            .set_dc_offset(direction, channel, offset_i, offset_q)
+    }
+
+    fn dc_offset(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.control.dc_offset(direction, channel)
+    }
+
+    fn has_iq_balance(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.has_iq_balance(direction, channel)
+    }
+
+    fn set_iq_balance(
+        &self,
+        direction: Direction,
+        channel: usize,
+        balance_i: f64,
+        balance_q: f64,
+    ) -> Result<(), Error> {
+        self.control
+            .set_iq_balance(direction, channel, balance_i, balance_q)
+    }
+
+    fn iq_balance(&self, direction: Direction, channel: usize) -> Result<(f64, f64), Error> {
+        self.control.iq_balance(direction, channel)
+    }
+
+    fn has_iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.has_iq_balance_mode(direction, channel)
+    }
+
+    fn set_iq_balance_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.control
+            .set_iq_balance_mode(direction, channel, automatic)
+    }
+
+    fn iq_balance_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.iq_balance_mode(direction, channel)
+    }
+
+    fn has_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<bool, Error> {
+        self.control.has_frequency_correction(direction, channel)
+    }
+
+    fn set_frequency_correction(
+        &self,
+        direction: Direction,
+        channel: usize,
+        ppm: f64,
+    ) -> Result<(), Error> {
+        self.control
+            .set_frequency_correction(direction, channel, ppm)
+    }
+
+    fn frequency_correction(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.control.frequency_correction(direction, channel)
+    }
+
+    fn list_gains(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.control.list_gains(direction, channel)
+    }
+
+    fn has_gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.has_gain_mode(direction, channel)
+    }
+
+    fn set_gain_mode(
+        &self,
+        direction: Direction,
+        channel: usize,
+        automatic: bool,
+    ) -> Result<(), Error> {
+        self.control.set_gain_mode(direction, channel, automatic)
+    }
+
+    fn gain_mode(&self, direction: Direction, channel: usize) -> Result<bool, Error> {
+        self.control.gain_mode(direction, channel)
+    }
+
+    fn set_gain(&self, direction: Direction, channel: usize, gain: f64) -> Result<(), Error> {
+        self.control.set_gain(direction, channel, gain)
+    }
+
+    fn gain(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.control.gain(direction, channel)
+    }
+
+    fn gain_range(&self, direction: Direction, channel: usize) -> Result<Range, Error> {
+        self.control.gain_range(direction, channel)
+    }
+
+    fn set_gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        gain: f64,
+    ) -> Result<(), Error> {
+        self.control
+            .set_gain_element(direction, channel, name, gain)
+    }
+
+    fn gain_element<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        self.control.gain_element(direction, channel, name)
+    }
+
+    fn gain_element_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Range, Error> {
+        self.control.gain_element_range(direction, channel, name)
+    }
+
+    fn frequency_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.control.frequency_range(direction, channel)
+    }
+
+    fn frequency(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.control.frequency(direction, channel)
+    }
+
+    fn set_frequency<A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        self.control
+            .set_frequency(direction, channel, frequency, args)
+    }
+
+    fn list_frequencies(&self, direction: Direction, channel: usize) -> Result<Vec<String>, Error> {
+        self.control.list_frequencies(direction, channel)
+    }
+
+    fn component_frequency_range<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<Vec<Range>, Error> {
+        self.control
+            .component_frequency_range(direction, channel, name)
+    }
+
+    fn component_frequency<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+    ) -> Result<f64, Error> {
+        self.control.component_frequency(direction, channel, name)
+    }
+
+    fn set_component_frequency<S: Into<Vec<u8>>, A: Into<Args>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        name: S,
+        frequency: f64,
+        args: A,
+    ) -> Result<(), Error> {
+        self.control
+            .set_component_frequency(direction, channel, name, frequency, args)
+    }
+
+    fn frequency_args_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.control.frequency_args_info(direction, channel)
+    }
+
+    fn sample_rate(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.control.sample_rate(direction, channel)
+    }
+
+    fn set_sample_rate(
+        &self,
+        direction: Direction,
+        channel: usize,
+        rate: f64,
+    ) -> Result<(), Error> {
+        self.control.set_sample_rate(direction, channel, rate)
+    }
+
+    fn get_sample_rate_range(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<Range>, Error> {
+        self.control.get_sample_rate_range(direction, channel)
+    }
+
+    fn list_sample_rates(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.control.list_sample_rates(direction, channel)
+    }
+
+    fn bandwidth(&self, direction: Direction, channel: usize) -> Result<f64, Error> {
+        self.control.bandwidth(direction, channel)
+    }
+
+    fn set_bandwidth(
+        &self,
+        direction: Direction,
+        channel: usize,
+        bandwidth: f64,
+    ) -> Result<(), Error> {
+        self.control.set_bandwidth(direction, channel, bandwidth)
+    }
+
+    fn bandwidth_range(&self, direction: Direction, channel: usize) -> Result<Vec<Range>, Error> {
+        self.control.bandwidth_range(direction, channel)
+    }
+
+    fn list_bandwidths(&self, direction: Direction, channel: usize) -> Result<Vec<f64>, Error> {
+        self.control.list_bandwidths(direction, channel)
+    }
+
+    fn list_clock_sources(&self) -> Result<Vec<String>, Error> {
+        self.control.list_clock_sources()
+    }
+
+    fn get_clock_source(&self) -> Result<String, Error> {
+        self.control.get_clock_source()
+    }
+
+    fn set_clock_source<S: Into<Vec<u8>>>(&self, clock_source: S) -> Result<(), Error> {
+        self.control.set_clock_source(clock_source)
+    }
+
+    fn get_master_clock_rate(&self) -> Result<f64, Error> {
+        self.control.get_master_clock_rate()
+    }
+
+    fn set_master_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.control.set_master_clock_rate(rate)
+    }
+
+    fn get_master_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        self.control.get_master_clock_rates()
+    }
+
+    fn set_reference_clock_rate(&self, rate: f64) -> Result<(), Error> {
+        self.control.set_reference_clock_rate(rate)
+    }
+
+    fn get_reference_clock_rate(&self) -> Result<f64, Error> {
+        self.control.get_reference_clock_rate()
+    }
+
+    fn get_reference_clock_rates(&self) -> Result<Vec<Range>, Error> {
+        self.control.get_reference_clock_rates()
+    }
+
+    fn list_time_sources(&self) -> Result<Vec<String>, Error> {
+        self.control.list_time_sources()
+    }
+
+    fn get_time_source(&self) -> Result<String, Error> {
+        self.control.get_time_source()
+    }
+
+    fn set_time_source<S: Into<Vec<u8>>>(&self, time_source: S) -> Result<(), Error> {
+        self.control.set_time_source(time_source)
+    }
+
+    fn has_hardware_time(&self, hw_time_source: Option<&str>) -> Result<bool, Error> {
+        self.control.has_hardware_time(hw_time_source)
+    }
+
+    fn get_hardware_time(&self, hw_time_source: Option<&str>) -> Result<HardwareTime, Error> {
+        self.control.get_hardware_time(hw_time_source)
+    }
+
+    fn set_hardware_time(
+        &self,
+        hw_time_source: Option<&str>,
+        new_time: HardwareTime,
+    ) -> Result<(), Error> {
+        self.control.set_hardware_time(hw_time_source, new_time)
+    }
+
+    fn set_command_time(&self, time: HardwareTime, what: Option<&str>) -> Result<(), Error> {
+        self.control.set_command_time(time, what)
+    }
+
+    fn clear_command_time(&self, what: Option<&str>) -> Result<(), Error> {
+        self.control.clear_command_time(what)
+    }
+
+    fn list_register_interfaces(&self) -> Result<Vec<String>, Error> {
+        self.control.list_register_interfaces()
+    }
+
+    fn write_register<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: u32,
+    ) -> Result<(), Error> {
+        self.control.write_register(name, address, value)
+    }
+
+    fn read_register<S: Into<Vec<u8>>>(&self, name: S, address: u32) -> Result<u32, Error> {
+        self.control.read_register(name, address)
+    }
+
+    fn write_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        value: &[u32],
+    ) -> Result<(), Error> {
+        self.control.write_registers(name, address, value)
+    }
+
+    fn read_registers<S: Into<Vec<u8>>>(
+        &self,
+        name: S,
+        address: u32,
+        length: usize,
+    ) -> Result<Vec<u32>, Error> {
+        self.control.read_registers(name, address, length)
+    }
+
+    fn write_setting<S: Into<Vec<u8>>>(&self, key: S, value: S) -> Result<(), Error> {
+        self.control.write_setting(key, value)
+    }
+
+    fn read_setting<S: Into<Vec<u8>>>(&self, key: S) -> Result<String, Error> {
+        self.control.read_setting(key)
+    }
+
+    fn setting_info(&self) -> Result<Vec<ArgInfo>, Error> {
+        self.control.setting_info()
+    }
+
+    fn setting_info_with_key(&self, key: &str) -> Result<Option<ArgInfo>, Error> {
+        self.control.setting_info_with_key(key)
+    }
+
+    fn channel_setting_info(
+        &self,
+        direction: Direction,
+        channel: usize,
+    ) -> Result<Vec<ArgInfo>, Error> {
+        self.control.channel_setting_info(direction, channel)
+    }
+
+    fn channel_setting_info_with_key(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: &str,
+    ) -> Result<Option<ArgInfo>, Error> {
+        self.control
+            .channel_setting_info_with_key(direction, channel, key)
+    }
+
+    fn write_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+        value: S,
+    ) -> Result<(), Error> {
+        self.control
+            .write_channel_setting(direction, channel, key, value)
+    }
+
+    fn read_channel_setting<S: Into<Vec<u8>>>(
+        &self,
+        direction: Direction,
+        channel: usize,
+        key: S,
+    ) -> Result<String, Error> {
+        self.control.read_channel_setting(direction, channel, key)
+    }
+
+    fn list_gpio_banks(&self) -> Result<Vec<String>, Error> {
+        self.control.list_gpio_banks()
+    }
+
+    fn write_gpio<S: Into<Vec<u8>>>(&self, bank: S, value: u32) -> Result<(), Error> {
+        self.control.write_gpio(bank, value)
+    }
+
+    fn write_gpio_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        value: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        self.control.write_gpio_masked(bank, value, mask)
+    }
+
+    fn read_gpio<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        self.control.read_gpio(bank)
+    }
+
+    fn write_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S, dir: u32) -> Result<(), Error> {
+        self.control.write_gpio_dir(bank, dir)
+    }
+
+    fn write_gpio_dir_masked<S: Into<Vec<u8>>>(
+        &self,
+        bank: S,
+        dir: u32,
+        mask: u32,
+    ) -> Result<(), Error> {
+        self.control.write_gpio_dir_masked(bank, dir, mask)
+    }
+
+    fn read_gpio_dir<S: Into<Vec<u8>>>(&self, bank: S) -> Result<u32, Error> {
+        self.control.read_gpio_dir(bank)
+    }
+
+    fn write_i2c(&self, addr: i32, data: &[u8]) -> Result<(), Error> {
+        self.control.write_i2c(addr, data)
+    }
+
+    fn read_i2c(&self, addr: i32, num_bytes: usize) -> Result<Vec<u8>, Error> {
+        self.control.read_i2c(addr, num_bytes)
+    }
+
+    fn transact_spi(&self, addr: i32, data: u32, num_bits: usize) -> Result<u32, Error> {
+        self.control.transact_spi(addr, data, num_bits)
+    }
+
+    fn list_uarts(&self) -> Result<Vec<String>, Error> {
+        self.control.list_uarts()
+    }
+
+    fn write_uart<S: Into<Vec<u8>>>(&self, which: S, data: &[u8]) -> Result<(), Error> {
+        self.control.write_uart(which, data)
+    }
+
+    fn read_uart<S: Into<Vec<u8>>>(&self, which: S, timeout_us: i64) -> Result<Vec<u8>, Error> {
+        self.control.read_uart(which, timeout_us)
+    }
+}
+
+/// A receive stream of a [`SimDevice`].
+pub struct SimRxStream<E: StreamSample> {
+    device: SimDevice,
+    channels: Vec<usize>,
+    active: bool,
+    burst: Option<usize>,
+    base_time: HardwareTime,
+    base_rate: f64,
+    samples: i64,
+    last: RxMetadata,
+    progress: Vec<Vec<Progress>>,
+    rng: Rng,
+    scratch: Vec<Complex<f64>>,
+    phantom: PhantomData<fn(&mut [E])>,
+}
+
+impl<E: StreamSample> SimRxStream<E> {
+    fn log_call(&self, method: &str, args: &[&dyn Debug]) -> Result<(), Error> {
+        self.device.control.log_call(method, args)
+    }
+
+    /// Timestamp of the next sample to be read.
+    fn next_time(&self) -> HardwareTime {
+        let offset = HardwareTime::from_secs_f64(self.samples as f64 / self.base_rate);
+        HardwareTime::from_ns(self.base_time.as_ns() + offset.as_ns())
+    }
+
+    fn start(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.base_time = time.unwrap_or_else(|| self.device.control.time());
+        self.base_rate = self.device.control.rx_tuning(self.channels[0])?.sample_rate;
+        self.samples = 0;
+        self.active = true;
+        Ok(())
+    }
+}
+
+impl<E: StreamSample> SdrRxStream<E> for SimRxStream<E> {
+    fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    fn mtu(&self) -> Result<usize, Error> {
+        self.log_call("rx.mtu", &[])?;
+        Ok(self.device.control.mtu())
+    }
+
+    fn activate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.log_call("rx.activate", &[&time])?;
+        self.burst = None;
+        self.start(time)
+    }
+
+    fn active(&self) -> bool {
+        self.active
+    }
+
+    fn activate_burst(
+        &mut self,
+        time: Option<HardwareTime>,
+        num_samples: usize,
+    ) -> Result<(), Error> {
+        self.log_call("rx.activate_burst", &[&time, &num_samples])?;
+        self.burst = Some(num_samples);
+        self.last.flags = StreamFlags::empty();
+        self.start(time)
+    }
+
+    fn deactivate(&mut self, time: Option<HardwareTime>) -> Result<(), Error> {
+        self.log_call("rx.deactivate", &[&time])?;
+        self.active = false;
+        self.burst = None;
+        Ok(())
+    }
+
+    fn read_with_meta(
+        &mut self,
+        buffers: &mut [&mut [E]],
+        timeout_us: i64,
+    ) -> Result<RxMetadata, Error> {
+        assert!(buffers.len() == self.channels.len());
+
+        let num_samples = buffers.iter().map(|b| b.len()).min().unwrap_or(0);
+        self.log_call("rx.read", &[&num_samples, &timeout_us])?;
+        if !self.active {
+            return Err(Error {
+                code: ErrorCode::StreamError,
+                message: "Stream is not active".into(),
+            });
+        }
+        if self.burst == Some(0) {
+            return Err(Error {
+                code: ErrorCode::Timeout,
+                message: "Burst complete".into(),
+            });
+        }
+
+        let mut len = num_samples.min(self.device.control.mtu());
+        let mut flags = StreamFlags::HAS_TIME;
+        if let Some(remaining) = &mut self.burst {
+            len = len.min(*remaining);
+            *remaining -= len;
+            if *remaining == 0 {
+                flags |= StreamFlags::END_BURST;
+            }
+        }
+
+        // Restart the timestamps from the current time if the sample rate changed.
+        let rate = self.device.control.rx_tuning(self.channels[0])?.sample_rate;
+        if rate != self.base_rate {
+            self.base_time = self.next_time();
+            self.base_rate = rate;
+            self.samples = 0;
+        }
+        let time = self.next_time();
+
+        let signals = self.device.sim.lock().unwrap().signals.clone();
+        self.scratch.resize(len, Complex::new(0.0, 0.0));
+        for (i, buffer) in buffers.iter_mut().enumerate() {
+            let tuning = self.device.control.rx_tuning(self.channels[i])?;
+            let progress = &mut self.progress[i];
+            progress.resize(signals.len(), Progress::default());
+
+            self.scratch.fill(Complex::new(0.0, 0.0));
+            receive(
+                &signals,
+                &tuning,
+                progress,
+                &mut self.rng,
+                &mut self.scratch,
+            );
+            convert(
+                Format::CF64,
+                sample_bytes(&self.scratch),
+                E::STREAM_FORMAT,
+                sample_bytes_mut(&mut buffer[..len]),
+            )?;
+        }
+
+        self.samples += len as i64;
+        self.device.control.set_time(self.next_time());
+        self.last = RxMetadata {
+            len,
+            flags,
+            raw_time: time,
+        };
+        Ok(self.last)
+    }
+
+    fn time(&self) -> HardwareTime {
+        self.last.raw_time
+    }
+
+    fn burst_ended(&self) -> bool {
+        self.last.flags.contains(StreamFlags::END_BURST)
+    }
+
+    fn read_status_event(&mut self, timeout_us: i64) -> Result<StatusEvent, Error> {
+        self.log_call("rx.read_status_event", &[&timeout_us])?;
+        Ok(self.device.control.next_status_event(Direction::Rx))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(dev: &SimDevice, len: usize) -> (Vec<Complex<f64>>, RxMetadata) {
+        let mut stream = dev.rx_stream::<Complex<f64>>(&[0]).unwrap();
+        stream.activate(None).unwrap();
+        let mut buf = vec![Complex::new(0.0, 0.0); len];
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        buf.truncate(meta.len);
+        (buf, meta)
+    }
+
+    fn power_db(samples: &[Complex<f64>]) -> f64 {
+        let power = samples.iter().map(|s| s.norm_sqr()).sum::<f64>() / samples.len() as f64;
+        10.0 * power.log10()
+    }
+
+    #[test]
+    fn tone() {
+        let dev = SimDevice::new().with_signal(Signal::tone(100.1e6, -20.0));
+        dev.set_frequency(Direction::Rx, 0, 100e6, ()).unwrap();
+        dev.set_sample_rate(Direction::Rx, 0, 1e6).unwrap();
+        dev.set_gain(Direction::Rx, 0, 10.0).unwrap();
+
+        let (buf, meta) = read(&dev, 1000);
+        assert_eq!(meta.len, 1000);
+        assert!((power_db(&buf) - -10.0).abs() < 1e-6);
+
+        // The tone is 100 kHz above the center, so advances by a tenth of a cycle per sample.
+        let rotation = (buf[1] / buf[0]).arg();
+        assert!((rotation - TAU / 10.0).abs() < 1e-9);
+
+        // Outside the filter bandwidth the tone is not received.
+        dev.set_bandwidth(Direction::Rx, 0, 150e3).unwrap();
+        let (buf, _) = read(&dev, 1000);
+        assert!(buf.iter().all(|s| s.norm() == 0.0));
+    }
+
+    #[test]
+    fn noise() {
+        let dev = SimDevice::new()
+            .with_signal(Signal::noise(-30.0))
+            .with_seed(1);
+        let (a, _) = read(&dev, 4096);
+        assert!((power_db(&a) - -30.0).abs() < 0.3);
+
+        let (b, _) = read(&dev, 100);
+        assert_ne!(a[..100], b[..]);
+
+        let again = SimDevice::new()
+            .with_signal(Signal::noise(-30.0))
+            .with_seed(1);
+        let (c, _) = read(&again, 100);
+        assert_eq!(a[..100], c[..]);
+    }
+
+    #[test]
+    fn recording() {
+        let samples = (0..4).map(|i| Complex::new(i as f64, 0.0)).collect();
+        let dev = SimDevice::new().with_signal(Signal::recording(samples, 500e3, 100e6));
+        dev.set_frequency(Direction::Rx, 0, 100e6, ()).unwrap();
+        dev.set_sample_rate(Direction::Rx, 0, 1e6).unwrap();
+
+        let (buf, _) = read(&dev, 10);
+        let re: Vec<f64> = buf.iter().map(|s| s.re).collect();
+        assert_eq!(re, [0.0, 0.0, 1.0, 1.0, 2.0, 2.0, 3.0, 3.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn timestamps() {
+        let dev = SimDevice::new();
+        dev.set_sample_rate(Direction::Rx, 0, 2e6).unwrap();
+        let mut stream = dev.rx_stream::<Complex<f32>>(&[0]).unwrap();
+        stream.activate(Some(HardwareTime::from_ns(1_000))).unwrap();
+
+        let mut buf = vec![Complex::new(0.0, 0.0); 2000];
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        assert_eq!(meta.time(), Some(HardwareTime::from_ns(1_000)));
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        assert_eq!(meta.time(), Some(HardwareTime::from_ns(1_001_000)));
+
+        dev.set_sample_rate(Direction::Rx, 0, 1e6).unwrap();
+        let meta = stream.read_with_meta(&mut [&mut buf], 0).unwrap();
+        assert_eq!(meta.time(), Some(HardwareTime::from_ns(2_001_000)));
+        assert_eq!(
+            dev.get_hardware_time(None).unwrap(),
+            HardwareTime::from_ns(4_001_000)
+        );
+
+        assert!(dev.rx_stream::<f32>(&[0]).is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use soapysdr_sys::*;
+use std::fmt;
+use std::ops::{Add, AddAssign, Sub, SubAssign};
+use std::time::Duration;
+
+const NS_PER_SEC: i64 = 1_000_000_000;
+
+/// A device timestamp, in nanoseconds on the hardware clock.
+///
+/// This is the time base used by [`Device::get_hardware_time`](crate::Device::get_hardware_time)
+/// and by the timestamps passed to and returned from streams. It is unrelated to host time such
+/// as [`std::time::Instant`].
+///
+/// # Example
+/// ```
+/// use soapysdr::HardwareTime;
+/// use std::time::Duration;
+/// let t = HardwareTime::from_ns(1_500_000_000) + Duration::from_millis(500);
+/// assert_eq!(t.as_ns(), 2_000_000_000);
+/// assert_eq!(t.duration_since(HardwareTime::ZERO), Some(Duration::from_secs(2)));
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Default, Debug)]
+pub struct HardwareTime(i64);
+
+impl HardwareTime {
+    /// The zero time of the hardware clock.
+    pub const ZERO: HardwareTime = HardwareTime(0);
+
+    /// Create a timestamp from a number of nanoseconds.
+    pub const fn from_ns(ns: i64) -> HardwareTime {
+        HardwareTime(ns)
+    }
+
+    /// Get the timestamp in nanoseconds.
+    pub const fn as_ns(self) -> i64 {
+        self.0
+    }
+
+    /// Create a timestamp from a number of seconds, rounded to the nearest nanosecond.
+    pub fn from_secs_f64(secs: f64) -> HardwareTime {
+        HardwareTime((secs * NS_PER_SEC as f64).round() as i64)
+    }
+
+    /// Get the timestamp in seconds.
+    pub fn as_secs_f64(self) -> f64 {
+        self.0 as f64 / NS_PER_SEC as f64
+    }
+
+    /// Convert a tick count of a clock running at `rate` ticks per second to a timestamp.
+    ///
+    /// This uses SoapySDR's conversion, which avoids the rounding error of floating point for
+    /// large tick counts and fractional rates, so it round-trips with
+    /// [`to_ticks`](HardwareTime::to_ticks).
+    pub fn from_ticks(ticks: i64, rate: f64) -> HardwareTime {
+        HardwareTime(unsafe { SoapySDR_ticksToTimeNs(ticks as _, rate) as i64 })
+    }
+
+    /// Convert the timestamp to a tick count of a clock running at `rate` ticks per second.
+    pub fn to_ticks(self, rate: f64) -> i64 {
+        unsafe { SoapySDR_timeNsToTicks(self.0 as _, rate) as i64 }
+    }
+
+    /// Get the timestamp of the sample with index `index` of a stream at `sample_rate` samples
+    /// per second, where sample 0 is at time zero.
+    pub fn from_samples(index: i64, sample_rate: f64) -> HardwareTime {
+        HardwareTime::from_ticks(index, sample_rate)
+    }
+
+    /// Get the index of the sample at this timestamp in a stream at `sample_rate` samples per
+    /// second, where sample 0 is at time zero.
+    pub fn to_samples(self, sample_rate: f64) -> i64 {
+        self.to_ticks(sample_rate)
+    }
+
+    /// Get the time elapsed since `earlier`, or `None` if `earlier` is later than `self`.
+    pub fn duration_since(self, earlier: HardwareTime) -> Option<Duration> {
+        let ns = self.0.checked_sub(earlier.0)?;
+        u64::try_from(ns).ok().map(Duration::from_nanos)
+    }
+
+    /// Add a duration, returning `None` on overflow.
+    pub fn checked_add(self, duration: Duration) -> Option<HardwareTime> {
+        let ns = i64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_add(ns).map(HardwareTime)
+    }
+
+    /// Subtract a duration, returning `None` on overflow.
+    pub fn checked_sub(self, duration: Duration) -> Option<HardwareTime> {
+        let ns = i64::try_from(duration.as_nanos()).ok()?;
+        self.0.checked_sub(ns).map(HardwareTime)
+    }
+}
+
+impl Add<Duration> for HardwareTime {
+    type Output = HardwareTime;
+
+    /// # Panics
+    ///  * If the result overflows.
+    fn add(self, rhs: Duration) -> HardwareTime {
+        self.checked_add(rhs)
+            .expect("overflow when adding duration to hardware time")
+    }
+}
+
+impl AddAssign<Duration> for HardwareTime {
+    fn add_assign(&mut self, rhs: Duration) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Duration> for HardwareTime {
+    type Output = HardwareTime;
+
+    /// # Panics
+    ///  * If the result overflows.
+    fn sub(self, rhs: Duration) -> HardwareTime {
+        self.checked_sub(rhs)
+            .expect("overflow when subtracting duration from hardware time")
+    }
+}
+
+impl SubAssign<Duration> for HardwareTime {
+    fn sub_assign(&mut self, rhs: Duration) {
+        *self = *self - rhs;
+    }
+}
+
+impl fmt::Display for HardwareTime {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let ns = self.0.unsigned_abs();
+        write!(
+            f,
+            "{}{}.{:09}s",
+            sign,
+            ns / NS_PER_SEC as u64,
+            ns % NS_PER_SEC as u64
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let t = HardwareTime::from_ns(1_000);
+        assert_eq!(t + Duration::from_nanos(500), HardwareTime::from_ns(1_500));
+        assert_eq!(t - Duration::from_micros(2), HardwareTime::from_ns(-1_000));
+
+        let mut u = t;
+        u += Duration::from_secs(1);
+        assert_eq!(u.as_ns(), 1_000_001_000);
+        u -= Duration::from_secs(1);
+        assert_eq!(u, t);
+
+        assert_eq!(
+            u.duration_since(HardwareTime::ZERO),
+            Some(Duration::from_nanos(1_000))
+        );
+        assert_eq!(HardwareTime::ZERO.duration_since(u), None);
+        assert_eq!(
+            HardwareTime::from_ns(i64::MAX).checked_add(Duration::from_nanos(1)),
+            None
+        );
+        assert_eq!(HardwareTime::ZERO.checked_sub(Duration::MAX), None);
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(HardwareTime::from_secs_f64(1.25).as_ns(), 1_250_000_000);
+        assert_eq!(HardwareTime::from_ns(-500_000_000).as_secs_f64(), -0.5);
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            HardwareTime::from_ns(1_000_000_001).to_string(),
+            "1.000000001s"
+        );
+        assert_eq!(
+            HardwareTime::from_ns(-1_500_000).to_string(),
+            "-0.001500000s"
+        );
+        assert_eq!(HardwareTime::ZERO.to_string(), "0.000000000s");
+    }
+}
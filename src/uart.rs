@@ -0,0 +1,117 @@
+use std::io;
+
+use crate::{Device, Error, ErrorCode};
+
+/// Default read timeout for a [Uart], in microseconds.
+const DEFAULT_TIMEOUT_US: i64 = 1_000_000;
+
+/// A UART device attached to an SDR, such as a GPSDO.
+///
+/// To obtain a Uart, call [Device::uarts]. The handle implements [`io::Read`] and
+/// [`io::Write`]; reads wait up to the [timeout](Uart::set_timeout_us) for data and fail with
+/// [`io::ErrorKind::TimedOut`] if none arrives. A UART has no end of file, so reads into a
+/// non-empty buffer never return `Ok(0)`; an idle UART is handled by retrying after the timeout.
+///
+/// # Example
+/// ```no_run
+/// use std::io::{BufRead, BufReader, ErrorKind};
+/// # let dev = soapysdr::Device::new("driver=uhd").unwrap();
+/// let uart = dev.uarts().unwrap().into_iter().find(|u| u.name() == "GPSDO").unwrap();
+/// let mut reader = BufReader::new(uart);
+/// let mut line = String::new();
+/// loop {
+///     match reader.read_line(&mut line) {
+///         Ok(_) => {
+///             print!("{}", line);
+///             line.clear();
+///         }
+///         // Nothing arrived in time; any partial line is kept in `line`.
+///         Err(e) if e.kind() == ErrorKind::TimedOut => continue,
+///         Err(e) => panic!("{}", e),
+///     }
+/// }
+/// ```
+pub struct Uart {
+    device: Device,
+    name: String,
+    timeout_us: i64,
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl Uart {
+    pub(crate) fn new(device: Device, name: String) -> Uart {
+        Uart {
+            device,
+            name,
+            timeout_us: DEFAULT_TIMEOUT_US,
+            buf: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Get the name of the UART.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the read timeout in microseconds.
+    pub fn timeout_us(&self) -> i64 {
+        self.timeout_us
+    }
+
+    /// Set the read timeout in microseconds.
+    pub fn set_timeout_us(&mut self, timeout_us: i64) {
+        self.timeout_us = timeout_us;
+    }
+}
+
+fn io_error(e: Error) -> io::Error {
+    let kind = match e.code {
+        ErrorCode::Timeout => io::ErrorKind::TimedOut,
+        ErrorCode::NotSupported => io::ErrorKind::InvalidInput,
+        _ => io::ErrorKind::Other,
+    };
+    io::Error::new(kind, e)
+}
+
+impl io::Read for Uart {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        if out.is_empty() {
+            return Ok(0);
+        }
+
+        if self.pos == self.buf.len() {
+            let data = self
+                .device
+                .read_uart(self.name.as_str(), self.timeout_us)
+                .map_err(io_error)?;
+            if data.is_empty() {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    "No UART data received before timeout",
+                ));
+            }
+            self.buf = data;
+            self.pos = 0;
+        }
+
+        let n = out.len().min(self.buf.len() - self.pos);
+        out[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+        Ok(n)
+    }
+}
+
+impl io::Write for Uart {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.device
+            .write_uart(self.name.as_str(), data)
+            .map_err(io_error)?;
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
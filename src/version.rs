@@ -0,0 +1,265 @@
+use soapysdr_sys::*;
+use std::cmp::Ordering;
+use std::ffi::CStr;
+use std::fmt;
+use std::os::raw::c_char;
+use std::sync::OnceLock;
+
+use crate::{Error, ErrorCode};
+
+/// A version number parsed from a SoapySDR version string.
+///
+/// Versions compare by their numeric components; `extra` (such as a build tag) is ignored for
+/// ordering and equality.
+#[derive(Clone, Debug)]
+pub struct Version {
+    /// Major version number.
+    pub major: u32,
+
+    /// Minor version number.
+    pub minor: u32,
+
+    /// Patch or increment number.
+    pub patch: u32,
+
+    /// Any text following the numeric components, such as `"-3"` or `"-gabc1234"`.
+    pub extra: String,
+}
+
+impl Version {
+    /// Create a version from its numeric components.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Version {
+        Version {
+            major,
+            minor,
+            patch,
+            extra: String::new(),
+        }
+    }
+
+    /// Parse a version string such as `"0.8.1"` or `"0.8.1-build_info"`.
+    ///
+    /// Up to three dot-separated numbers are read; missing components are zero. Returns `None` if
+    /// the string does not start with a number.
+    ///
+    /// ```
+    /// use soapysdr::Version;
+    /// let v = Version::parse("0.8.1-gabc1234").unwrap();
+    /// assert_eq!((v.major, v.minor, v.patch), (0, 8, 1));
+    /// assert_eq!(v.extra, "-gabc1234");
+    /// assert!(v >= Version::new(0, 8, 0));
+    /// ```
+    pub fn parse(s: &str) -> Option<Version> {
+        let mut parts = [0u32; 3];
+        let mut rest = s;
+        for (i, part) in parts.iter_mut().enumerate() {
+            if i > 0 {
+                match rest.strip_prefix('.') {
+                    Some(r) if r.starts_with(|c: char| c.is_ascii_digit()) => rest = r,
+                    _ => break,
+                }
+            }
+            let end = rest
+                .find(|c: char| !c.is_ascii_digit())
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return None;
+            }
+            *part = rest[..end].parse().ok()?;
+            rest = &rest[end..];
+        }
+
+        Some(Version {
+            major: parts[0],
+            minor: parts[1],
+            patch: parts[2],
+            extra: rest.to_owned(),
+        })
+    }
+
+    fn key(&self) -> (u32, u32, u32) {
+        (self.major, self.minor, self.patch)
+    }
+}
+
+impl PartialEq for Version {
+    fn eq(&self, other: &Version) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for Version {}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Version) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Version) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}.{}{}",
+            self.major, self.minor, self.patch, self.extra
+        )
+    }
+}
+
+/// Versions of the SoapySDR library loaded at runtime, returned by [`version`].
+#[derive(Clone, Debug)]
+pub struct LibraryVersion {
+    /// The API version, which determines the available functions.
+    pub api: Version,
+
+    /// The ABI version. Code compiled against a different ABI version is incompatible.
+    pub abi: Version,
+
+    /// The library release version.
+    pub lib: Version,
+
+    /// The unparsed API version string.
+    pub api_str: String,
+
+    /// The unparsed ABI version string.
+    pub abi_str: String,
+
+    /// The unparsed library version string, including any build information.
+    pub lib_str: String,
+}
+
+/// The API version of the SoapySDR headers these bindings were generated from.
+pub const BINDINGS_API_VERSION: Version = Version::new(0, 8, 0);
+
+impl LibraryVersion {
+    /// Check that the runtime API version is at least `min`, returning an error with
+    /// [`ErrorCode::NotSupported`] naming `feature` and the required version if it is not.
+    ///
+    /// The crate builds only against SoapySDR 0.8 or newer, and a library with an older ABI
+    /// can't be loaded in its place, so every function it wraps is always available. Use this for
+    /// features of later releases, such as functions called directly through `soapysdr_sys`.
+    pub fn require_api(&self, min: Version, feature: &str) -> Result<(), Error> {
+        if self.api >= min {
+            Ok(())
+        } else {
+            Err(Error {
+                code: ErrorCode::NotSupported,
+                message: format!(
+                    "{} requires SoapySDR API version {} or newer, but the loaded library is {}",
+                    feature, min, self.api_str
+                ),
+            })
+        }
+    }
+}
+
+unsafe fn static_str(ptr: *const c_char) -> String {
+    unsafe { CStr::from_ptr(ptr).to_string_lossy().into() }
+}
+
+/// Get the versions of the SoapySDR library loaded at runtime.
+///
+/// # Example
+/// ```no_run
+/// let v = soapysdr::version();
+/// println!("SoapySDR {} (API {}, ABI {})", v.lib_str, v.api_str, v.abi_str);
+/// ```
+pub fn version() -> &'static LibraryVersion {
+    static VERSION: OnceLock<LibraryVersion> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        let (api_str, abi_str, lib_str) = unsafe {
+            (
+                static_str(SoapySDR_getAPIVersion()),
+                static_str(SoapySDR_getABIVersion()),
+                static_str(SoapySDR_getLibVersion()),
+            )
+        };
+        let parse = |s: &str| Version::parse(s).unwrap_or(Version::new(0, 0, 0));
+        LibraryVersion {
+            api: parse(&api_str),
+            abi: parse(&abi_str),
+            lib: parse(&lib_str),
+            api_str,
+            abi_str,
+            lib_str,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let v = Version::parse("0.8.1").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (0, 8, 1));
+        assert_eq!(v.extra, "");
+
+        let v = Version::parse("0.8.1-gabc1234").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (0, 8, 1));
+        assert_eq!(v.extra, "-gabc1234");
+
+        let v = Version::parse("0.8-3").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (0, 8, 0));
+        assert_eq!(v.extra, "-3");
+
+        let v = Version::parse("12").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (12, 0, 0));
+
+        let v = Version::parse("1.2.3.4").unwrap();
+        assert_eq!((v.major, v.minor, v.patch), (1, 2, 3));
+        assert_eq!(v.extra, ".4");
+
+        let v = Version::parse("1.x").unwrap();
+        assert_eq!((v.major, v.minor), (1, 0));
+        assert_eq!(v.extra, ".x");
+
+        assert!(Version::parse("").is_none());
+        assert!(Version::parse("v0.8.1").is_none());
+        assert!(Version::parse("99999999999.0").is_none());
+    }
+
+    #[test]
+    fn ordering() {
+        let v = |s| Version::parse(s).unwrap();
+        assert!(v("0.7.2") < v("0.8.0"));
+        assert!(v("0.8.0") < v("0.8.1"));
+        assert!(v("0.9") > v("0.8.10"));
+        assert!(v("1.0.0") > v("0.99.99"));
+        assert_eq!(v("0.8.1-3"), v("0.8.1"));
+        assert_eq!(v("0.8"), Version::new(0, 8, 0));
+        assert_eq!(v("0.8.1-3").to_string(), "0.8.1-3");
+    }
+
+    #[test]
+    fn require_api() {
+        let version = |api: &str| LibraryVersion {
+            api: Version::parse(api).unwrap(),
+            abi: Version::new(0, 8, 0),
+            lib: Version::new(0, 8, 0),
+            api_str: api.into(),
+            abi_str: "0.8".into(),
+            lib_str: "0.8.0".into(),
+        };
+        assert!(
+            version("0.8.0")
+                .require_api(Version::new(0, 8, 0), "Feature")
+                .is_ok()
+        );
+
+        let err = version("0.7.2")
+            .require_api(Version::new(0, 8, 0), "Feature")
+            .unwrap_err();
+        assert_eq!(err.code, ErrorCode::NotSupported);
+        assert!(err.message.contains("Feature"));
+        assert!(err.message.contains("0.8.0"));
+        assert!(err.message.contains("0.7.2"));
+    }
+}